@@ -3,6 +3,7 @@
 #[cfg(feature = "tmf676")]
 use tmflib::tmf676::payment::Payment;
 
+use tmflib::common::money::Money;
 use tmflib::tmf632::individual_v4::Individual;
 use tmflib::tmf637::v4::product::Product;
 use tmflib::HasDescription;
@@ -15,7 +16,7 @@ fn main() {
             HasName,
         };
 
-        let amount = 123.45;
+        let amount = Money::from(123.45);
 
         let product1 = Product::new("Mobile Phone");
         let product2 = Product::new("Phone Case");
@@ -23,14 +24,14 @@ fn main() {
         let party = Individual::new("John Quinton Smith");
         let method = PaymentMethodRefOrValue::default().name("Credit Card");
         let account = AccountRef::default();
-        let item1 = PaymentItem::new(product1).amount(100.0);
-        let item2 = PaymentItem::new(product2).amount(23.45);
+        let item1 = PaymentItem::new(product1).amount(Money::from(100));
+        let item2 = PaymentItem::new(product2).amount(Money::from(23.45));
 
         let payment = Payment::new(method, account)
             .payer(&party)
             .description("Final Payment for device")
-            .amount(amount)
-            .tax(amount / 10.0)
+            .amount(amount.clone())
+            .tax(amount * 0.1)
             .item(item1)
             .item(item2);
 
@@ -0,0 +1,48 @@
+//! Accepted Quote to Product Order
+//!
+
+#![allow(unused_imports)]
+
+use tmflib::common::price::Price;
+use tmflib::common::related_party::RelatedParty;
+#[cfg(all(feature = "tmf620", feature = "build-V4"))]
+use tmflib::tmf620::product_offering::ProductOffering;
+#[cfg(all(feature = "tmf620", feature = "build-V5"))]
+use tmflib::tmf620::product_offering_v5::ProductOffering;
+#[cfg(all(feature = "tmf622", feature = "build-V4"))]
+use tmflib::tmf622::product_order_v4::ProductOrder;
+#[cfg(all(feature = "tmf622", feature = "build-V5"))]
+use tmflib::tmf622::product_order_v5::ProductOrder;
+#[cfg(all(feature = "tmf632", feature = "build-V4"))]
+use tmflib::tmf632::organization_v4::Organization;
+#[cfg(all(feature = "tmf632", feature = "build-V5"))]
+use tmflib::tmf632::organization_v5::Organization;
+use tmflib::tmf648::{quote::Quote, quote_item::QuoteItem, quote_price::QuotePrice};
+use tmflib::{HasName, HasRelatedParty};
+
+fn main() {
+    #[cfg(all(feature = "tmf622", feature = "build-V4"))]
+    {
+        let offer = ProductOffering::new("Business Broadband");
+        let mut item = QuoteItem::new().product(offer).action("add");
+        item.price(
+            QuotePrice::new("Subscription")
+                .price(Price::new_ex(100.0))
+                .period("Monthly"),
+        );
+
+        let mut quote = Quote::new();
+        quote.set_name("My Quote");
+        let _result = quote.add_quote_item(item);
+        let org = Organization::new("A Customer");
+        quote.add_party(RelatedParty::from(&org));
+
+        // Only accepted quotes that have not expired can be ordered
+        match ProductOrder::try_from(quote) {
+            Ok(order) => {
+                dbg!(order);
+            }
+            Err(e) => println!("Could not order quote: {e}"),
+        }
+    }
+}
@@ -66,6 +66,9 @@ pub struct AttachmentRefOrValue {
     /// Size of the attachment
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<AttachmentSize>,
+    /// SHA-256 hash of the content, hex encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
     /// How long is this attachment valid for?
     #[serde(skip_serializing_if = "Option::is_none")]
     pub valid_for: Option<TimePeriod>,
@@ -79,6 +82,28 @@ impl AttachmentRefOrValue {
             ..AttachmentRefOrValue::create()
         }
     }
+
+    /// Create an inline attachment, setting the size and content hash from the content.
+    /// The result only depends on the inputs, no id or validity is generated.
+    pub fn inline(
+        name: impl Into<String>,
+        mime_type: impl Into<String>,
+        content: impl Into<String>,
+    ) -> AttachmentRefOrValue {
+        let content: String = content.into();
+        AttachmentRefOrValue {
+            name: Some(name.into()),
+            attachment_type: Some(AttachmentType::InLine),
+            mime_type: Some(mime_type.into()),
+            size: Some(AttachmentSize {
+                amount: content.len() as f64,
+                units: "bytes".to_string(),
+            }),
+            content_hash: Some(sha256::digest(content.as_str())),
+            content: Some(content),
+            ..Default::default()
+        }
+    }
 }
 
 impl From<&Document> for AttachmentRefOrValue {
@@ -0,0 +1,211 @@
+//! Order Cancellation Module
+//!
+//! # Description
+//! TMF622 (Product Order) and TMF641 (Service Order) share the same order lifecycle, described by
+//! [`OrderStateType`], and both cancel an order through a task resource.
+//! This module holds the state machine shared by those tasks:
+//! - Applying an [`TaskStateType::Acknowledged`] task moves the order to pending cancellation and
+//!   the task to [`TaskStateType::InProgress`]. If the order refuses, the task is
+//!   [`TaskStateType::TerminatedWithError`] with the reason as its completion message.
+//! - Completing an [`TaskStateType::InProgress`] task moves the order to cancelled, records the
+//!   cancellation date and moves the task to [`TaskStateType::Done`].
+//! - A task only acts on the order it references.
+
+use serde::{Deserialize, Serialize};
+
+use super::tmf_error::TMFError;
+use crate::{now, DateTime, HasId};
+
+/// Task State Type
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskStateType {
+    /// Task has been received
+    #[default]
+    Acknowledged,
+    /// Task has been rejected
+    Rejected,
+    /// Task is being processed
+    InProgress,
+    /// Task has been cancelled
+    Cancelled,
+    /// Task has completed
+    Done,
+    /// Task could not be completed
+    TerminatedWithError,
+}
+
+/// Order state shared by Product and Service Orders
+#[derive(Clone, Debug, PartialEq)]
+pub enum OrderStateType {
+    /// Order has been received
+    Acknowledged,
+    /// Order has been rejected
+    Rejected,
+    /// Order is awaiting further information
+    Pending,
+    /// Order is on hold
+    Held,
+    /// Order is being processed
+    InProgress,
+    /// Order has been cancelled
+    Cancelled,
+    /// Order has been completed
+    Completed,
+    /// Order processing has failed
+    Failed,
+    /// Order has been partially completed
+    Partial,
+    /// Cancellation request is being assessed (e.g. PONR)
+    AssessingCancellation,
+    /// Cancellation has been accepted and is being processed
+    PendingCancellation,
+}
+
+impl OrderStateType {
+    /// Returns true if no further state changes are possible
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            OrderStateType::Rejected
+                | OrderStateType::Cancelled
+                | OrderStateType::Completed
+                | OrderStateType::Failed
+                | OrderStateType::Partial
+        )
+    }
+
+    /// Returns true if the order state machine allows moving to the new state
+    pub fn can_transition(&self, to: &OrderStateType) -> bool {
+        use OrderStateType::*;
+        match self {
+            Acknowledged => matches!(
+                to,
+                Rejected
+                    | Pending
+                    | Held
+                    | InProgress
+                    | AssessingCancellation
+                    | PendingCancellation
+            ),
+            Pending | Held => matches!(
+                to,
+                Pending | Held | InProgress | AssessingCancellation | PendingCancellation
+            ),
+            InProgress => matches!(
+                to,
+                Pending
+                    | Held
+                    | Completed
+                    | Failed
+                    | Partial
+                    | AssessingCancellation
+                    | PendingCancellation
+            ),
+            // Cancellation can be refused (e.g. PONR reached) returning the order to processing
+            AssessingCancellation => {
+                matches!(to, InProgress | Held | Pending | PendingCancellation)
+            }
+            PendingCancellation => matches!(to, Cancelled),
+            _ => false,
+        }
+    }
+}
+
+/// Order that can be cancelled by a [`CancelTask`]
+pub trait Cancellable: HasId {
+    /// Move the order to pending cancellation, recording the reason
+    fn pending_cancellation(&mut self, reason: Option<String>) -> Result<(), TMFError>;
+    /// Move the order to cancelled, recording the reason and date
+    fn cancel(&mut self, reason: Option<String>, date: DateTime) -> Result<(), TMFError>;
+}
+
+/// Task resource requesting the cancellation of an order
+pub trait CancelTask: HasId {
+    /// Type of order this task cancels
+    type Order: Cancellable;
+
+    /// Id of the order to be cancelled, if any
+    fn target_id(&self) -> Option<String>;
+    /// Reason the order is being cancelled
+    fn reason(&self) -> Option<String>;
+    /// Current task state
+    fn task_state(&self) -> Option<TaskStateType>;
+    /// Set the task state and completion message
+    fn set_task_state(&mut self, state: TaskStateType, message: Option<String>);
+    /// Record the date the order was cancelled
+    fn set_effective_date(&mut self, date: DateTime);
+
+    /// Apply this cancellation to the target order, moving it to pending cancellation.
+    /// If the order cannot be cancelled the task is terminated with an error.
+    fn apply(&mut self, order: &mut Self::Order) -> Result<(), TMFError> {
+        self.check_target(order)?;
+        if self.task_state().unwrap_or_default() != TaskStateType::Acknowledged {
+            return Err(TMFError::StateError(format!(
+                "Cancellation {} has already been applied",
+                self.get_id()
+            )));
+        }
+        match order.pending_cancellation(self.reason()) {
+            Ok(_) => {
+                self.set_task_state(TaskStateType::InProgress, None);
+                Ok(())
+            }
+            Err(e) => {
+                self.set_task_state(TaskStateType::TerminatedWithError, Some(e.to_string()));
+                Err(e)
+            }
+        }
+    }
+
+    /// Complete the cancellation, moving the target order to cancelled
+    /// and recording the effective cancellation date.
+    fn complete(&mut self, order: &mut Self::Order) -> Result<(), TMFError> {
+        self.check_target(order)?;
+        if self.task_state() != Some(TaskStateType::InProgress) {
+            return Err(TMFError::StateError(format!(
+                "Cancellation {} is not in progress",
+                self.get_id()
+            )));
+        }
+        let date = now();
+        order.cancel(self.reason(), date.clone())?;
+        self.set_effective_date(date);
+        self.set_task_state(TaskStateType::Done, None);
+        Ok(())
+    }
+
+    /// Check this task references the given order
+    fn check_target(&self, order: &Self::Order) -> Result<(), TMFError> {
+        match self.target_id() {
+            Some(id) if id == order.get_id() => Ok(()),
+            Some(id) => Err(TMFError::InvalidReference(format!(
+                "Cancellation targets order {} not {}",
+                id,
+                order.get_id()
+            ))),
+            None => Err(TMFError::InvalidReference(
+                "Cancellation has no target order".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_task_state_deserialize() {
+        let state: TaskStateType = serde_json::from_str("\"terminatedWithError\"").unwrap();
+
+        assert_eq!(state, TaskStateType::TerminatedWithError);
+    }
+
+    #[test]
+    fn test_order_state_transition() {
+        assert!(OrderStateType::AssessingCancellation.can_transition(&OrderStateType::InProgress));
+        assert!(!OrderStateType::PendingCancellation.can_transition(&OrderStateType::InProgress));
+        assert!(OrderStateType::Partial.is_final());
+    }
+}
@@ -0,0 +1,456 @@
+//! Order Item Dependency Module
+//!
+//! # Description
+//! Order items across TMF622 (Product Order), TMF641 (Service Order) and TMF697 (Work Order)
+//! can express relationships to other items within the same order such as "dependsOn" or "reliesOn".
+//! This module builds a dependency graph over those items and produces an [`ExecutionPlan`]
+//! made up of stages, where every item in a stage can be executed in parallel once all
+//! previous stages have completed.
+//! # Validation
+//! - Every item must have an id, and ids must be unique within the order.
+//! - Every dependency must point at an item within the same set of items.
+//! - Dependencies must not form a cycle.
+//! ```
+//! use tmflib::common::dependency::DependencyGraph;
+//! use tmflib::tmf641::service_order_item::{ServiceOrderItem, ServiceOrderItemRelationship};
+//!
+//! let mut first = ServiceOrderItem::default();
+//! first.id = "1".to_string();
+//! let mut second = ServiceOrderItem::default();
+//! second.id = "2".to_string();
+//! second.service_order_item_relationship = Some(vec![ServiceOrderItemRelationship::new("reliesOn", "1")]);
+//!
+//! let graph = DependencyGraph::new(&[first, second]).unwrap();
+//! let plan = graph.execution_plan().unwrap();
+//! assert_eq!(plan.stages.len(), 2);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::tmf_error::TMFError;
+
+/// Relationship types that indicate one order item must wait for another.
+pub const DEPENDENCY_TYPES: [&str; 2] = ["dependsOn", "reliesOn"];
+
+/// Returns true if the relationship type expresses a dependency (case insensitive)
+pub fn is_dependency(relationship_type: &str) -> bool {
+    DEPENDENCY_TYPES
+        .iter()
+        .any(|t| t.eq_ignore_ascii_case(relationship_type.trim()))
+}
+
+/// Relationship from one order item to another item within the same order.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderItemRelationship {
+    /// Id of the related order item
+    pub id: String,
+    /// Type of relationship, e.g. dependsOn, reliesOn, bundles
+    pub relationship_type: String,
+}
+
+impl OrderItemRelationship {
+    /// Create a new relationship to another order item
+    pub fn new(
+        relationship_type: impl Into<String>,
+        id: impl Into<String>,
+    ) -> OrderItemRelationship {
+        OrderItemRelationship {
+            id: id.into(),
+            relationship_type: relationship_type.into(),
+        }
+    }
+}
+
+/// Trait for order items that can take part in a dependency graph
+pub trait HasDependency {
+    /// Id of this item within its order, if set
+    fn get_item_id(&self) -> Option<String>;
+    /// Ids of all items this item depends on
+    fn get_dependencies(&self) -> Vec<String>;
+}
+
+/// Execution state of a single item within an [`ExecutionPlan`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ItemExecutionState {
+    /// Waiting to be executed
+    #[default]
+    Pending,
+    /// Item is being executed
+    InProgress,
+    /// Item has completed successfully
+    Completed,
+    /// Item execution has failed
+    Failed,
+    /// A prerequisite of this item has failed
+    Blocked,
+}
+
+/// Graph of dependencies between the items of an order
+#[derive(Clone, Debug, Default)]
+pub struct DependencyGraph {
+    // Item ids in the order they were supplied
+    items: Vec<String>,
+    // Map of item id to the ids it depends on
+    prerequisites: HashMap<String, Vec<String>>,
+}
+
+impl DependencyGraph {
+    /// Build a graph from a list of order items, validating ids and dependency targets.
+    /// Cycles are detected when the [`ExecutionPlan`] is generated.
+    pub fn new<T: HasDependency>(items: &[T]) -> Result<DependencyGraph, TMFError> {
+        let mut graph = DependencyGraph::default();
+        for item in items {
+            let id = item.get_item_id().ok_or(TMFError::InvalidId(
+                "Order item is missing an id".to_string(),
+            ))?;
+            if graph.prerequisites.contains_key(&id) {
+                return Err(TMFError::InvalidId(format!(
+                    "Duplicate order item id: {id}"
+                )));
+            }
+            let mut deps = item.get_dependencies();
+            deps.sort();
+            deps.dedup();
+            graph.items.push(id.clone());
+            graph.prerequisites.insert(id, deps);
+        }
+        // Now all items are known, check all targets exist
+        for id in graph.items.iter() {
+            for dep in graph.prerequisites[id].iter() {
+                if !graph.prerequisites.contains_key(dep) {
+                    return Err(TMFError::DependencyError(format!(
+                        "Item {id} depends on missing item {dep}"
+                    )));
+                }
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Get the list of item ids in this graph
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    /// Get the items the given item directly depends on
+    pub fn prerequisites(&self, id: &str) -> Option<&Vec<String>> {
+        self.prerequisites.get(id)
+    }
+
+    /// Get the items that directly depend on the given item
+    pub fn dependents(&self, id: &str) -> Vec<String> {
+        self.items
+            .iter()
+            .filter(|i| self.prerequisites[*i].iter().any(|d| d == id))
+            .cloned()
+            .collect()
+    }
+
+    /// Get all items that transitively depend on any of the failed items.
+    /// The failed items themselves are not included.
+    pub fn blocked_by(&self, failed: &[String]) -> Vec<String> {
+        let mut blocked: HashSet<String> = HashSet::new();
+        let mut queue: Vec<String> = failed.to_vec();
+        while let Some(id) = queue.pop() {
+            for dependent in self.dependents(&id) {
+                if !failed.contains(&dependent) && blocked.insert(dependent.clone()) {
+                    queue.push(dependent);
+                }
+            }
+        }
+        // Return in a stable order
+        self.items
+            .iter()
+            .filter(|i| blocked.contains(*i))
+            .cloned()
+            .collect()
+    }
+
+    /// Generate a topologically sorted execution plan.
+    /// Returns an error listing the items involved if a cycle is detected.
+    pub fn execution_plan(&self) -> Result<ExecutionPlan, TMFError> {
+        let mut remaining: Vec<String> = self.items.clone();
+        let mut done: HashSet<String> = HashSet::new();
+        let mut stages = vec![];
+        while !remaining.is_empty() {
+            let (ready, waiting): (Vec<String>, Vec<String>) = remaining
+                .into_iter()
+                .partition(|i| self.prerequisites[i].iter().all(|d| done.contains(d)));
+            if ready.is_empty() {
+                return Err(TMFError::DependencyError(format!(
+                    "Dependency cycle detected between items: {}",
+                    waiting.join(",")
+                )));
+            }
+            done.extend(ready.iter().cloned());
+            stages.push(ready);
+            remaining = waiting;
+        }
+        let state = self
+            .items
+            .iter()
+            .map(|i| (i.clone(), ItemExecutionState::default()))
+            .collect();
+        Ok(ExecutionPlan {
+            stages,
+            graph: self.clone(),
+            state,
+        })
+    }
+}
+
+/// Parallelisable execution plan for the items of an order
+#[derive(Clone, Debug)]
+pub struct ExecutionPlan {
+    /// Stages of item ids, items within a stage have no dependencies on each other
+    pub stages: Vec<Vec<String>>,
+    graph: DependencyGraph,
+    state: HashMap<String, ItemExecutionState>,
+}
+
+impl ExecutionPlan {
+    /// Get the current execution state of an item
+    pub fn get_state(&self, id: &str) -> Option<&ItemExecutionState> {
+        self.state.get(id)
+    }
+
+    /// Items that are pending and have all their prerequisites completed
+    pub fn ready(&self) -> Vec<String> {
+        self.stages
+            .iter()
+            .flatten()
+            .filter(|i| self.state[*i] == ItemExecutionState::Pending)
+            .filter(|i| self.prerequisites_completed(i))
+            .cloned()
+            .collect()
+    }
+
+    /// Mark an item as being executed, only items returned by [`ExecutionPlan::ready`] can start
+    pub fn start(&mut self, id: &str) -> Result<(), TMFError> {
+        self.set_state(id, ItemExecutionState::InProgress)
+    }
+
+    /// Mark a ready or in progress item as completed
+    pub fn complete(&mut self, id: &str) -> Result<(), TMFError> {
+        self.set_state(id, ItemExecutionState::Completed)
+    }
+
+    /// Mark a pending or in progress item as failed, all items depending on it are marked as blocked.
+    /// Returns the list of newly blocked items.
+    pub fn fail(&mut self, id: &str) -> Result<Vec<String>, TMFError> {
+        self.set_state(id, ItemExecutionState::Failed)?;
+        let blocked = self.graph.blocked_by(&[id.to_string()]);
+        let mut out = vec![];
+        for b in blocked {
+            if self.state[&b] == ItemExecutionState::Pending {
+                self.state.insert(b.clone(), ItemExecutionState::Blocked);
+                out.push(b);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Returns true once no item is pending or in progress
+    pub fn is_finished(&self) -> bool {
+        self.state
+            .values()
+            .all(|s| *s != ItemExecutionState::Pending && *s != ItemExecutionState::InProgress)
+    }
+
+    fn prerequisites_completed(&self, id: &str) -> bool {
+        self.graph.prerequisites[id]
+            .iter()
+            .all(|d| self.state[d] == ItemExecutionState::Completed)
+    }
+
+    fn set_state(&mut self, id: &str, state: ItemExecutionState) -> Result<(), TMFError> {
+        let current = self
+            .state
+            .get(id)
+            .ok_or(TMFError::InvalidId(format!("Unknown order item: {id}")))?;
+        let ready = *current == ItemExecutionState::Pending && self.prerequisites_completed(id);
+        let allowed = match (current, &state) {
+            (ItemExecutionState::Blocked, _) => {
+                return Err(TMFError::DependencyError(format!(
+                    "Item {id} is blocked by a failed prerequisite"
+                )));
+            }
+            (ItemExecutionState::Completed | ItemExecutionState::Failed, _) => {
+                return Err(TMFError::DependencyError(format!(
+                    "Item {id} has already finished as {current:?}"
+                )));
+            }
+            (_, ItemExecutionState::InProgress) => ready,
+            (ItemExecutionState::InProgress, ItemExecutionState::Completed) => true,
+            (_, ItemExecutionState::Completed) => ready,
+            (_, ItemExecutionState::Failed) => true,
+            _ => false,
+        };
+        if !allowed {
+            return Err(TMFError::DependencyError(format!(
+                "Item {id} cannot move from {current:?} to {state:?}, prerequisites must complete first"
+            )));
+        }
+        self.state.insert(id.to_string(), state);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct TestItem {
+        id: Option<String>,
+        deps: Vec<String>,
+    }
+
+    impl HasDependency for TestItem {
+        fn get_item_id(&self) -> Option<String> {
+            self.id.clone()
+        }
+        fn get_dependencies(&self) -> Vec<String> {
+            self.deps.clone()
+        }
+    }
+
+    fn item(id: &str, deps: &[&str]) -> TestItem {
+        TestItem {
+            id: Some(id.to_string()),
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_is_dependency() {
+        assert!(is_dependency("reliesOn"));
+        assert!(is_dependency("DEPENDSON"));
+        assert!(!is_dependency("bundles"));
+    }
+
+    #[test]
+    fn test_plan_stages() {
+        let items = vec![
+            item("1", &[]),
+            item("2", &["1"]),
+            item("3", &["1"]),
+            item("4", &["2", "3"]),
+        ];
+        let plan = DependencyGraph::new(&items)
+            .unwrap()
+            .execution_plan()
+            .unwrap();
+
+        assert_eq!(plan.stages.len(), 3);
+        assert_eq!(plan.stages[0], vec!["1".to_string()]);
+        assert_eq!(plan.stages[1], vec!["2".to_string(), "3".to_string()]);
+        assert_eq!(plan.stages[2], vec!["4".to_string()]);
+    }
+
+    #[test]
+    fn test_graph_cycle() {
+        let items = vec![item("1", &["3"]), item("2", &["1"]), item("3", &["2"])];
+        let plan = DependencyGraph::new(&items).unwrap().execution_plan();
+
+        assert!(plan.is_err());
+    }
+
+    #[test]
+    fn test_graph_missing_target() {
+        let items = vec![item("1", &[]), item("2", &["5"])];
+        let graph = DependencyGraph::new(&items);
+
+        assert!(graph.is_err());
+    }
+
+    #[test]
+    fn test_graph_missing_id() {
+        let items = vec![item("1", &[]), TestItem::default()];
+        let graph = DependencyGraph::new(&items);
+
+        assert!(graph.is_err());
+    }
+
+    #[test]
+    fn test_graph_duplicate_id() {
+        let items = vec![item("1", &[]), item("1", &[])];
+        let graph = DependencyGraph::new(&items);
+
+        assert!(graph.is_err());
+    }
+
+    #[test]
+    fn test_plan_fail_blocks() {
+        let items = vec![
+            item("1", &[]),
+            item("2", &["1"]),
+            item("3", &["2"]),
+            item("4", &[]),
+        ];
+        let mut plan = DependencyGraph::new(&items)
+            .unwrap()
+            .execution_plan()
+            .unwrap();
+
+        assert_eq!(plan.ready(), vec!["1".to_string(), "4".to_string()]);
+        let blocked = plan.fail("1").unwrap();
+        assert_eq!(blocked, vec!["2".to_string(), "3".to_string()]);
+        assert_eq!(plan.get_state("3"), Some(&ItemExecutionState::Blocked));
+        assert!(plan.start("2").is_err());
+        assert!(!plan.is_finished());
+        plan.complete("4").unwrap();
+        assert!(plan.is_finished());
+    }
+
+    #[test]
+    fn test_plan_ready_after_complete() {
+        let items = vec![item("1", &[]), item("2", &["1"])];
+        let mut plan = DependencyGraph::new(&items)
+            .unwrap()
+            .execution_plan()
+            .unwrap();
+
+        plan.complete("1").unwrap();
+        assert_eq!(plan.ready(), vec!["2".to_string()]);
+    }
+
+    #[test]
+    fn test_plan_start_not_ready() {
+        let items = vec![item("1", &[]), item("2", &["1"])];
+        let mut plan = DependencyGraph::new(&items)
+            .unwrap()
+            .execution_plan()
+            .unwrap();
+
+        assert!(matches!(plan.start("2"), Err(TMFError::DependencyError(_))));
+        assert!(plan.complete("2").is_err());
+        plan.start("1").unwrap();
+        assert!(plan.start("1").is_err());
+        plan.complete("1").unwrap();
+        plan.start("2").unwrap();
+        assert_eq!(plan.get_state("2"), Some(&ItemExecutionState::InProgress));
+    }
+
+    #[test]
+    fn test_plan_finished_items() {
+        let items = vec![item("1", &[]), item("2", &["1"]), item("3", &[])];
+        let mut plan = DependencyGraph::new(&items)
+            .unwrap()
+            .execution_plan()
+            .unwrap();
+
+        plan.complete("1").unwrap();
+        plan.complete("2").unwrap();
+        // Failing a completed item does not block dependents that have already run
+        assert!(matches!(plan.fail("1"), Err(TMFError::DependencyError(_))));
+        assert_eq!(plan.get_state("2"), Some(&ItemExecutionState::Completed));
+        plan.fail("3").unwrap();
+        assert!(plan.start("3").is_err());
+        assert!(plan.complete("3").is_err());
+        assert_eq!(plan.get_state("3"), Some(&ItemExecutionState::Failed));
+    }
+}
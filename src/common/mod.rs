@@ -18,7 +18,9 @@ const MOD_PATH: &str = "common";
 
 pub mod addressable;
 pub mod attachment;
+pub mod cancel;
 pub mod contact;
+pub mod dependency;
 pub mod entity;
 pub mod event;
 pub mod extensible;
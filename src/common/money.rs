@@ -9,6 +9,8 @@
 //! # Mul / Div types
 //! Multiplication and Division has been implemented for both f32 and i32 types. Division by zero is not permitted
 //! and will result in the LHS being returned unaltered.
+//! # Encoding
+//! TMF servers send the value either as a JSON number or as a string, both are accepted.
 //! ```
 //! use rust_decimal::Decimal;
 //! use tmflib::common::money::Money;
@@ -37,6 +39,39 @@ pub struct Money {
     pub value: Decimal,
 }
 
+/// Serde adapter for an optional [Money] that writes the value as a JSON number,
+/// for schemas that define the value as a number.
+pub(crate) mod optional_number {
+    use super::Money;
+    use rust_decimal::prelude::ToPrimitive;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct Number<'a> {
+        unit: &'a str,
+        value: f64,
+    }
+
+    pub fn serialize<S: Serializer>(
+        money: &Option<Money>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        money
+            .as_ref()
+            .map(|m| Number {
+                unit: &m.unit,
+                value: m.value.to_f64().unwrap_or_default(),
+            })
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Money>, D::Error> {
+        Option::<Money>::deserialize(deserializer)
+    }
+}
+
 impl Money {
     /// Set currency for this Money with an optional currency_code.
     /// This code must confirm to ISO4217. If an invalid code is passed in, an
@@ -245,6 +280,18 @@ mod test {
         assert_eq!(money.value, Decimal::from_f32(12.34).unwrap_or_default());
     }
 
+    #[test]
+    fn test_money_deserialize_encodings() {
+        let number: Money = serde_json::from_str("{\"unit\":\"AUD\",\"value\":10.05}").unwrap();
+        let string: Money = serde_json::from_str("{\"unit\":\"AUD\",\"value\":\"10.05\"}").unwrap();
+        let integer: Money = serde_json::from_str("{\"unit\":\"AUD\",\"value\":10}").unwrap();
+
+        assert_eq!(number.value, Decimal::new(1005, 2));
+        assert_eq!(string.value, Decimal::new(1005, 2));
+        assert_eq!(integer.value, Decimal::from(10));
+        assert!(serde_json::from_str::<Money>("{\"value\":10}").is_err());
+    }
+
     #[test]
     fn test_money_add() {
         let money1 = Money::from(10);
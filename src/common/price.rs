@@ -74,6 +74,99 @@ impl Price {
     }
 }
 
+impl Price {
+    /// Create a price from a tax exclusive amount and a tax rate, keeping full decimal precision.
+    /// The currency of the resulting price is taken from the supplied amount.
+    pub fn from_ex_amount(amount: Money, tax_rate: f32) -> Price {
+        let rate = Decimal::from_f32(tax_rate).unwrap_or_default();
+        Price {
+            percentage: 0.0,
+            tax_rate,
+            tax_included_amount: Money {
+                unit: amount.unit.clone(),
+                value: amount.value + amount.value * rate,
+            },
+            duty_free_amount: amount,
+        }
+    }
+
+    /// Amount of tax, i.e. the difference between the tax included and duty free amounts
+    pub fn tax_amount(&self) -> Money {
+        Money {
+            unit: self.duty_free_amount.unit.clone(),
+            value: self.tax_included_amount.value - self.duty_free_amount.value,
+        }
+    }
+
+    /// Round both amounts to two decimal places
+    pub fn round(mut self) -> Price {
+        self.duty_free_amount.value = self.duty_free_amount.value.round_dp(2);
+        self.tax_included_amount.value = self.tax_included_amount.value.round_dp(2);
+        self
+    }
+}
+
+/// Alteration (e.g. discount) applied to a price
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceAlteration {
+    /// Name of the alteration
+    pub name: String,
+    /// Type of price this alteration applies to, e.g. recurring. Applies to all types if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_type: Option<String>,
+    /// Recurring period this alteration applies to, e.g. month. Applies to all periods if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurring_charge_period: Option<String>,
+    /// Number of periods the alteration applies for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application_duration: Option<u32>,
+    /// Alterations are applied in ascending priority order
+    pub priority: i16,
+    /// Percentage discount, e.g. 10.0 for 10% off
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percentage: Option<f32>,
+    /// Fixed (tax exclusive) reduction per unit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Money>,
+    /// Resulting reduction once applied to a price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<Price>,
+}
+
+impl PriceAlteration {
+    /// Create a percentage based discount
+    pub fn percentage(name: impl Into<String>, percentage: f32) -> PriceAlteration {
+        PriceAlteration {
+            name: name.into(),
+            percentage: Some(percentage),
+            ..Default::default()
+        }
+    }
+
+    /// Create a fixed amount discount, applied per unit
+    pub fn amount(name: impl Into<String>, amount: Money) -> PriceAlteration {
+        PriceAlteration {
+            name: name.into(),
+            amount: Some(amount),
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if this alteration should be applied to a price of the given type and period
+    pub fn applies_to(&self, price_type: &str, recurring_charge_period: Option<&str>) -> bool {
+        let type_match = match &self.price_type {
+            Some(t) => t == price_type,
+            None => true,
+        };
+        let period_match = match &self.recurring_charge_period {
+            Some(p) => Some(p.as_str()) == recurring_charge_period,
+            None => true,
+        };
+        type_match && period_match
+    }
+}
+
 impl Add for Price {
     type Output = Price;
 
@@ -198,6 +291,26 @@ mod test {
         \"dutyFreeAmount\" : { \"unit\" : \"AUD\", \"value\" : 100.0 },
         \"taxIncludedAmount\" : { \"unit\" : \"AUD\", \"value\" : 110.0 }
     }";
+    #[test]
+    fn test_price_from_ex_amount() {
+        let price = Price::from_ex_amount(Money::from(100), 0.1);
+
+        assert_eq!(price.duty_free_amount.value, Decimal::from(100));
+        assert_eq!(
+            price.tax_included_amount.value.round_dp(2),
+            Decimal::from(110)
+        );
+    }
+
+    #[test]
+    fn test_alteration_applies_to() {
+        let mut alteration = PriceAlteration::percentage("Promo", 10.0);
+        alteration.price_type = Some("recurring".to_string());
+
+        assert!(alteration.applies_to("recurring", Some("month")));
+        assert!(!alteration.applies_to("oneTime", None));
+    }
+
     #[test]
     fn test_price_inc() {
         let price = Price::new_inc(100.0);
@@ -6,7 +6,7 @@ use crate::HasName;
 use crate::Uri;
 
 /// Reference to another TMF schema
-#[derive(Clone, Default, Debug, Deserialize, Serialize)]
+#[derive(Clone, Default, Debug, Deserialize, PartialEq, Serialize)]
 pub struct EntityRef {
     /// Entity HREF
     pub href: String,
@@ -84,6 +84,19 @@ impl From<&GeographicAddress> for RelatedPlaceRefOrValue {
     }
 }
 
+#[cfg(feature = "tmf642")]
+impl From<&crate::tmf642::RelatedPlace> for RelatedPlaceRefOrValue {
+    fn from(value: &crate::tmf642::RelatedPlace) -> Self {
+        let place = value.related_place.clone().unwrap_or_default();
+        RelatedPlaceRefOrValue {
+            referred_type: String::from("Place"),
+            href: place.href.clone().unwrap_or_default(),
+            id: place.id.clone().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::GeographicAddress;
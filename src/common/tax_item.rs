@@ -4,12 +4,26 @@ use super::money::Money;
 use serde::{Deserialize, Serialize};
 
 /// Tax Details
-#[derive(Clone, Default, Debug, Deserialize, Serialize)]
+#[derive(Clone, Default, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaxItem {
-    tax_category: String,
-    tax_rate: f32,
-    tax_amount: Money,
+    /// Tax category, e.g. GST, VAT
+    pub tax_category: String,
+    /// Tax rate applied, e.g. 0.10 for 10%
+    pub tax_rate: f32,
+    /// Amount of tax
+    pub tax_amount: Money,
+}
+
+impl TaxItem {
+    /// Create a new tax item
+    pub fn new(category: impl Into<String>, rate: f32, amount: Money) -> TaxItem {
+        TaxItem {
+            tax_category: category.into(),
+            tax_rate: rate,
+            tax_amount: amount,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -22,6 +36,14 @@ mod test {
         \"taxAmount\" : { \"unit\" : \"AUD\", \"value\": 100.0}
     }";
 
+    #[test]
+    fn test_taxitem_new() {
+        let taxitem = TaxItem::new("GST", 0.10, Money::from(10));
+
+        assert_eq!(taxitem.tax_category.as_str(), "GST");
+        assert_eq!(taxitem.tax_amount, Money::from(10));
+    }
+
     #[test]
     fn test_taxitem_deserialise() {
         let taxitem: TaxItem = serde_json::from_str(TAX_JSON).expect("Could not parase TAX_JSON");
@@ -40,6 +40,30 @@ pub enum TMFError {
     /// Invalid Note error
     #[error("No data present for {0}")]
     NoDataError(String),
+    /// Dependency Error
+    #[error("Dependency Error: {0}")]
+    DependencyError(String),
+    /// Invalid State Transition error
+    #[error("State Error: {0}")]
+    StateError(String),
+    /// Credit Limit Exceeded error
+    #[error("Credit Limit Error: {0}")]
+    CreditLimitError(String),
+    /// Invalid Payment Method error
+    #[error("Payment Method Error: {0}")]
+    PaymentMethodError(String),
+    /// Invalid Price or Share Tier error
+    #[error("Tier Error: {0}")]
+    TierError(String),
+    /// Quantity that cannot be used, e.g. not a number or out of range
+    #[error("Invalid Quantity: {0}")]
+    InvalidQuantity(String),
+    /// Division by zero error
+    #[error("Division by zero: {0}")]
+    DivisionByZero(String),
+    /// Probable cause not valid for the alarm type
+    #[error("Probable Cause Error: {0}")]
+    ProbableCauseError(String),
     /// Regex Error
     #[error("Regex Error: {0}")]
     RegexError(#[from] RegexError),
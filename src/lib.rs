@@ -71,6 +71,14 @@ pub type Cardinality = u16;
 pub type TimeStamp = String;
 /// Type alias for DateTime
 pub type DateTime = String;
+
+/// Current time as an RFC3339 [DateTime], to the second
+pub(crate) fn now() -> DateTime {
+    let now = Utc::now();
+    chrono::DateTime::from_timestamp(now.timestamp(), 0)
+        .expect("Invalid now() output")
+        .to_rfc3339()
+}
 /// Type alias for Uri
 pub type Uri = String;
 /// Priority Type
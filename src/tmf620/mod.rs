@@ -27,6 +27,7 @@ const MOD_PATH: &str = "productCatalogManagement/v5";
 pub mod bundled_product_offering;
 pub mod catalog;
 pub mod category;
+pub mod pricing;
 
 #[cfg(all(feature = "tmf620", feature = "build-V4"))]
 pub mod product_offering;
@@ -0,0 +1,835 @@
+//! Pricing Engine Module
+//!
+//! Calculates item and total prices from the [`ProductOfferingPrice`] entries linked to a
+//! Product Offering. Results can be converted into cart prices (TMF663) or order prices (TMF622).
+//!
+//! Pricing rules:
+//! - Each non-discount price is evaluated for the requested quantity by [`PriceEvaluator`],
+//!   applying price tiers and summing the components of bundled prices.
+//! - Discount prices from the catalogue, and any alterations supplied with the request, are
+//!   applied in ascending priority order. Percentages are applied to the running amount, fixed
+//!   amounts are applied per unit. Prices never go below zero.
+//! - Tax is taken from the price tax items if present, otherwise the engine default is used.
+//!   A price with several tax items is taxed at their combined rate, with a tax item per category.
+//! - Amounts are rounded to two decimal places.
+//! - Totals are grouped by price type and recurring charge period.
+//! ```
+//! # #[cfg(feature = "build-V4")]
+//! # use tmflib::tmf620::product_offering::ProductOffering;
+//! # #[cfg(feature = "build-V5")]
+//! # use tmflib::tmf620::product_offering_v5::ProductOffering;
+//! use tmflib::common::money::Money;
+//! use tmflib::tmf620::pricing::{PriceRequest, PricingEngine};
+//! use tmflib::tmf620::product_offering_price::{PriceType, ProductOfferingPrice};
+//! use tmflib::HasId;
+//!
+//! let offer = ProductOffering::new("Broadband");
+//! let monthly = ProductOfferingPrice::new("Monthly Fee")
+//!     .price(Money::from(50))
+//!     .recurring("month", 1);
+//! let mut engine = PricingEngine::new();
+//! engine.add_price(offer.get_id(), monthly);
+//!
+//! let result = engine.price(&[PriceRequest::new(offer.get_id(), 2)]).unwrap();
+//! assert_eq!(result.totals[0].price.duty_free_amount, Money::from(100));
+//! ```
+
+use std::collections::HashMap;
+
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::product_offering_price::{PriceType, ProductOfferingPrice};
+use crate::common::money::Money;
+use crate::common::price::{Price, PriceAlteration};
+use crate::common::tax_item::TaxItem;
+use crate::common::tmf_error::TMFError;
+#[cfg(feature = "build-V4")]
+use crate::tmf620::product_offering::ProductOffering;
+#[cfg(feature = "build-V5")]
+use crate::tmf620::product_offering_v5::ProductOffering;
+use crate::{HasId, HasName};
+
+/// Default tax rate, matching the Australian market default used by [`Price`]
+const DEFAULT_TAX_RATE: f32 = 0.10;
+const DEFAULT_TAX_CATEGORY: &str = "GST";
+const PRICE_DP: u32 = 2;
+
+/// Request to price a single item
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceRequest {
+    /// Identifier of the item being priced, e.g. cart item or order item id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_id: Option<String>,
+    /// Product Offering being priced
+    pub offering_id: String,
+    /// Quantity
+    pub quantity: u16,
+    /// Additional alterations (e.g. promotions) to apply to this item
+    pub alterations: Vec<PriceAlteration>,
+    /// Selected component price ids for bundled prices. All components are used if empty.
+    pub selection: Vec<String>,
+}
+
+impl PriceRequest {
+    /// Create a new price request for a quantity of an offering
+    pub fn new(offering_id: impl Into<String>, quantity: u16) -> PriceRequest {
+        PriceRequest {
+            offering_id: offering_id.into(),
+            quantity,
+            ..Default::default()
+        }
+    }
+
+    /// Builder pattern to set the item id
+    pub fn item_id(mut self, item_id: impl Into<String>) -> PriceRequest {
+        self.item_id = Some(item_id.into());
+        self
+    }
+
+    /// Builder pattern to add an alteration
+    pub fn alteration(mut self, alteration: PriceAlteration) -> PriceRequest {
+        self.alterations.push(alteration);
+        self
+    }
+
+    /// Builder pattern to select a component of a bundled price
+    pub fn select(mut self, component_id: impl Into<String>) -> PriceRequest {
+        self.selection.push(component_id.into());
+        self
+    }
+}
+
+/// Single calculated price component of an item, derived from one [`ProductOfferingPrice`]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceComponent {
+    /// Name, taken from the Product Offering Price
+    pub name: String,
+    /// Price Type
+    pub price_type: PriceType,
+    /// Recurring charge period, e.g. month
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurring_charge_period: Option<String>,
+    /// Unit of measure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_of_measure: Option<String>,
+    /// Tax exclusive price per unit before alterations
+    pub unit_price: Money,
+    /// Final price for the quantity requested, after alterations
+    pub price: Price,
+    /// Alterations applied, including the resulting reduction
+    pub price_alteration: Vec<PriceAlteration>,
+    /// Tax applied
+    pub tax: Vec<TaxItem>,
+}
+
+/// Calculated prices for a single item
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemPrice {
+    /// Identifier of the item priced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_id: Option<String>,
+    /// Product Offering priced
+    pub offering_id: String,
+    /// Quantity
+    pub quantity: u16,
+    /// Price components
+    pub components: Vec<PriceComponent>,
+}
+
+/// Total for all components of the same price type and recurring period
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceSummary {
+    /// Price Type
+    pub price_type: PriceType,
+    /// Recurring charge period, e.g. month
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurring_charge_period: Option<String>,
+    /// Total price
+    pub price: Price,
+    /// Total tax, per tax category
+    pub tax: Vec<TaxItem>,
+}
+
+/// Result of a pricing run
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricingResult {
+    /// Prices for each requested item, in request order
+    pub items: Vec<ItemPrice>,
+    /// Totals grouped by price type and recurring period
+    pub totals: Vec<PriceSummary>,
+}
+
+/// Evaluates the amount of a [`ProductOfferingPrice`] for a quantity, resolving
+/// tiered, volume and bundled (composite) prices.
+///
+/// A bundled price is the sum of its component prices, each evaluated for the same quantity.
+/// Bundles may be nested but must not refer back to themselves.
+#[derive(Clone, Debug, Default)]
+pub struct PriceEvaluator {
+    prices: HashMap<String, ProductOfferingPrice>,
+}
+
+impl PriceEvaluator {
+    /// Create a new evaluator
+    pub fn new() -> PriceEvaluator {
+        PriceEvaluator::default()
+    }
+
+    /// Add a price, replacing any existing price with the same id
+    pub fn add(&mut self, price: ProductOfferingPrice) {
+        self.prices.insert(price.get_id(), price);
+    }
+
+    /// Get a price by id
+    pub fn get(&self, id: &str) -> Option<&ProductOfferingPrice> {
+        self.prices.get(id)
+    }
+
+    /// Check all bundled prices refer to known components and contain no circular references
+    pub fn validate(&self) -> Result<(), TMFError> {
+        self.prices
+            .values()
+            .try_for_each(|p| self.check_bundle(p, &mut vec![]))
+    }
+
+    /// Evaluate the price with the given id for a quantity.
+    /// For bundled prices, `selection` limits which components are included.
+    pub fn evaluate(
+        &self,
+        id: &str,
+        quantity: u32,
+        selection: &[String],
+    ) -> Result<Money, TMFError> {
+        let pop = self
+            .get(id)
+            .ok_or(TMFError::InvalidReference(format!("Unknown price {id}")))?;
+        self.amount(pop, quantity, selection)
+    }
+
+    /// Evaluate a price for a quantity.
+    /// For bundled prices, `selection` limits which components are included.
+    pub fn amount(
+        &self,
+        pop: &ProductOfferingPrice,
+        quantity: u32,
+        selection: &[String],
+    ) -> Result<Money, TMFError> {
+        self.check_bundle(pop, &mut vec![])?;
+        self.bundle_amount(pop, quantity, selection)
+    }
+
+    fn check_bundle(
+        &self,
+        pop: &ProductOfferingPrice,
+        path: &mut Vec<String>,
+    ) -> Result<(), TMFError> {
+        let id = pop.get_id();
+        if path.contains(&id) {
+            path.push(id);
+            return Err(TMFError::DependencyError(format!(
+                "Circular bundled price: {}",
+                path.join(" -> ")
+            )));
+        }
+        path.push(id);
+        for component in pop.get_components() {
+            let child = self
+                .get(&component)
+                .ok_or(TMFError::InvalidReference(format!(
+                    "Bundled price {} references unknown price {component}",
+                    pop.get_name()
+                )))?;
+            self.check_bundle(child, path)?;
+        }
+        path.pop();
+        Ok(())
+    }
+
+    fn bundle_amount(
+        &self,
+        pop: &ProductOfferingPrice,
+        quantity: u32,
+        selection: &[String],
+    ) -> Result<Money, TMFError> {
+        let components = pop.get_components();
+        if !pop.is_bundle() || components.is_empty() {
+            return pop.tier_amount(quantity);
+        }
+        if let Some(unknown) = selection.iter().find(|s| !components.contains(s)) {
+            return Err(TMFError::InvalidReference(format!(
+                "Price {unknown} is not a component of {}",
+                pop.get_name()
+            )));
+        }
+        let chosen = match selection.is_empty() {
+            true => components.as_slice(),
+            false => selection,
+        };
+        let mut total: Option<Money> = None;
+        for id in chosen {
+            // Components were resolved by check_bundle()
+            let amount = self.bundle_amount(&self.prices[id], quantity, &[])?;
+            total = match total {
+                Some(t) => {
+                    check_currency(&t, &amount)?;
+                    Some(t + amount)
+                }
+                None => Some(amount),
+            };
+        }
+        Ok(total.unwrap_or_default())
+    }
+}
+
+/// Pricing Engine
+#[derive(Clone, Debug)]
+pub struct PricingEngine {
+    prices: HashMap<String, Vec<ProductOfferingPrice>>,
+    evaluator: PriceEvaluator,
+    tax_category: String,
+    tax_rate: f32,
+}
+
+impl Default for PricingEngine {
+    fn default() -> Self {
+        PricingEngine {
+            prices: HashMap::new(),
+            evaluator: PriceEvaluator::default(),
+            tax_category: DEFAULT_TAX_CATEGORY.to_string(),
+            tax_rate: DEFAULT_TAX_RATE,
+        }
+    }
+}
+
+impl PricingEngine {
+    /// Create a new pricing engine with the default tax settings
+    pub fn new() -> PricingEngine {
+        PricingEngine::default()
+    }
+
+    /// Builder pattern to set the default tax category and rate,
+    /// used when a price has no tax items of its own.
+    pub fn tax(mut self, category: impl Into<String>, rate: f32) -> PricingEngine {
+        self.tax_category = category.into();
+        self.tax_rate = rate;
+        self
+    }
+
+    /// Add a single price for an offering
+    pub fn add_price(&mut self, offering_id: impl Into<String>, price: ProductOfferingPrice) {
+        self.evaluator.add(price.clone());
+        self.prices
+            .entry(offering_id.into())
+            .or_default()
+            .push(price);
+    }
+
+    /// Add a price that is only used as a component of bundled prices
+    pub fn add_component(&mut self, price: ProductOfferingPrice) {
+        self.evaluator.add(price);
+    }
+
+    /// Add all prices referenced by an offering, taken from the supplied list of prices.
+    /// Other prices in the list are available as bundle components.
+    /// Returns an error if a referenced price cannot be found.
+    pub fn add_offering(
+        &mut self,
+        offering: &ProductOffering,
+        prices: &[ProductOfferingPrice],
+    ) -> Result<(), TMFError> {
+        prices.iter().for_each(|p| self.add_component(p.clone()));
+        for pop_ref in offering.product_offering_price.iter().flatten() {
+            let pop = prices
+                .iter()
+                .find(|p| p.id.is_some() && p.id == pop_ref.id)
+                .ok_or(TMFError::InvalidReference(format!(
+                    "Offering {} references unknown price {}",
+                    offering.get_id(),
+                    pop_ref.id.clone().unwrap_or_default()
+                )))?;
+            self.add_price(offering.get_id(), pop.clone());
+        }
+        Ok(())
+    }
+
+    /// Price a list of items and calculate totals
+    pub fn price(&self, requests: &[PriceRequest]) -> Result<PricingResult, TMFError> {
+        let items = requests
+            .iter()
+            .map(|r| self.price_item(r))
+            .collect::<Result<Vec<ItemPrice>, TMFError>>()?;
+        let totals = summarise(items.iter().flat_map(|i| i.components.iter()))?;
+        Ok(PricingResult { items, totals })
+    }
+
+    /// Price a single item
+    pub fn price_item(&self, request: &PriceRequest) -> Result<ItemPrice, TMFError> {
+        let pops = self
+            .prices
+            .get(&request.offering_id)
+            .ok_or(TMFError::InvalidReference(format!(
+                "No prices found for offering {}",
+                request.offering_id
+            )))?;
+
+        // Catalogue discounts apply to every other price of the offering
+        let mut alterations = vec![];
+        let mut charges = vec![];
+        for pop in pops {
+            match pop.get_price_type()? {
+                PriceType::Discount => alterations.push(PriceAlteration {
+                    name: pop.get_name(),
+                    percentage: pop.get_percentage(),
+                    amount: pop.get_price().cloned(),
+                    ..Default::default()
+                }),
+                _ => charges.push(pop),
+            }
+        }
+        alterations.extend(request.alterations.iter().cloned());
+        // Stable sort keeps catalogue discounts ahead of request alterations of the same priority
+        alterations.sort_by_key(|a| a.priority);
+
+        let components = charges
+            .into_iter()
+            .map(|pop| self.price_component(pop, request, &alterations))
+            .collect::<Result<Vec<PriceComponent>, TMFError>>()?;
+
+        Ok(ItemPrice {
+            item_id: request.item_id.clone(),
+            offering_id: request.offering_id.clone(),
+            quantity: request.quantity,
+            components,
+        })
+    }
+
+    fn price_component(
+        &self,
+        pop: &ProductOfferingPrice,
+        request: &PriceRequest,
+        alterations: &[PriceAlteration],
+    ) -> Result<PriceComponent, TMFError> {
+        let quantity = request.quantity;
+        let price_type = pop.get_price_type()?;
+        let period = pop.get_recurring_period();
+        let gross = self
+            .evaluator
+            .amount(pop, u32::from(quantity), &request.selection)?;
+        // Tiered and bundled prices report the average price per unit
+        let unit_price = match quantity {
+            0 => pop.get_price().cloned().unwrap_or_default(),
+            q => Money {
+                unit: gross.unit.clone(),
+                value: gross.value / Decimal::from(q),
+            },
+        };
+        let tax_rate = pop.get_tax_rate().unwrap_or(self.tax_rate);
+
+        let mut net = gross;
+        let mut applied = vec![];
+        for alteration in alterations
+            .iter()
+            .filter(|a| a.applies_to(&price_type.to_string(), period.as_deref()))
+        {
+            let mut reduction = Money {
+                unit: net.unit.clone(),
+                value: Decimal::ZERO,
+            };
+            if let Some(percentage) = alteration.percentage {
+                let pct = Decimal::from_f32(percentage).unwrap_or_default() / Decimal::from(100);
+                reduction.value += net.value * pct;
+            }
+            if let Some(amount) = &alteration.amount {
+                check_currency(&net, amount)?;
+                reduction.value += amount.value * Decimal::from(quantity);
+            }
+            // Never discount below zero
+            reduction.value = round(reduction.value.min(net.value));
+            net.value -= reduction.value;
+            applied.push(PriceAlteration {
+                price: Some(Price::from_ex_amount(reduction, tax_rate).round()),
+                ..alteration.clone()
+            });
+        }
+
+        let price = Price::from_ex_amount(net, tax_rate).round();
+        let tax = self.tax_items(pop, &price);
+        Ok(PriceComponent {
+            name: pop.get_name(),
+            price_type,
+            recurring_charge_period: period,
+            unit_of_measure: pop.get_unit_of_measure(),
+            unit_price,
+            price,
+            price_alteration: applied,
+            tax,
+        })
+    }
+
+    /// Split the tax on a price into a tax item for each tax on the product offering price.
+    /// The last item takes any rounding difference so the items add up to the price tax.
+    fn tax_items(&self, pop: &ProductOfferingPrice, price: &Price) -> Vec<TaxItem> {
+        let total = price.tax_amount();
+        let taxes = pop.get_tax();
+        if taxes.is_empty() {
+            return vec![TaxItem::new(
+                self.tax_category.clone(),
+                self.tax_rate,
+                total,
+            )];
+        }
+        let mut remaining = total.value;
+        let mut items = vec![];
+        for (i, tax) in taxes.iter().enumerate() {
+            let value = match i + 1 == taxes.len() {
+                true => remaining,
+                false => round(
+                    price.duty_free_amount.value
+                        * Decimal::from_f32(tax.tax_rate).unwrap_or_default(),
+                ),
+            };
+            remaining -= value;
+            items.push(TaxItem::new(
+                tax.tax_category.clone(),
+                tax.tax_rate,
+                Money {
+                    unit: total.unit.clone(),
+                    value,
+                },
+            ));
+        }
+        items
+    }
+}
+
+fn round(value: Decimal) -> Decimal {
+    value.round_dp(PRICE_DP)
+}
+
+fn check_currency(left: &Money, right: &Money) -> Result<(), TMFError> {
+    if left.unit != right.unit {
+        return Err(TMFError::CurrencyError(format!(
+            "Cannot combine {} with {}",
+            left.unit, right.unit
+        )));
+    }
+    Ok(())
+}
+
+/// Group components by price type and recurring period, summing prices and tax.
+fn summarise<'a>(
+    components: impl Iterator<Item = &'a PriceComponent>,
+) -> Result<Vec<PriceSummary>, TMFError> {
+    let mut totals: Vec<PriceSummary> = vec![];
+    for component in components {
+        let existing = totals.iter_mut().find(|t| {
+            t.price_type == component.price_type
+                && t.recurring_charge_period == component.recurring_charge_period
+        });
+        match existing {
+            Some(total) => {
+                check_currency(
+                    &total.price.duty_free_amount,
+                    &component.price.duty_free_amount,
+                )?;
+                total.price.duty_free_amount += component.price.duty_free_amount.clone();
+                total.price.tax_included_amount += component.price.tax_included_amount.clone();
+                for tax in &component.tax {
+                    match total
+                        .tax
+                        .iter_mut()
+                        .find(|t| t.tax_category == tax.tax_category)
+                    {
+                        Some(t) => t.tax_amount += tax.tax_amount.clone(),
+                        None => total.tax.push(tax.clone()),
+                    }
+                }
+            }
+            None => totals.push(PriceSummary {
+                price_type: component.price_type.clone(),
+                recurring_charge_period: component.recurring_charge_period.clone(),
+                price: component.price.clone(),
+                tax: component.tax.clone(),
+            }),
+        }
+    }
+    // Check all totals share the same currency
+    if let Some(first) = totals.first() {
+        for total in totals.iter().skip(1) {
+            check_currency(&first.price.duty_free_amount, &total.price.duty_free_amount)?;
+        }
+    }
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tmf620::product_offering_price::ProductOfferingPriceRef;
+
+    const OFFER: &str = "Broadband";
+
+    fn engine(offer: &ProductOffering) -> PricingEngine {
+        let mut engine = PricingEngine::new();
+        engine.add_price(
+            offer.get_id(),
+            ProductOfferingPrice::new("Connection")
+                .price(Money::from(100))
+                .price_type(PriceType::OneTime),
+        );
+        engine.add_price(
+            offer.get_id(),
+            ProductOfferingPrice::new("Monthly")
+                .price(Money::from(50))
+                .recurring("month", 1),
+        );
+        engine
+    }
+
+    #[test]
+    fn test_price_quantity_and_totals() {
+        let offer = ProductOffering::new(OFFER);
+        let engine = engine(&offer);
+
+        let result = engine
+            .price(&[PriceRequest::new(offer.get_id(), 2).item_id("1")])
+            .unwrap();
+
+        assert_eq!(result.items.len(), 1);
+        assert_eq!(result.items[0].components.len(), 2);
+        assert_eq!(result.totals.len(), 2);
+        let monthly = result
+            .totals
+            .iter()
+            .find(|t| t.price_type == PriceType::Recurring)
+            .unwrap();
+        assert_eq!(monthly.recurring_charge_period, Some("month".to_string()));
+        assert_eq!(monthly.price.duty_free_amount, Money::from(100));
+        assert_eq!(monthly.price.tax_included_amount, Money::from(110));
+        assert_eq!(monthly.tax[0].tax_amount, Money::from(10));
+    }
+
+    #[test]
+    fn test_price_catalogue_discount() {
+        let offer = ProductOffering::new(OFFER);
+        let mut engine = engine(&offer);
+        engine.add_price(
+            offer.get_id(),
+            ProductOfferingPrice::new("Launch Offer")
+                .price_type(PriceType::Discount)
+                .percentage(10.0),
+        );
+
+        let result = engine
+            .price_item(&PriceRequest::new(offer.get_id(), 1))
+            .unwrap();
+
+        let monthly = &result.components[1];
+        assert_eq!(monthly.price.duty_free_amount, Money::from(45));
+        assert_eq!(monthly.price_alteration.len(), 1);
+        assert_eq!(
+            monthly.price_alteration[0]
+                .price
+                .as_ref()
+                .unwrap()
+                .duty_free_amount,
+            Money::from(5)
+        );
+    }
+
+    #[test]
+    fn test_price_alteration_priority_and_floor() {
+        let offer = ProductOffering::new(OFFER);
+        let engine = engine(&offer);
+        let mut first = PriceAlteration::amount("Credit", Money::from(40));
+        first.priority = 1;
+        let mut second = PriceAlteration::percentage("Half Off", 50.0);
+        second.price_type = Some(PriceType::Recurring.to_string());
+        let mut big = PriceAlteration::amount("Free Connection", Money::from(500));
+        big.price_type = Some(PriceType::OneTime.to_string());
+
+        let request = PriceRequest::new(offer.get_id(), 1)
+            .alteration(first)
+            .alteration(second)
+            .alteration(big);
+        let result = engine.price_item(&request).unwrap();
+
+        // Connection: 100 - 500 floored at zero, then credit has nothing left
+        assert_eq!(
+            result.components[0].price.duty_free_amount.value,
+            Decimal::ZERO
+        );
+        // Monthly: 50 - 50% = 25, then credit of 40 floors at zero
+        let monthly = &result.components[1];
+        assert_eq!(monthly.price_alteration[0].name, "Half Off".to_string());
+        assert_eq!(monthly.price_alteration[1].name, "Credit".to_string());
+        assert_eq!(monthly.price.duty_free_amount.value, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_price_tax_override() {
+        let offer = ProductOffering::new(OFFER);
+        let mut engine = PricingEngine::new().tax("VAT", 0.2);
+        engine.add_price(
+            offer.get_id(),
+            ProductOfferingPrice::new("Device").price(Money::from(100)),
+        );
+
+        let result = engine
+            .price_item(&PriceRequest::new(offer.get_id(), 1))
+            .unwrap();
+
+        assert_eq!(result.components[0].tax[0].tax_category, "VAT".to_string());
+        assert_eq!(
+            result.components[0].price.tax_included_amount,
+            Money::from(120)
+        );
+    }
+
+    #[test]
+    fn test_price_multiple_tax() {
+        let offer = ProductOffering::new(OFFER);
+        let mut engine = PricingEngine::new();
+        engine.add_price(
+            offer.get_id(),
+            ProductOfferingPrice::new("Device")
+                .price(Money::from(100))
+                .tax(TaxItem::new("GST", 0.05, Money::default()))
+                .tax(TaxItem::new("PST", 0.07, Money::default())),
+        );
+
+        let result = engine
+            .price_item(&PriceRequest::new(offer.get_id(), 1))
+            .unwrap();
+
+        let component = &result.components[0];
+        assert_eq!(component.price.tax_included_amount, Money::from(112));
+        assert_eq!(component.tax.len(), 2);
+        assert_eq!(component.tax[0].tax_amount, Money::from(5));
+        assert_eq!(component.tax[1].tax_amount, Money::from(7));
+    }
+
+    #[test]
+    fn test_price_currency_mismatch() {
+        let offer = ProductOffering::new(OFFER);
+        let mut engine = engine(&offer);
+        let mut usd = Money::from(10);
+        usd.currency("USD").unwrap();
+        engine.add_price(
+            offer.get_id(),
+            ProductOfferingPrice::new("Setup").price(usd),
+        );
+
+        assert!(engine
+            .price(&[PriceRequest::new(offer.get_id(), 1)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_price_unknown_offering() {
+        let engine = PricingEngine::new();
+
+        assert!(engine.price_item(&PriceRequest::new("missing", 1)).is_err());
+    }
+
+    #[test]
+    fn test_price_tiered_usage() {
+        let offer = ProductOffering::new(OFFER);
+        let mut engine = PricingEngine::new();
+        engine.add_price(
+            offer.get_id(),
+            ProductOfferingPrice::new("Data")
+                .price_type(PriceType::Usage)
+                .unit_of_measure("GB")
+                .tier(0, Some(10), Money::from(2))
+                .tier(10, None, Money::from(1)),
+        );
+
+        let result = engine
+            .price_item(&PriceRequest::new(offer.get_id(), 15))
+            .unwrap();
+
+        assert_eq!(result.components[0].price.duty_free_amount, Money::from(25));
+    }
+
+    #[test]
+    fn test_evaluate_bundle() {
+        let voice = ProductOfferingPrice::new("Voice").price(Money::from(20));
+        let data = ProductOfferingPrice::new("Data").price(Money::from(30));
+        let bundle = ProductOfferingPrice::new("Bundle")
+            .bundle(&voice)
+            .bundle(&data);
+        let mut evaluator = PriceEvaluator::new();
+        evaluator.add(voice);
+        evaluator.add(data.clone());
+        evaluator.add(bundle.clone());
+
+        assert!(evaluator.validate().is_ok());
+        assert_eq!(
+            evaluator.evaluate(&bundle.get_id(), 2, &[]).unwrap(),
+            Money::from(100)
+        );
+        assert_eq!(
+            evaluator.amount(&bundle, 1, &[data.get_id()]).unwrap(),
+            Money::from(30)
+        );
+        assert!(evaluator
+            .amount(&bundle, 1, &["other".to_string()])
+            .is_err());
+    }
+
+    #[test]
+    fn test_evaluate_circular_bundle() {
+        let inner = ProductOfferingPrice::new("Inner");
+        let outer = ProductOfferingPrice::new("Outer").bundle(&inner);
+        let inner = inner.bundle(&outer);
+        let mut evaluator = PriceEvaluator::new();
+        evaluator.add(inner);
+        evaluator.add(outer.clone());
+
+        assert!(evaluator.validate().is_err());
+        assert!(evaluator.amount(&outer, 1, &[]).is_err());
+    }
+
+    #[test]
+    fn test_price_bundle_selection() {
+        let offer = ProductOffering::new(OFFER);
+        let voice = ProductOfferingPrice::new("Voice").price(Money::from(20));
+        let data = ProductOfferingPrice::new("Data").price(Money::from(30));
+        let bundle = ProductOfferingPrice::new("Bundle")
+            .recurring("month", 1)
+            .bundle(&voice)
+            .bundle(&data);
+        let mut engine = PricingEngine::new();
+        engine.add_component(voice.clone());
+        engine.add_component(data);
+        engine.add_price(offer.get_id(), bundle);
+
+        let result = engine
+            .price_item(&PriceRequest::new(offer.get_id(), 1).select(voice.get_id()))
+            .unwrap();
+
+        assert_eq!(result.components[0].price.duty_free_amount, Money::from(20));
+    }
+
+    #[test]
+    fn test_add_offering() {
+        let mut offer = ProductOffering::new(OFFER);
+        let pop = ProductOfferingPrice::new("Monthly").price(Money::from(50));
+        offer.product_offering_price = Some(vec![ProductOfferingPriceRef::from(pop.clone())]);
+        let mut engine = PricingEngine::new();
+
+        assert!(engine.add_offering(&offer, &[]).is_err());
+        assert!(engine.add_offering(&offer, &[pop]).is_ok());
+        assert!(engine
+            .price_item(&PriceRequest::new(offer.get_id(), 1))
+            .is_ok());
+    }
+}
@@ -1,6 +1,8 @@
 //! Product Offering Price Module
 
 use chrono::Utc;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -8,7 +10,10 @@ use super::MOD_PATH;
 use crate::common::event::{Event, EventPayload};
 use crate::common::money::Money;
 use crate::common::tax_item::TaxItem;
-use crate::{HasId, HasLastUpdate, HasName, HasReference, HasValidity, TMFEvent, TimePeriod};
+use crate::common::tmf_error::TMFError;
+use crate::{
+    vec_insert, HasId, HasLastUpdate, HasName, HasReference, HasValidity, TMFEvent, TimePeriod,
+};
 use tmflib_derive::{HasId, HasLastUpdate, HasName, HasValidity};
 
 const CLASS_PATH: &str = "productOfferingPrice";
@@ -60,6 +65,112 @@ impl HasReference for ProductOfferingPrice {
     }
 }
 
+/// Relationship to a component price of a bundled [`ProductOfferingPrice`]
+#[derive(Clone, Default, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledProductOfferingPriceRelationship {
+    /// Id of the component price
+    pub id: String,
+    /// HTTP Reference
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    /// Name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl From<&ProductOfferingPrice> for BundledProductOfferingPriceRelationship {
+    fn from(pop: &ProductOfferingPrice) -> Self {
+        BundledProductOfferingPriceRelationship {
+            id: pop.get_id(),
+            href: pop.href.clone(),
+            name: pop.name.clone(),
+        }
+    }
+}
+
+/// Relationship between two [`ProductOfferingPrice`] entries, e.g. a discount applying to a price
+#[derive(Clone, Default, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductOfferingPriceRelationship {
+    /// Id of the related price
+    pub id: String,
+    /// HTTP Reference
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    /// Name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Type of relationship
+    pub relationship_type: String,
+}
+
+/// How tier prices are applied to a quantity
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TierType {
+    /// Each unit is charged at the price of the tier it falls into
+    #[default]
+    Tiered,
+    /// All units are charged at the price of the tier the total quantity falls into
+    Volume,
+}
+
+/// Price band covering quantities from `lower_bound` up to and including `upper_bound`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceTier {
+    /// Upper bound of the previous tier, or the first quantity within this tier
+    pub lower_bound: u32,
+    /// Highest quantity within this tier, unbounded if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upper_bound: Option<u32>,
+    /// Price per unit within this tier
+    pub price: Money,
+}
+
+/// Type of price defined by a [`ProductOfferingPrice`]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PriceType {
+    /// Charged once, e.g. connection fee
+    #[default]
+    OneTime,
+    /// Charged every recurring period, e.g. monthly fee
+    Recurring,
+    /// Charged per unit of usage
+    Usage,
+    /// Reduction applied to other prices
+    Discount,
+}
+
+impl std::fmt::Display for PriceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let out = match self {
+            PriceType::OneTime => "oneTime",
+            PriceType::Recurring => "recurring",
+            PriceType::Usage => "usage",
+            PriceType::Discount => "discount",
+        };
+        write!(f, "{out}")
+    }
+}
+
+impl TryFrom<&str> for PriceType {
+    type Error = TMFError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "onetime" | "one time" => Ok(PriceType::OneTime),
+            "recurring" => Ok(PriceType::Recurring),
+            "usage" => Ok(PriceType::Usage),
+            "discount" | "allowance" => Ok(PriceType::Discount),
+            _ => Err(TMFError::GenericError(format!(
+                "Unknown price type: {value}"
+            ))),
+        }
+    }
+}
+
 /// Pricing linked to a Product Offering
 #[derive(
     Clone, Default, Debug, Deserialize, HasId, HasLastUpdate, HasName, HasValidity, Serialize,
@@ -84,6 +195,18 @@ pub struct ProductOfferingPrice {
     description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     is_bundle: Option<bool>,
+    /// Component prices of a bundled price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundled_pop_relationship: Option<Vec<BundledProductOfferingPriceRelationship>>,
+    /// Relationships to other prices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pop_relationship: Option<Vec<ProductOfferingPriceRelationship>>,
+    /// Price tiers, ordered by lower bound
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_tier: Option<Vec<PriceTier>>,
+    /// How price tiers are applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tier_type: Option<TierType>,
     #[serde(skip_serializing_if = "Option::is_none")]
     last_update: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -114,6 +237,267 @@ impl ProductOfferingPrice {
         pop.name = Some(name.into());
         pop
     }
+
+    /// Builder pattern to set the (tax exclusive) price
+    pub fn price(mut self, price: Money) -> ProductOfferingPrice {
+        self.price = Some(price);
+        self
+    }
+
+    /// Builder pattern to set the price type
+    pub fn price_type(mut self, price_type: PriceType) -> ProductOfferingPrice {
+        self.price_type = Some(price_type.to_string());
+        self
+    }
+
+    /// Builder pattern to make this a recurring price charged every `length` periods of `period_type`, e.g. month
+    pub fn recurring(
+        mut self,
+        period_type: impl Into<String>,
+        length: u16,
+    ) -> ProductOfferingPrice {
+        self.price_type = Some(PriceType::Recurring.to_string());
+        self.recurring_charge_period_type = Some(period_type.into());
+        self.recurring_charge_period_length = Some(length);
+        self
+    }
+
+    /// Builder pattern to set the unit of measure, e.g. GB for a usage price
+    pub fn unit_of_measure(mut self, unit: impl Into<String>) -> ProductOfferingPrice {
+        self.unit_of_measure = Some(unit.into());
+        self
+    }
+
+    /// Builder pattern to set a percentage, used for discounts
+    pub fn percentage(mut self, percentage: f32) -> ProductOfferingPrice {
+        self.percentage = Some(percentage);
+        self
+    }
+
+    /// Builder pattern to add a tax item, the combined tax rate is used when pricing
+    pub fn tax(mut self, tax: TaxItem) -> ProductOfferingPrice {
+        vec_insert(&mut self.tax, tax);
+        self
+    }
+
+    /// Builder pattern to add a component price, marking this price as a bundle
+    pub fn bundle(mut self, component: &ProductOfferingPrice) -> ProductOfferingPrice {
+        self.is_bundle = Some(true);
+        vec_insert(
+            &mut self.bundled_pop_relationship,
+            BundledProductOfferingPriceRelationship::from(component),
+        );
+        self
+    }
+
+    /// Builder pattern to relate this price to another price
+    pub fn relate(
+        mut self,
+        related: &ProductOfferingPrice,
+        relationship_type: impl Into<String>,
+    ) -> ProductOfferingPrice {
+        vec_insert(
+            &mut self.pop_relationship,
+            ProductOfferingPriceRelationship {
+                id: related.get_id(),
+                href: related.href.clone(),
+                name: related.name.clone(),
+                relationship_type: relationship_type.into(),
+            },
+        );
+        self
+    }
+
+    /// Builder pattern to add a price tier covering quantities from `lower_bound`
+    /// up to and including `upper_bound`
+    pub fn tier(
+        mut self,
+        lower_bound: u32,
+        upper_bound: Option<u32>,
+        price: Money,
+    ) -> ProductOfferingPrice {
+        vec_insert(
+            &mut self.price_tier,
+            PriceTier {
+                lower_bound,
+                upper_bound,
+                price,
+            },
+        );
+        if let Some(tiers) = self.price_tier.as_mut() {
+            tiers.sort_by_key(|t| t.lower_bound);
+        }
+        self
+    }
+
+    /// Builder pattern to set how price tiers are applied
+    pub fn tier_type(mut self, tier_type: TierType) -> ProductOfferingPrice {
+        self.tier_type = Some(tier_type);
+        self
+    }
+
+    /// Is this a bundled price made up of component prices
+    pub fn is_bundle(&self) -> bool {
+        self.is_bundle.unwrap_or_default()
+    }
+
+    /// Get the ids of the component prices of a bundled price
+    pub fn get_components(&self) -> Vec<String> {
+        self.bundled_pop_relationship
+            .iter()
+            .flatten()
+            .map(|r| r.id.clone())
+            .collect()
+    }
+
+    /// Calculate the tax exclusive amount for a quantity using this price's tiers, or the
+    /// flat price if no tiers are defined.
+    pub fn tier_amount(&self, quantity: u32) -> Result<Money, TMFError> {
+        let tiers = match self.price_tier.as_ref() {
+            Some(t) if !t.is_empty() => t,
+            _ => {
+                let price = self.price.clone().ok_or(TMFError::NoDataError(format!(
+                    "Price {} has no amount",
+                    self.get_name()
+                )))?;
+                return Ok(price * quantity);
+            }
+        };
+        let unit = tiers[0].price.unit.clone();
+        let mut amount = Money {
+            unit: unit.clone(),
+            value: Decimal::ZERO,
+        };
+        if quantity == 0 {
+            return Ok(amount);
+        }
+        if tiers.iter().any(|t| t.price.unit != unit) {
+            return Err(TMFError::CurrencyError(format!(
+                "Tiers of price {} use different currencies",
+                self.get_name()
+            )));
+        }
+        let bands = self.tier_bands()?;
+        match self.tier_type.clone().unwrap_or_default() {
+            TierType::Volume => {
+                let (_, tier) = bands
+                    .iter()
+                    .find(|(start, t)| {
+                        quantity > *start && quantity <= t.upper_bound.unwrap_or(u32::MAX)
+                    })
+                    .ok_or(TMFError::NoDataError(format!(
+                        "No tier of price {} covers quantity {quantity}",
+                        self.get_name()
+                    )))?;
+                amount = tier.price.clone() * quantity;
+            }
+            TierType::Tiered => {
+                let mut covered = 0;
+                for (start, tier) in bands {
+                    if covered >= quantity {
+                        break;
+                    }
+                    let top = tier.upper_bound.unwrap_or(u32::MAX).min(quantity);
+                    amount += tier.price.clone() * (top - start);
+                    covered = top;
+                }
+                if covered < quantity {
+                    return Err(TMFError::NoDataError(format!(
+                        "Tiers of price {} do not cover quantity {quantity}",
+                        self.get_name()
+                    )));
+                }
+            }
+        }
+        Ok(amount)
+    }
+
+    /// Validate the tiers and return each with the quantity it starts after. Tiers must be contiguous from zero and may give their
+    /// lower bound as the last quantity of the previous tier, e.g. 0-10 then 10-20, or as the
+    /// first quantity of their own, e.g. 1-10 then 11-20.
+    fn tier_bands(&self) -> Result<Vec<(u32, &PriceTier)>, TMFError> {
+        let mut tiers: Vec<&PriceTier> = self.price_tier.iter().flatten().collect();
+        tiers.sort_by_key(|t| t.lower_bound);
+        let mut bands = vec![];
+        let mut previous: Option<u32> = Some(0);
+        for tier in tiers {
+            let start = match previous {
+                Some(p) if tier.lower_bound == p || p.checked_add(1) == Some(tier.lower_bound) => p,
+                Some(p) => {
+                    return Err(TMFError::TierError(format!(
+                        "Tier of price {} starting at {} leaves a gap after {p}",
+                        self.get_name(),
+                        tier.lower_bound
+                    )))
+                }
+                None => {
+                    return Err(TMFError::TierError(format!(
+                        "Tier of price {} starting at {} overlaps an unbounded tier",
+                        self.get_name(),
+                        tier.lower_bound
+                    )))
+                }
+            };
+            if let Some(upper) = tier.upper_bound {
+                if upper < tier.lower_bound {
+                    return Err(TMFError::TierError(format!(
+                        "Tier of price {} has upper bound {upper} below lower bound {}",
+                        self.get_name(),
+                        tier.lower_bound
+                    )));
+                }
+            }
+            bands.push((start, tier));
+            previous = tier.upper_bound;
+        }
+        Ok(bands)
+    }
+
+    /// Get the type of this price, defaults to [`PriceType::OneTime`] if not set
+    pub fn get_price_type(&self) -> Result<PriceType, TMFError> {
+        match &self.price_type {
+            Some(t) => PriceType::try_from(t.as_str()),
+            None => Ok(PriceType::default()),
+        }
+    }
+
+    /// Get the (tax exclusive) price if set
+    pub fn get_price(&self) -> Option<&Money> {
+        self.price.as_ref()
+    }
+
+    /// Get the recurring charge period type, e.g. month
+    pub fn get_recurring_period(&self) -> Option<String> {
+        self.recurring_charge_period_type.clone()
+    }
+
+    /// Get the unit of measure
+    pub fn get_unit_of_measure(&self) -> Option<String> {
+        self.unit_of_measure.clone()
+    }
+
+    /// Get the percentage if set
+    pub fn get_percentage(&self) -> Option<f32> {
+        self.percentage
+    }
+
+    /// Get the list of tax items
+    pub fn get_tax(&self) -> Vec<TaxItem> {
+        self.tax.clone().unwrap_or_default()
+    }
+
+    /// Get the combined rate of all tax items, or None if no tax items are set
+    pub fn get_tax_rate(&self) -> Option<f32> {
+        match self.tax.as_deref() {
+            // Summed as decimals so e.g. 0.05 and 0.07 give exactly 0.12
+            Some(tax) if !tax.is_empty() => tax
+                .iter()
+                .map(|t| Decimal::from_f32(t.tax_rate).unwrap_or_default())
+                .sum::<Decimal>()
+                .to_f32(),
+            _ => None,
+        }
+    }
 }
 
 /// Container for the payload that generated the event
@@ -192,6 +576,79 @@ mod test {
         assert_eq!(pop.version, Some(PRICE_VERS.into()));
     }
 
+    #[test]
+    fn test_price_type() {
+        let pop = ProductOfferingPrice::new(POP).recurring("month", 1);
+
+        assert_eq!(pop.get_price_type().unwrap(), PriceType::Recurring);
+        assert_eq!(pop.get_recurring_period(), Some("month".to_string()));
+        assert_eq!(PriceType::try_from("oneTime").unwrap(), PriceType::OneTime);
+        assert!(PriceType::try_from("unknown").is_err());
+    }
+
+    #[test]
+    fn test_price_tiered() {
+        let pop = ProductOfferingPrice::new(POP)
+            .tier(10, None, Money::from(1))
+            .tier(0, Some(10), Money::from(2));
+
+        assert_eq!(pop.tier_amount(0).unwrap(), Money::from(0));
+        assert_eq!(pop.tier_amount(5).unwrap(), Money::from(10));
+        assert_eq!(pop.tier_amount(15).unwrap(), Money::from(25));
+    }
+
+    #[test]
+    fn test_price_volume() {
+        let pop = ProductOfferingPrice::new(POP)
+            .tier(0, Some(10), Money::from(2))
+            .tier(10, None, Money::from(1))
+            .tier_type(TierType::Volume);
+
+        assert_eq!(pop.tier_amount(10).unwrap(), Money::from(20));
+        assert_eq!(pop.tier_amount(15).unwrap(), Money::from(15));
+    }
+
+    #[test]
+    fn test_price_tier_from_one() {
+        let pop = ProductOfferingPrice::new(POP)
+            .tier(1, Some(10), Money::from(2))
+            .tier(11, None, Money::from(1));
+
+        assert_eq!(pop.tier_amount(15).unwrap(), Money::from(25));
+        assert_eq!(
+            pop.tier_type(TierType::Volume).tier_amount(11).unwrap(),
+            Money::from(11)
+        );
+    }
+
+    #[test]
+    fn test_price_tier_invalid() {
+        let pop = ProductOfferingPrice::new(POP).tier(10, Some(5), Money::from(2));
+        assert!(matches!(pop.tier_amount(3), Err(TMFError::TierError(_))));
+
+        let pop = ProductOfferingPrice::new(POP)
+            .tier(0, Some(10), Money::from(2))
+            .tier(5, None, Money::from(1));
+        assert!(matches!(pop.tier_amount(3), Err(TMFError::TierError(_))));
+    }
+
+    #[test]
+    fn test_price_tier_gap() {
+        let pop = ProductOfferingPrice::new(POP).tier(0, Some(10), Money::from(2));
+
+        assert!(pop.tier_amount(11).is_err());
+        assert!(pop.tier_type(TierType::Volume).tier_amount(11).is_err());
+    }
+
+    #[test]
+    fn test_price_bundle() {
+        let component = ProductOfferingPrice::new("Component");
+        let bundle = ProductOfferingPrice::new(POP).bundle(&component);
+
+        assert!(bundle.is_bundle());
+        assert_eq!(bundle.get_components(), vec![component.get_id()]);
+    }
+
     #[test]
     fn test_priceref_from_price() {
         let price = ProductOfferingPrice::new(POP);
@@ -19,4 +19,6 @@ const MOD_PATH: &str = "troubleTicket/v4";
 #[cfg(feature = "build-V5")]
 const MOD_PATH: &str = "troubleTicket/v5";
 
+pub mod sla;
+pub mod status;
 pub mod trouble_ticket;
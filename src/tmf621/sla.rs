@@ -0,0 +1,271 @@
+//! Trouble Ticket SLA Module
+//!
+//! Measures the time taken to resolve a ticket against a target for its severity.
+//! The clock runs from when the ticket is acknowledged, pauses while the ticket is pending or held
+//! and stops once the ticket is resolved, restarting if the ticket is reopened.
+//! ```
+//! use chrono::{Duration, Utc};
+//! use tmflib::tmf621::sla::{SlaPolicy, SlaStatus};
+//! use tmflib::tmf621::trouble_ticket::TroubleTicket;
+//!
+//! let mut ticket = TroubleTicket::new("No dial tone");
+//! ticket.severity = Some("Critical".to_string());
+//! let report = SlaPolicy::default().evaluate(&ticket, Utc::now() + Duration::hours(5)).unwrap();
+//! assert_eq!(report.status, SlaStatus::Breached);
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::trouble_ticket::TroubleTicket;
+use crate::common::tmf_error::TMFError;
+use crate::HasId;
+
+/// Default fraction of the target after which a ticket is at risk
+const DEFAULT_AT_RISK: f32 = 0.75;
+
+/// Status of a ticket against its SLA target
+#[derive(Clone, Debug, PartialEq)]
+pub enum SlaStatus {
+    /// Clock is running with time to spare
+    OnTrack,
+    /// Clock is running and has passed the at risk threshold
+    AtRisk,
+    /// Target has been exceeded
+    Breached,
+    /// Ticket was resolved within the target
+    Met,
+}
+
+/// Result of evaluating a ticket against its SLA target
+#[derive(Clone, Debug)]
+pub struct SlaReport {
+    /// Target time to resolve
+    pub target: Duration,
+    /// Time counted against the target so far
+    pub elapsed: Duration,
+    /// Time remaining, negative once breached
+    pub remaining: Duration,
+    /// Status against the target
+    pub status: SlaStatus,
+}
+
+/// Resolution targets per ticket severity
+#[derive(Clone, Debug)]
+pub struct SlaPolicy {
+    targets: HashMap<String, Duration>,
+    at_risk: f32,
+}
+
+impl Default for SlaPolicy {
+    fn default() -> Self {
+        SlaPolicy::new()
+            .target("Critical", Duration::hours(4))
+            .target("Major", Duration::hours(8))
+            .target("Minor", Duration::hours(24))
+            .target("Warning", Duration::hours(72))
+    }
+}
+
+impl SlaPolicy {
+    /// Create a policy with no targets
+    pub fn new() -> SlaPolicy {
+        SlaPolicy {
+            targets: HashMap::new(),
+            at_risk: DEFAULT_AT_RISK,
+        }
+    }
+
+    /// Builder pattern to set the resolution target for a severity
+    pub fn target(mut self, severity: impl Into<String>, target: Duration) -> SlaPolicy {
+        self.targets.insert(severity.into(), target);
+        self
+    }
+
+    /// Builder pattern to set the fraction of the target after which a ticket is at risk, e.g. 0.8
+    pub fn at_risk(mut self, fraction: f32) -> SlaPolicy {
+        self.at_risk = fraction;
+        self
+    }
+
+    /// Evaluate a ticket against the target for its severity as at the given time
+    pub fn evaluate(
+        &self,
+        ticket: &TroubleTicket,
+        now: DateTime<Utc>,
+    ) -> Result<SlaReport, TMFError> {
+        let severity = ticket
+            .severity
+            .clone()
+            .ok_or(TMFError::NoDataError(format!(
+                "severity on ticket {}",
+                ticket.get_id()
+            )))?;
+        let target = self
+            .targets
+            .get(&severity)
+            .cloned()
+            .ok_or(TMFError::NoDataError(format!("SLA target for {severity}")))?;
+        let elapsed = elapsed(ticket, now)?;
+        let remaining = target - elapsed;
+        let stopped = ticket.get_status().is_some_and(|s| s.is_stopped());
+        let threshold = target.num_seconds() as f32 * self.at_risk;
+        let status = match (stopped, remaining < Duration::zero()) {
+            (_, true) => SlaStatus::Breached,
+            (true, false) => SlaStatus::Met,
+            (false, false) if elapsed.num_seconds() as f32 >= threshold => SlaStatus::AtRisk,
+            (false, false) => SlaStatus::OnTrack,
+        };
+        Ok(SlaReport {
+            target,
+            elapsed,
+            remaining,
+            status,
+        })
+    }
+
+    /// Open tickets that are at risk or have breached their target and should be escalated.
+    /// Tickets that cannot be evaluated are skipped.
+    pub fn escalations<'a>(
+        &self,
+        tickets: &'a [TroubleTicket],
+        now: DateTime<Utc>,
+    ) -> Vec<(&'a TroubleTicket, SlaReport)> {
+        tickets
+            .iter()
+            .filter(|t| !t.get_status().is_some_and(|s| s.is_stopped()))
+            .filter_map(|t| self.evaluate(t, now).ok().map(|r| (t, r)))
+            .filter(|(_, r)| matches!(r.status, SlaStatus::AtRisk | SlaStatus::Breached))
+            .collect()
+    }
+}
+
+/// Time counted against the SLA for a ticket as at the given time, based on its status history.
+/// Time spent pending, held or resolved is not counted.
+pub fn elapsed(ticket: &TroubleTicket, now: DateTime<Utc>) -> Result<Duration, TMFError> {
+    let history = ticket.get_status_change();
+    if history.is_empty() {
+        return Err(TMFError::NoDataError(format!(
+            "status history on ticket {}",
+            ticket.get_id()
+        )));
+    }
+    let mut total = Duration::zero();
+    for (i, change) in history.iter().enumerate() {
+        if change.status.is_paused() || change.status.is_stopped() {
+            continue;
+        }
+        let start = parse(&change.change_date)?;
+        let end = match history.get(i + 1) {
+            Some(next) => parse(&next.change_date)?,
+            None => now,
+        };
+        if end > start {
+            total += end - start;
+        }
+    }
+    Ok(total)
+}
+
+fn parse(time: &str) -> Result<DateTime<Utc>, TMFError> {
+    DateTime::parse_from_rfc3339(time)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| TMFError::InvalidTimePeriod(format!("Invalid status change date {time}: {e}")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tmf621::status::{StatusChange, TroubleTicketStatusType};
+
+    fn ticket(history: &[(TroubleTicketStatusType, &str)]) -> TroubleTicket {
+        let mut ticket = TroubleTicket::new("Ticket");
+        ticket.severity = Some("Major".to_string());
+        ticket.set_status_change(
+            history
+                .iter()
+                .map(|(status, date)| StatusChange {
+                    status: status.clone(),
+                    change_date: date.to_string(),
+                    change_reason: None,
+                })
+                .collect(),
+        );
+        ticket
+    }
+
+    fn at(time: &str) -> DateTime<Utc> {
+        parse(time).unwrap()
+    }
+
+    #[test]
+    fn test_sla_pause() {
+        use TroubleTicketStatusType::*;
+        let ticket = ticket(&[
+            (Acknowledged, "2024-01-01T00:00:00+00:00"),
+            (InProgress, "2024-01-01T01:00:00+00:00"),
+            (Pending, "2024-01-01T02:00:00+00:00"),
+            (InProgress, "2024-01-01T05:00:00+00:00"),
+        ]);
+
+        let elapsed = elapsed(&ticket, at("2024-01-01T06:00:00+00:00")).unwrap();
+
+        assert_eq!(elapsed, Duration::hours(3));
+    }
+
+    #[test]
+    fn test_sla_invalid_date() {
+        use TroubleTicketStatusType::*;
+        let ticket = ticket(&[(InProgress, "yesterday")]);
+
+        assert!(matches!(
+            elapsed(&ticket, at("2024-01-01T06:00:00+00:00")),
+            Err(TMFError::InvalidTimePeriod(_))
+        ));
+    }
+
+    #[test]
+    fn test_sla_status() {
+        use TroubleTicketStatusType::*;
+        let policy = SlaPolicy::default();
+        let ticket = ticket(&[(InProgress, "2024-01-01T00:00:00+00:00")]);
+
+        let on_track = policy
+            .evaluate(&ticket, at("2024-01-01T01:00:00+00:00"))
+            .unwrap();
+        let at_risk = policy
+            .evaluate(&ticket, at("2024-01-01T07:00:00+00:00"))
+            .unwrap();
+        let breached = policy
+            .evaluate(&ticket, at("2024-01-01T09:00:00+00:00"))
+            .unwrap();
+
+        assert_eq!(on_track.status, SlaStatus::OnTrack);
+        assert_eq!(at_risk.status, SlaStatus::AtRisk);
+        assert_eq!(breached.status, SlaStatus::Breached);
+        assert_eq!(breached.remaining, Duration::hours(-1));
+        assert_eq!(
+            policy
+                .escalations(&[ticket], at("2024-01-01T09:00:00+00:00"))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_sla_met() {
+        use TroubleTicketStatusType::*;
+        let ticket = ticket(&[
+            (InProgress, "2024-01-01T00:00:00+00:00"),
+            (Resolved, "2024-01-01T02:00:00+00:00"),
+        ]);
+
+        let report = SlaPolicy::default()
+            .evaluate(&ticket, at("2024-01-02T00:00:00+00:00"))
+            .unwrap();
+
+        assert_eq!(report.elapsed, Duration::hours(2));
+        assert_eq!(report.status, SlaStatus::Met);
+    }
+}
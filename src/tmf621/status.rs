@@ -0,0 +1,141 @@
+//! Trouble Ticket Status Module
+//!
+//! Lifecycle of a trouble ticket as defined by TMF621.
+//! ```text
+//! acknowledged -> inProgress | rejected | cancelled
+//! inProgress   -> pending | held | resolved | cancelled
+//! pending      -> inProgress | cancelled
+//! held         -> inProgress | cancelled
+//! resolved     -> closed | inProgress
+//! ```
+//! Rejected, cancelled and closed are terminal states.
+
+use serde::{Deserialize, Serialize};
+
+use crate::DateTime;
+
+/// Trouble Ticket Status
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TroubleTicketStatusType {
+    /// Ticket has been received and acknowledged
+    #[default]
+    Acknowledged,
+    /// Ticket has been rejected
+    Rejected,
+    /// Waiting on information from the originator
+    Pending,
+    /// Held, e.g. waiting on a third party
+    Held,
+    /// Ticket is being worked on
+    InProgress,
+    /// Ticket has been cancelled
+    Cancelled,
+    /// Ticket has been closed
+    Closed,
+    /// Issue has been resolved
+    Resolved,
+}
+
+impl TroubleTicketStatusType {
+    /// Returns true if a ticket may move from this status to the next status
+    pub fn can_transition(&self, next: &TroubleTicketStatusType) -> bool {
+        use TroubleTicketStatusType::*;
+        matches!(
+            (self, next),
+            (Acknowledged, InProgress | Rejected | Cancelled)
+                | (InProgress, Pending | Held | Resolved | Cancelled)
+                | (Pending, InProgress | Cancelled)
+                | (Held, InProgress | Cancelled)
+                | (Resolved, Closed | InProgress)
+        )
+    }
+
+    /// Returns true if no further transitions are possible
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TroubleTicketStatusType::Rejected
+                | TroubleTicketStatusType::Cancelled
+                | TroubleTicketStatusType::Closed
+        )
+    }
+
+    /// Statuses a ticket moves through from this status to be resolved and then closed,
+    /// or None if the ticket is already finished
+    pub fn closing_steps(&self) -> Option<Vec<TroubleTicketStatusType>> {
+        use TroubleTicketStatusType::*;
+        match self {
+            Acknowledged | Pending | Held => Some(vec![InProgress, Resolved, Closed]),
+            InProgress => Some(vec![Resolved, Closed]),
+            Resolved => Some(vec![Closed]),
+            Rejected | Cancelled | Closed => None,
+        }
+    }
+
+    /// Returns true if the SLA clock is paused while in this status
+    pub fn is_paused(&self) -> bool {
+        matches!(
+            self,
+            TroubleTicketStatusType::Pending | TroubleTicketStatusType::Held
+        )
+    }
+
+    /// Returns true if the SLA clock has stopped, i.e. the ticket has been resolved or finished
+    pub fn is_stopped(&self) -> bool {
+        self.is_terminal() || *self == TroubleTicketStatusType::Resolved
+    }
+}
+
+impl std::fmt::Display for TroubleTicketStatusType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = serde_json::to_value(self).unwrap_or_default();
+        write!(f, "{}", status.as_str().unwrap_or_default())
+    }
+}
+
+/// Record of a change in status of a trouble ticket
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChange {
+    /// New status
+    pub status: TroubleTicketStatusType,
+    /// When the status changed
+    pub change_date: DateTime,
+    /// Why the status changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_reason: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_status_transition() {
+        use TroubleTicketStatusType::*;
+
+        assert!(Acknowledged.can_transition(&InProgress));
+        assert!(InProgress.can_transition(&Held));
+        assert!(Resolved.can_transition(&InProgress));
+        assert!(!Closed.can_transition(&InProgress));
+        assert!(!Pending.can_transition(&Resolved));
+        for status in [Acknowledged, Pending, Held, InProgress, Resolved] {
+            let mut current = status;
+            for step in current.closing_steps().unwrap() {
+                assert!(current.can_transition(&step));
+                current = step;
+            }
+            assert_eq!(current, Closed);
+        }
+        assert!(Cancelled.closing_steps().is_none());
+    }
+
+    #[test]
+    fn test_status_serde() {
+        let status: TroubleTicketStatusType = serde_json::from_str("\"inProgress\"").unwrap();
+
+        assert_eq!(status, TroubleTicketStatusType::InProgress);
+        assert_eq!(status.to_string(), "inProgress");
+    }
+}
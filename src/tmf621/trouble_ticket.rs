@@ -1,25 +1,44 @@
 //! Trouble Ticket Module
 
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
 use tmflib_derive::{
     HasAttachment, HasDescription, HasId, HasLastUpdate, HasName, HasNote, HasRelatedParty,
 };
+use uuid::Uuid;
 
 use crate::common::attachment::AttachmentRefOrValue;
 use crate::common::event::{Event, EventPayload};
 use crate::common::note::Note;
+use crate::common::related_entity::RelatedEntity;
 use crate::common::related_party::RelatedParty;
+use crate::common::related_place::RelatedPlaceRefOrValue;
 use crate::common::tmf_error::TMFError;
+#[cfg(feature = "tmf642")]
+use crate::tmf642::{correlation::AlarmSummary, Alarm, PerceivedSeverity};
 use crate::{
     DateTime, HasAttachment, HasDescription, HasId, HasLastUpdate, HasName, HasNote,
     HasRelatedParty, TMFEvent, Uri,
 };
 
+use super::status::{StatusChange, TroubleTicketStatusType};
 // URL Path components
 use super::MOD_PATH;
+use crate::now;
 
 const CLASS_PATH: &str = "troubleTicket";
+/// Relationship from a secondary ticket to the master it was merged into
+pub const RELATIONSHIP_DUPLICATE: &str = "duplicate";
+/// Referred type used when a ticket references an alarm
+const ALARM_TYPE: &str = "Alarm";
+/// Role of the alarm identified as the root cause
+pub const ROLE_ROOT_CAUSE: &str = "rootCause";
+/// Role of an alarm correlated with the root cause
+pub const ROLE_CORRELATED: &str = "correlatedAlarm";
+/// Role of an object the alarm was raised against
+pub const ROLE_ALARMED_OBJECT: &str = "alarmedObject";
+/// Role of a service affected by the alarm
+pub const ROLE_AFFECTED_SERVICE: &str = "affectedService";
 
 /// Trouble Ticket
 #[derive(
@@ -48,6 +67,22 @@ pub struct TroubleTicket {
     last_update: Option<DateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
     description: Option<String>,
+    /// Severity of the issue, e.g. Critical, Major, Minor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    /// Priority of the ticket, e.g. Critical, High, Medium, Low
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    /// Type of ticket
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticket_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<TroubleTicketStatusType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status_change: Option<Vec<StatusChange>>,
+    /// When the ticket was resolved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution_date: Option<DateTime>,
     // Referenced fields
     #[serde(skip_serializing_if = "Option::is_none")]
     note: Option<Vec<Note>>,
@@ -55,16 +90,352 @@ pub struct TroubleTicket {
     related_party: Option<Vec<RelatedParty>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     attachment: Option<Vec<AttachmentRefOrValue>>,
+    /// Entities related to this ticket, e.g. the alarms that raised it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_entity: Option<Vec<RelatedEntity>>,
+    /// Places affected by this ticket
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub place: Option<Vec<RelatedPlaceRefOrValue>>,
+    /// Related trouble tickets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trouble_ticket_relationship: Option<Vec<TroubleTicketRelationship>>,
 }
 
 impl TroubleTicket {
     /// Create a new trouble ticket
     pub fn new(name: impl Into<String>) -> TroubleTicket {
+        let status = TroubleTicketStatusType::default();
         TroubleTicket {
             name: Some(name.into()),
+            status: Some(status.clone()),
+            status_change: Some(vec![StatusChange {
+                status,
+                change_date: now(),
+                change_reason: None,
+            }]),
             ..TroubleTicket::create_with_time()
         }
     }
+
+    /// Current status of the ticket
+    pub fn get_status(&self) -> Option<TroubleTicketStatusType> {
+        self.status.clone()
+    }
+
+    /// History of status changes, oldest first
+    pub fn get_status_change(&self) -> &[StatusChange] {
+        self.status_change.as_deref().unwrap_or_default()
+    }
+
+    /// Replace the status history, e.g. when loading a ticket from another system.
+    /// The status is set from the most recent change.
+    pub fn set_status_change(&mut self, history: Vec<StatusChange>) {
+        self.status = history.last().map(|c| c.status.clone());
+        self.status_change = Some(history);
+    }
+
+    /// Move the ticket to a new status, validating the transition and recording it in the status history
+    pub fn set_status(
+        &mut self,
+        status: TroubleTicketStatusType,
+        reason: Option<String>,
+    ) -> Result<(), TMFError> {
+        let current = self.status.clone().unwrap_or_default();
+        if !current.can_transition(&status) {
+            return Err(TMFError::StateError(format!(
+                "Ticket {} cannot move from {} to {}",
+                self.get_id(),
+                current,
+                status
+            )));
+        }
+        let time = now();
+        match status {
+            TroubleTicketStatusType::Resolved => self.resolution_date = Some(time.clone()),
+            TroubleTicketStatusType::InProgress => self.resolution_date = None,
+            _ => {}
+        }
+        self.status_change
+            .get_or_insert_with(Vec::new)
+            .push(StatusChange {
+                status: status.clone(),
+                change_date: time,
+                change_reason: reason,
+            });
+        self.status = Some(status);
+        self.set_last_update(TroubleTicket::get_timestamp());
+        Ok(())
+    }
+}
+
+impl TroubleTicket {
+    /// Merge secondary tickets reporting the same issue into this (master) ticket.
+    /// - Notes, attachments and related parties are copied to the master, skipping any already present.
+    /// - Each secondary is given a [`RELATIONSHIP_DUPLICATE`] relationship to the master, then
+    ///   resolved (if not already) and closed.
+    /// - A ticket listed more than once is only merged once.
+    ///
+    /// Returns an attribute value change event for the master and a status change event per secondary.
+    /// All secondaries are checked first, nothing is changed if the master or any secondary is already finished.
+    pub fn merge(
+        &mut self,
+        secondaries: &mut [TroubleTicket],
+    ) -> Result<Vec<Event<TroubleTicketEvent, TroubleTicketEventType>>, TMFError> {
+        if self.status.as_ref().is_some_and(|s| s.is_terminal()) {
+            return Err(TMFError::StateError(format!(
+                "Master ticket {} is {}",
+                self.get_id(),
+                self.status.clone().unwrap_or_default()
+            )));
+        }
+        let mut ids: Vec<String> = vec![];
+        let mut merged = vec![];
+        for (i, secondary) in secondaries.iter().enumerate() {
+            let id = secondary.get_id();
+            if id == self.get_id() {
+                return Err(TMFError::InvalidReference(format!(
+                    "Ticket {} cannot be merged into itself",
+                    self.get_id()
+                )));
+            }
+            let status = secondary.status.clone().unwrap_or_default();
+            let steps = status.closing_steps().ok_or(TMFError::StateError(format!(
+                "Secondary ticket {id} is {status}"
+            )))?;
+            if !ids.contains(&id) {
+                ids.push(id);
+                merged.push((i, steps));
+            }
+        }
+
+        let mut events = vec![];
+        let reason = format!("Duplicate of {}", self.get_id());
+        for (i, steps) in merged {
+            let secondary = &mut secondaries[i];
+            for note in secondary.note.iter().flatten() {
+                let notes = self.note.get_or_insert_with(Vec::new);
+                if !notes.contains(note) {
+                    notes.push(note.clone());
+                }
+            }
+            for attachment in secondary.attachment.iter().flatten() {
+                let attachments = self.attachment.get_or_insert_with(Vec::new);
+                let duplicate = attachments.iter().any(|a| match (&a.id, &attachment.id) {
+                    (Some(x), Some(y)) => x == y,
+                    _ => {
+                        a.name == attachment.name
+                            && a.url == attachment.url
+                            && a.content == attachment.content
+                    }
+                });
+                if !duplicate {
+                    attachments.push(attachment.clone());
+                }
+            }
+            for party in secondary.related_party.iter().flatten() {
+                let parties = self.related_party.get_or_insert_with(Vec::new);
+                if !parties
+                    .iter()
+                    .any(|p| p.id == party.id && p.role == party.role)
+                {
+                    parties.push(party.clone());
+                }
+            }
+
+            secondary
+                .trouble_ticket_relationship
+                .get_or_insert_with(Vec::new)
+                .push(
+                    TroubleTicketRelationship::from(self.clone())
+                        .relationship(RELATIONSHIP_DUPLICATE),
+                );
+            for status in steps {
+                secondary.set_status(status, Some(reason.clone()))?;
+            }
+            events.push(secondary.to_event(TroubleTicketEventType::TroubleTicketStatusChangeEvent));
+        }
+        self.set_last_update(TroubleTicket::get_timestamp());
+        events.insert(
+            0,
+            self.to_event(TroubleTicketEventType::TroubleTicketAttributeValueChangeEvent),
+        );
+        Ok(events)
+    }
+
+    /// When the ticket was raised, taken from the first status change
+    fn raised(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        self.get_status_change()
+            .first()
+            .and_then(|c| chrono::DateTime::parse_from_rfc3339(&c.change_date).ok())
+    }
+
+    /// Suggest open tickets that are likely duplicates of this ticket, most likely first.
+    /// A candidate must be raised within `window` of this ticket and share at least one related entity or place.
+    pub fn suggest_duplicates<'a>(
+        &self,
+        candidates: &'a [TroubleTicket],
+        window: Duration,
+    ) -> Vec<&'a TroubleTicket> {
+        let raised = self.raised();
+        let mut scored: Vec<(usize, &TroubleTicket)> = candidates
+            .iter()
+            .filter(|c| c.get_id() != self.get_id())
+            .filter(|c| !c.status.as_ref().is_some_and(|s| s.is_terminal()))
+            .filter(|c| match (raised, c.raised()) {
+                (Some(a), Some(b)) => (a - b).abs() <= window,
+                _ => false,
+            })
+            .map(|c| {
+                let entities = c
+                    .related_entity
+                    .iter()
+                    .flatten()
+                    .filter(|e| {
+                        self.related_entity
+                            .iter()
+                            .flatten()
+                            .any(|m| m.id == e.id && m.referred_type == e.referred_type)
+                    })
+                    .count();
+                let places = c
+                    .place
+                    .iter()
+                    .flatten()
+                    .filter(|p| self.place.iter().flatten().any(|m| &m == p))
+                    .count();
+                (entities + places, c)
+            })
+            .filter(|(score, _)| *score > 0)
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, c)| c).collect()
+    }
+}
+
+#[cfg(feature = "tmf642")]
+impl TroubleTicket {
+    /// Raise a ticket from a group of correlated alarms.
+    /// The root cause alarm, or the first alarm if none is flagged, is used to name the ticket.
+    /// The highest severity across the group sets the ticket severity and priority.
+    pub fn from_alarms(alarms: &[Alarm]) -> Result<TroubleTicket, TMFError> {
+        let summary = AlarmSummary::new(alarms)?;
+        let mut ticket = TroubleTicket::new(summary.name.clone());
+        ticket.description = summary.details.clone();
+        ticket.severity = Some(severity_name(&summary.severity));
+        ticket.priority = Some(priority_name(&summary.severity));
+        ticket.ticket_type = Some(ALARM_TYPE.to_string());
+
+        let mut entities: Vec<RelatedEntity> = vec![];
+        let mut places: Vec<RelatedPlaceRefOrValue> = vec![];
+        for alarm in alarms {
+            let role = match summary.is_root(alarm) {
+                true => ROLE_ROOT_CAUSE,
+                false => ROLE_CORRELATED,
+            };
+            entities.push(RelatedEntity {
+                name: alarm
+                    .probable_cause
+                    .as_ref()
+                    .map(|c| c.name())
+                    .unwrap_or_default(),
+                id: alarm.get_id(),
+                href: alarm.get_href(),
+                role: Some(role.to_string()),
+                referred_type: ALARM_TYPE.to_string(),
+            });
+            if let Some(object) = &alarm.alarmed_object {
+                entities.push(RelatedEntity {
+                    name: object.name.clone().unwrap_or_default(),
+                    id: object.id.clone(),
+                    href: object.href.clone().unwrap_or_default(),
+                    role: Some(ROLE_ALARMED_OBJECT.to_string()),
+                    referred_type: object.referred_type.clone().unwrap_or_default(),
+                });
+            }
+            for place in alarm.place.iter() {
+                let place = RelatedPlaceRefOrValue::from(place);
+                if !places.contains(&place) {
+                    places.push(place);
+                }
+            }
+        }
+        for service in summary.services.iter() {
+            entities.push(RelatedEntity {
+                name: service.name.clone().unwrap_or_default(),
+                id: service.id.clone().unwrap_or_default(),
+                href: service.href.clone().unwrap_or_default(),
+                role: Some(ROLE_AFFECTED_SERVICE.to_string()),
+                referred_type: service
+                    .referred_type
+                    .clone()
+                    .unwrap_or(String::from("Service")),
+            });
+        }
+        // An object shared by several alarms is only referenced once
+        let mut seen = vec![];
+        entities.retain(|e| {
+            let key = (e.id.clone(), e.role.clone());
+            let first = !seen.contains(&key);
+            seen.push(key);
+            first
+        });
+        ticket.related_entity = Some(entities);
+        if !places.is_empty() {
+            ticket.place = Some(places);
+        }
+        Ok(ticket)
+    }
+
+    /// Ids of the alarms this ticket was raised from
+    pub fn alarm_ids(&self) -> Vec<String> {
+        self.related_entity
+            .iter()
+            .flatten()
+            .filter(|e| e.referred_type == ALARM_TYPE)
+            .map(|e| e.id.clone())
+            .collect()
+    }
+
+    /// Find the alarms this ticket was raised from within a list of alarms
+    pub fn find_alarms<'a>(&self, alarms: &'a [Alarm]) -> Vec<&'a Alarm> {
+        let ids = self.alarm_ids();
+        alarms
+            .iter()
+            .filter(|a| ids.contains(&a.get_id()))
+            .collect()
+    }
+}
+
+#[cfg(feature = "tmf642")]
+fn severity_name(severity: &PerceivedSeverity) -> String {
+    match severity {
+        PerceivedSeverity::Critical => "Critical",
+        PerceivedSeverity::Major => "Major",
+        PerceivedSeverity::Minor => "Minor",
+        PerceivedSeverity::Warning => "Warning",
+        PerceivedSeverity::Indeterminate => "Indeterminate",
+        PerceivedSeverity::Cleared => "Cleared",
+    }
+    .to_string()
+}
+
+#[cfg(feature = "tmf642")]
+fn priority_name(severity: &PerceivedSeverity) -> String {
+    match severity {
+        PerceivedSeverity::Critical => "Critical",
+        PerceivedSeverity::Major => "High",
+        PerceivedSeverity::Minor => "Medium",
+        _ => "Low",
+    }
+    .to_string()
+}
+
+#[cfg(feature = "tmf642")]
+impl From<&Alarm> for TroubleTicket {
+    fn from(value: &Alarm) -> Self {
+        // A single alarm always provides a root
+        TroubleTicket::from_alarms(std::slice::from_ref(value)).unwrap_or_default()
+    }
 }
 
 /// Trouble Ticket Event Type
@@ -117,6 +488,9 @@ impl EventPayload<TroubleTicketEvent> for TroubleTicket {
         let now = Utc::now();
         let event_time = chrono::DateTime::from_timestamp(now.timestamp(), 0).unwrap();
         Event {
+            event_id: Uuid::new_v4().to_string(),
+            event_time: event_time.to_string(),
+            event_type,
             id: Some(self.get_id()),
             href: Some(self.get_href()),
             description: Some(desc),
@@ -178,6 +552,172 @@ mod test {
         assert_eq!(ticket.get_name(), TICKET_NAME.to_string());
     }
 
+    #[cfg(feature = "tmf642")]
+    #[test]
+    fn test_troubleticket_from_alarms() {
+        use super::{ROLE_AFFECTED_SERVICE, ROLE_ROOT_CAUSE};
+        use crate::tmf642::{
+            Alarm, AlarmType, AlarmedObjectRef, PerceivedSeverity, RelatedPlace, ServiceRef,
+        };
+
+        let mut root = Alarm::new(
+            AlarmedObjectRef::new("router-01"),
+            AlarmType::EquipmentAlarm,
+            PerceivedSeverity::Minor,
+        );
+        root.is_root_cause = Some(true);
+        root.affected_service.push(ServiceRef {
+            id: Some("svc-01".to_string()),
+            ..Default::default()
+        });
+        root.place.push(RelatedPlace::default());
+        let child = Alarm::new(
+            AlarmedObjectRef::new("port-01"),
+            AlarmType::CommunicationsAlarm,
+            PerceivedSeverity::Critical,
+        );
+        let alarms = vec![child.clone(), root.clone()];
+
+        let ticket = TroubleTicket::from_alarms(&alarms).unwrap();
+
+        assert_eq!(ticket.severity, Some("Critical".to_string()));
+        assert_eq!(ticket.priority, Some("Critical".to_string()));
+        assert!(ticket.get_name().contains("router-01"));
+        let entities = ticket.related_entity.clone().unwrap();
+        assert!(entities
+            .iter()
+            .any(|e| e.id == root.get_id() && e.role.as_deref() == Some(ROLE_ROOT_CAUSE)));
+        assert!(entities
+            .iter()
+            .any(|e| e.id == "svc-01" && e.role.as_deref() == Some(ROLE_AFFECTED_SERVICE)));
+        assert_eq!(ticket.place.as_ref().map(|p| p.len()), Some(1));
+        assert_eq!(ticket.find_alarms(&alarms).len(), 2);
+        assert!(TroubleTicket::from_alarms(&[]).is_err());
+    }
+
+    #[test]
+    fn test_troubleticket_status() {
+        use crate::tmf621::status::TroubleTicketStatusType;
+
+        let mut ticket = TroubleTicket::new(TICKET_NAME);
+
+        ticket
+            .set_status(TroubleTicketStatusType::InProgress, None)
+            .unwrap();
+        ticket
+            .set_status(
+                TroubleTicketStatusType::Resolved,
+                Some("Replaced card".to_string()),
+            )
+            .unwrap();
+
+        assert!(ticket
+            .set_status(TroubleTicketStatusType::Pending, None)
+            .is_err());
+        assert!(ticket.resolution_date.is_some());
+        assert_eq!(ticket.get_status_change().len(), 3);
+        assert_eq!(
+            ticket.get_status_change()[2].change_reason,
+            Some("Replaced card".to_string())
+        );
+    }
+
+    #[test]
+    fn test_troubleticket_merge() {
+        use super::{TroubleTicketEventType, RELATIONSHIP_DUPLICATE};
+        use crate::common::note::Note;
+        use crate::common::related_party::RelatedParty;
+        use crate::tmf621::status::TroubleTicketStatusType;
+        use crate::HasNote;
+
+        let note = Note::new("Customer reports no service");
+        let party = RelatedParty {
+            id: "P1".to_string(),
+            ..Default::default()
+        };
+        let mut master = TroubleTicket::new(TICKET_NAME);
+        master.add_note(note.clone());
+        let mut first = TroubleTicket::new("First");
+        first.add_note(note);
+        first.related_party = Some(vec![party.clone()]);
+        let mut second = TroubleTicket::new("Second");
+        second
+            .set_status(TroubleTicketStatusType::InProgress, None)
+            .unwrap();
+        second
+            .set_status(TroubleTicketStatusType::Resolved, None)
+            .unwrap();
+        second.related_party = Some(vec![party]);
+        let duplicate = first.clone();
+        let mut secondaries = vec![first, second, duplicate];
+
+        let events = master.merge(&mut secondaries).unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[0].event_type,
+            TroubleTicketEventType::TroubleTicketAttributeValueChangeEvent
+        ));
+        assert_eq!(master.note.as_ref().map(|n| n.len()), Some(1));
+        assert_eq!(master.related_party.as_ref().map(|p| p.len()), Some(1));
+        assert_eq!(
+            secondaries[0].get_status(),
+            Some(TroubleTicketStatusType::Closed)
+        );
+        assert!(secondaries[0]
+            .get_status_change()
+            .iter()
+            .any(|c| c.status == TroubleTicketStatusType::Resolved));
+        assert_eq!(
+            secondaries[2].get_status(),
+            Some(TroubleTicketStatusType::Acknowledged)
+        );
+        assert_eq!(
+            secondaries[1].get_status(),
+            Some(TroubleTicketStatusType::Closed)
+        );
+        let relationship = &secondaries[0].trouble_ticket_relationship.as_ref().unwrap()[0];
+        assert_eq!(relationship.id, master.get_id());
+        assert_eq!(relationship.relationship_type, RELATIONSHIP_DUPLICATE);
+        assert!(master.merge(&mut secondaries).is_err());
+
+        // A finished secondary stops the merge before any other ticket changes
+        let mut other = TroubleTicket::new("Other");
+        other
+            .set_status(TroubleTicketStatusType::Cancelled, None)
+            .unwrap();
+        let mut secondaries = vec![TroubleTicket::new("Open"), other];
+        let mut master = TroubleTicket::new(TICKET_NAME);
+        assert!(master.merge(&mut secondaries).is_err());
+        assert_eq!(
+            secondaries[0].get_status(),
+            Some(TroubleTicketStatusType::Acknowledged)
+        );
+    }
+
+    #[test]
+    fn test_troubleticket_suggest_duplicates() {
+        use crate::common::related_entity::RelatedEntity;
+        use chrono::Duration;
+
+        let entity = RelatedEntity {
+            id: "router-01".to_string(),
+            referred_type: "Resource".to_string(),
+            ..Default::default()
+        };
+        let mut master = TroubleTicket::new(TICKET_NAME);
+        master.related_entity = Some(vec![entity.clone()]);
+        let mut duplicate = TroubleTicket::new("Duplicate");
+        duplicate.related_entity = Some(vec![entity]);
+        let unrelated = TroubleTicket::new("Unrelated");
+        let candidates = vec![unrelated, duplicate.clone(), master.clone()];
+
+        let suggested = master.suggest_duplicates(&candidates, Duration::hours(1));
+
+        assert_eq!(suggested.len(), 1);
+        assert_eq!(suggested[0].get_id(), duplicate.get_id());
+    }
+
     #[test]
     fn test_troubleticketrelationship_from() {
         let ticket = TroubleTicket::new(TICKET_NAME);
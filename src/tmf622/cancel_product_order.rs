@@ -0,0 +1,251 @@
+//! Cancel Product Order Module
+//!
+//! A [`CancelProductOrder`] is a task resource requesting the cancellation of an existing
+//! [`ProductOrder`]. Applying the task drives the target order into `pendingCancellation` and
+//! completing it moves the order to `cancelled`, with the task state tracking the outcome.
+//! ```
+//! # #[cfg(feature = "build-V4")]
+//! # use tmflib::tmf622::product_order_v4::ProductOrder;
+//! # #[cfg(feature = "build-V5")]
+//! # use tmflib::tmf622::product_order_v5::ProductOrder;
+//! use tmflib::common::cancel::CancelTask;
+//! use tmflib::tmf622::cancel_product_order::CancelProductOrder;
+//! use tmflib::tmf622::{ProductOrderStateType, TaskStateType};
+//!
+//! let mut order = ProductOrder::new();
+//! let mut cancel = CancelProductOrder::new(&order, "Customer changed their mind");
+//! cancel.apply(&mut order).unwrap();
+//! cancel.complete(&mut order).unwrap();
+//! assert_eq!(order.state, Some(ProductOrderStateType::Cancelled));
+//! assert_eq!(cancel.state, Some(TaskStateType::Done));
+//! ```
+
+use serde::{Deserialize, Serialize};
+use tmflib_derive::HasId;
+use uuid::Uuid;
+
+use super::{ProductOrderStateType, MOD_PATH};
+use crate::common::cancel::{CancelTask, Cancellable, TaskStateType};
+use crate::common::event::{Event, EventPayload};
+use crate::common::tmf_error::TMFError;
+#[cfg(feature = "build-V4")]
+use crate::tmf622::product_order_v4::{ProductOrder, ProductOrderRef};
+#[cfg(feature = "build-V5")]
+use crate::tmf622::product_order_v5::{ProductOrder, ProductOrderRef};
+use crate::{now, DateTime, HasId, TMFEvent, Uri};
+
+const CLASS_PATH: &str = "cancelProductOrder";
+
+/// Request to cancel a Product Order
+#[derive(Clone, Debug, Default, Deserialize, HasId, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelProductOrder {
+    /// Unique Id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// HTTP Uri
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub href: Option<Uri>,
+    /// Reason the order is being cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancellation_reason: Option<String>,
+    /// Message describing the outcome of the task, e.g. why it was refused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_message: Option<String>,
+    /// Date the order was actually cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_cancellation_date: Option<DateTime>,
+    /// Date the requester would like the order cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requested_cancellation_date: Option<DateTime>,
+    /// Order to be cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_order: Option<ProductOrderRef>,
+    /// Task State
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<TaskStateType>,
+}
+
+impl CancelProductOrder {
+    /// Create a new cancellation request for a product order
+    pub fn new(order: &ProductOrder, reason: impl Into<String>) -> CancelProductOrder {
+        CancelProductOrder {
+            cancellation_reason: Some(reason.into()),
+            product_order: Some(ProductOrderRef::from(order)),
+            state: Some(TaskStateType::Acknowledged),
+            ..CancelProductOrder::create()
+        }
+    }
+
+    /// Builder pattern to set the requested cancellation date
+    pub fn requested_date(mut self, date: impl Into<DateTime>) -> CancelProductOrder {
+        self.requested_cancellation_date = Some(date.into());
+        self
+    }
+}
+
+impl Cancellable for ProductOrder {
+    fn pending_cancellation(&mut self, reason: Option<String>) -> Result<(), TMFError> {
+        self.set_state(ProductOrderStateType::PendingCancellation)?;
+        self.cancellation_reason = reason;
+        Ok(())
+    }
+
+    fn cancel(&mut self, reason: Option<String>, date: DateTime) -> Result<(), TMFError> {
+        self.set_state(ProductOrderStateType::Cancelled)?;
+        self.cancellation_reason = reason;
+        self.cancellation_date = Some(date);
+        Ok(())
+    }
+}
+
+impl CancelTask for CancelProductOrder {
+    type Order = ProductOrder;
+
+    fn target_id(&self) -> Option<String> {
+        self.product_order.as_ref().map(|r| r.id.clone())
+    }
+
+    fn reason(&self) -> Option<String> {
+        self.cancellation_reason.clone()
+    }
+
+    fn task_state(&self) -> Option<TaskStateType> {
+        self.state.clone()
+    }
+
+    fn set_task_state(&mut self, state: TaskStateType, message: Option<String>) {
+        self.state = Some(state);
+        if message.is_some() {
+            self.completion_message = message;
+        }
+    }
+
+    fn set_effective_date(&mut self, date: DateTime) {
+        self.effective_cancellation_date = Some(date);
+    }
+}
+
+/// Cancel Product Order Event Type
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub enum CancelProductOrderEventType {
+    /// Cancellation request created
+    #[default]
+    CancelProductOrderCreateEvent,
+    /// Cancellation request changed state
+    CancelProductOrderStateChangeEvent,
+    /// Cancellation request requires more information
+    CancelProductOrderInformationRequiredEvent,
+}
+
+/// Cancel Product Order Event Container
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelProductOrderEvent {
+    /// Impacted cancellation request
+    pub cancel_product_order: CancelProductOrder,
+}
+
+impl TMFEvent<CancelProductOrderEvent> for CancelProductOrder {
+    fn event(&self) -> CancelProductOrderEvent {
+        CancelProductOrderEvent {
+            cancel_product_order: self.clone(),
+        }
+    }
+}
+
+impl EventPayload<CancelProductOrderEvent> for CancelProductOrder {
+    type Subject = CancelProductOrder;
+    type EventType = CancelProductOrderEventType;
+
+    fn to_event(
+        &self,
+        event_type: Self::EventType,
+    ) -> Event<CancelProductOrderEvent, Self::EventType> {
+        let desc = format!("{:?} for cancellation {}", event_type, self.get_id());
+        let event_time = now();
+        Event {
+            event_id: Uuid::new_v4().to_string(),
+            id: Some(self.get_id()),
+            href: Some(self.get_href()),
+            description: Some(desc),
+            domain: Some(CancelProductOrder::get_class()),
+            time_occurred: Some(event_time.clone()),
+            event_time,
+            event_type,
+            event: self.event(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const REASON: &str = "No longer required";
+
+    #[test]
+    fn test_cancel_new() {
+        let order = ProductOrder::new();
+        let cancel = CancelProductOrder::new(&order, REASON);
+
+        assert_eq!(cancel.state, Some(TaskStateType::Acknowledged));
+        assert_eq!(cancel.product_order.unwrap().id, order.get_id());
+    }
+
+    #[test]
+    fn test_cancel_apply_complete() {
+        let mut order = ProductOrder::new();
+        let mut cancel = CancelProductOrder::new(&order, REASON);
+
+        cancel.apply(&mut order).unwrap();
+        assert_eq!(
+            order.state,
+            Some(ProductOrderStateType::PendingCancellation)
+        );
+        assert_eq!(cancel.state, Some(TaskStateType::InProgress));
+
+        cancel.complete(&mut order).unwrap();
+        assert_eq!(order.state, Some(ProductOrderStateType::Cancelled));
+        assert_eq!(order.cancellation_reason, Some(REASON.to_string()));
+        assert!(order.cancellation_date.is_some());
+        assert_eq!(cancel.state, Some(TaskStateType::Done));
+    }
+
+    #[test]
+    fn test_cancel_completed_order() {
+        let mut order = ProductOrder::new();
+        order.set_state(ProductOrderStateType::InProgress).unwrap();
+        order.set_state(ProductOrderStateType::Completed).unwrap();
+        let mut cancel = CancelProductOrder::new(&order, REASON);
+
+        let result = cancel.apply(&mut order);
+
+        assert!(result.is_err());
+        assert_eq!(cancel.state, Some(TaskStateType::TerminatedWithError));
+        assert!(cancel.completion_message.is_some());
+        assert_eq!(order.state, Some(ProductOrderStateType::Completed));
+    }
+
+    #[test]
+    fn test_cancel_wrong_order() {
+        let order = ProductOrder::new();
+        let mut other = ProductOrder::new();
+        let mut cancel = CancelProductOrder::new(&order, REASON);
+
+        assert!(cancel.apply(&mut other).is_err());
+    }
+
+    #[test]
+    fn test_cancel_event() {
+        let order = ProductOrder::new();
+        let cancel = CancelProductOrder::new(&order, REASON);
+
+        let event =
+            cancel.to_event(CancelProductOrderEventType::CancelProductOrderStateChangeEvent);
+
+        assert_eq!(event.id, Some(cancel.get_id()));
+        assert_eq!(event.event.cancel_product_order.get_id(), cancel.get_id());
+    }
+}
@@ -14,6 +14,8 @@
 
 //! TMF622 Product Order Management
 
+use serde::{Deserialize, Serialize};
+
 #[cfg(feature = "build-V4")]
 const MOD_PATH: &str = "productOrderingManagement/v4";
 #[cfg(feature = "build-V5")]
@@ -24,6 +26,95 @@ pub mod product_order_v4;
 #[cfg(all(feature = "tmf622", feature = "build-V5"))]
 pub mod product_order_v5;
 
+pub mod cancel_product_order;
 #[cfg(all(feature = "tmf622", feature = "build-V5"))]
 pub mod milestone;
 pub mod product_order_item;
+
+/// Product Order State
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProductOrderStateType {
+    /// Order has been received
+    #[default]
+    Acknowledged,
+    /// Order has been rejected
+    Rejected,
+    /// Order is awaiting further information
+    Pending,
+    /// Order is on hold
+    Held,
+    /// Order is being processed
+    InProgress,
+    /// Order has been cancelled
+    Cancelled,
+    /// Order has been completed
+    Completed,
+    /// Order processing has failed
+    Failed,
+    /// Order has been partially completed
+    Partial,
+    /// Cancellation request is being assessed (e.g. PONR)
+    AssessingCancellation,
+    /// Cancellation has been accepted and is being processed
+    PendingCancellation,
+}
+
+impl ProductOrderStateType {
+    /// Returns true if no further state changes are possible
+    pub fn is_final(&self) -> bool {
+        OrderStateType::from(self).is_final()
+    }
+
+    /// Returns true if the order state machine allows moving to the new state,
+    /// see [`OrderStateType::can_transition`]
+    pub fn can_transition(&self, to: &ProductOrderStateType) -> bool {
+        OrderStateType::from(self).can_transition(&OrderStateType::from(to))
+    }
+}
+
+impl From<&ProductOrderStateType> for OrderStateType {
+    fn from(value: &ProductOrderStateType) -> Self {
+        match value {
+            ProductOrderStateType::Acknowledged => OrderStateType::Acknowledged,
+            ProductOrderStateType::Rejected => OrderStateType::Rejected,
+            ProductOrderStateType::Pending => OrderStateType::Pending,
+            ProductOrderStateType::Held => OrderStateType::Held,
+            ProductOrderStateType::InProgress => OrderStateType::InProgress,
+            ProductOrderStateType::Cancelled => OrderStateType::Cancelled,
+            ProductOrderStateType::Completed => OrderStateType::Completed,
+            ProductOrderStateType::Failed => OrderStateType::Failed,
+            ProductOrderStateType::Partial => OrderStateType::Partial,
+            ProductOrderStateType::AssessingCancellation => OrderStateType::AssessingCancellation,
+            ProductOrderStateType::PendingCancellation => OrderStateType::PendingCancellation,
+        }
+    }
+}
+
+use crate::common::cancel::OrderStateType;
+pub use crate::common::cancel::TaskStateType;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ORDER_STATE_JSON: &str = "\"pendingCancellation\"";
+
+    #[test]
+    fn test_orderstate_deserialize() {
+        let state: ProductOrderStateType = serde_json::from_str(ORDER_STATE_JSON).unwrap();
+
+        assert_eq!(state, ProductOrderStateType::PendingCancellation);
+    }
+
+    #[test]
+    fn test_orderstate_transition() {
+        assert!(ProductOrderStateType::InProgress
+            .can_transition(&ProductOrderStateType::PendingCancellation));
+        assert!(!ProductOrderStateType::Completed
+            .can_transition(&ProductOrderStateType::PendingCancellation));
+        assert!(
+            !ProductOrderStateType::Acknowledged.can_transition(&ProductOrderStateType::Cancelled)
+        );
+    }
+}
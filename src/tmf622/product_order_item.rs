@@ -3,12 +3,23 @@
 use serde::{Deserialize, Serialize};
 use std::convert::From;
 
+use crate::common::dependency::{is_dependency, HasDependency, OrderItemRelationship};
+use crate::common::price::{Price, PriceAlteration};
+use crate::common::tmf_error::TMFError;
+use crate::tmf620::pricing::{
+    PriceComponent, PriceRequest, PriceSummary, PricingEngine, PricingResult,
+};
+
 #[cfg(all(feature = "tmf620", feature = "build-V4"))]
 use crate::tmf620::product_offering::{ProductOffering, ProductOfferingRef};
 #[cfg(all(feature = "tmf620", feature = "build-V5"))]
 use crate::tmf620::product_offering_v5::{ProductOffering, ProductOfferingRef};
 use crate::tmf641::service_order_item::ServiceOrderItem;
+use crate::tmf648::quote::{Quote, QuoteRef, QuoteStateType};
+use crate::tmf648::quote_item::{QuoteItem, QuoteItemRef};
+use crate::tmf648::quote_price::QuotePrice;
 use crate::tmf663::cart_item::CartItem;
+use crate::{vec_insert, HasId};
 
 /// Action Type for Order Items
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
@@ -25,13 +36,300 @@ pub enum OrderItemActionType {
     NoChange,
 }
 
+impl TryFrom<&str> for OrderItemActionType {
+    type Error = TMFError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "add" => Ok(OrderItemActionType::Add),
+            "modify" => Ok(OrderItemActionType::Modify),
+            "delete" => Ok(OrderItemActionType::Delete),
+            "nochange" => Ok(OrderItemActionType::NoChange),
+            _ => Err(TMFError::GenericError(format!(
+                "Unknown order item action: {value}"
+            ))),
+        }
+    }
+}
+
+/// Order pricing, for an order item or the order total
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderPrice {
+    /// Description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Price Type, e.g. recurring
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_type: Option<String>,
+    /// Recurring charge period, e.g. month
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurring_charge_period: Option<String>,
+    /// Unit of measure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_of_measure: Option<String>,
+    /// Price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<Price>,
+    /// Alterations (e.g. discounts) applied to this price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_alteration: Option<Vec<PriceAlteration>>,
+}
+
+impl From<&PriceComponent> for OrderPrice {
+    fn from(value: &PriceComponent) -> Self {
+        OrderPrice {
+            name: Some(value.name.clone()),
+            price_type: Some(value.price_type.to_string()),
+            recurring_charge_period: value.recurring_charge_period.clone(),
+            unit_of_measure: value.unit_of_measure.clone(),
+            price: Some(value.price.clone()),
+            price_alteration: match value.price_alteration.is_empty() {
+                true => None,
+                false => Some(value.price_alteration.clone()),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&QuotePrice> for OrderPrice {
+    fn from(value: &QuotePrice) -> Self {
+        OrderPrice {
+            description: value.description.clone(),
+            name: value.name.clone(),
+            price_type: value.get_price_type(),
+            recurring_charge_period: value.get_period(),
+            unit_of_measure: value.get_unit_of_measure(),
+            price: value.price.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&PriceSummary> for OrderPrice {
+    fn from(value: &PriceSummary) -> Self {
+        OrderPrice {
+            description: Some(String::from("Order Total")),
+            name: Some(value.price_type.to_string()),
+            price_type: Some(value.price_type.to_string()),
+            recurring_charge_period: value.recurring_charge_period.clone(),
+            price: Some(value.price.clone()),
+            ..Default::default()
+        }
+    }
+}
+
 /// Line item for a Product Order
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProductOrderItem {
+    /// Identifier of the line item within the order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     quantity: u16,
     action: OrderItemActionType,
     product_offering: Option<ProductOfferingRef>,
+    /// Relationships to other items within the same order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_order_item_relationship: Option<Vec<OrderItemRelationship>>,
+    /// Calculated prices for this item
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_price: Option<Vec<OrderPrice>>,
+    /// Quote item this order item was created from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_item: Option<QuoteItemRef>,
+}
+
+impl ProductOrderItem {
+    /// Builder pattern to set the id of this order item
+    pub fn id(mut self, id: impl Into<String>) -> ProductOrderItem {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Builder pattern to add a dependency on another item in the same order
+    pub fn depends_on(mut self, id: impl Into<String>) -> ProductOrderItem {
+        vec_insert(
+            &mut self.product_order_item_relationship,
+            OrderItemRelationship::new("dependsOn", id),
+        );
+        self
+    }
+}
+
+impl HasDependency for ProductOrderItem {
+    fn get_item_id(&self) -> Option<String> {
+        self.id.clone()
+    }
+
+    fn get_dependencies(&self) -> Vec<String> {
+        self.product_order_item_relationship
+            .iter()
+            .flatten()
+            .filter(|r| is_dependency(&r.relationship_type))
+            .map(|r| r.id.clone())
+            .collect()
+    }
+}
+
+/// Price `items`, setting the price of each item. Items without a product offering have nothing
+/// to price and are skipped. Prices are matched to items by item id.
+pub(crate) fn price_items(
+    items: &mut [ProductOrderItem],
+    engine: &PricingEngine,
+) -> Result<PricingResult, TMFError> {
+    let requests: Vec<PriceRequest> = items
+        .iter()
+        .filter(|i| i.product_offering.is_some())
+        .map(PriceRequest::from)
+        .collect();
+    let result = engine.price(&requests)?;
+    let mut used = vec![false; result.items.len()];
+    for item in items.iter_mut() {
+        if item.product_offering.is_none() {
+            item.item_price = None;
+            continue;
+        }
+        // Each price is used once, so items sharing an id (or without one) keep their order
+        let found = (0..used.len()).find(|i| !used[*i] && result.items[*i].item_id == item.id);
+        item.item_price = found.map(|i| {
+            used[i] = true;
+            result.items[i]
+                .components
+                .iter()
+                .map(OrderPrice::from)
+                .collect()
+        });
+    }
+    Ok(result)
+}
+
+impl From<&ProductOrderItem> for PriceRequest {
+    fn from(value: &ProductOrderItem) -> Self {
+        PriceRequest {
+            item_id: value.id.clone(),
+            offering_id: value
+                .product_offering
+                .as_ref()
+                .map(|po| po.id.clone())
+                .unwrap_or_default(),
+            quantity: value.quantity,
+            ..Default::default()
+        }
+    }
+}
+
+impl TryFrom<&QuoteItem> for ProductOrderItem {
+    type Error = TMFError;
+    fn try_from(value: &QuoteItem) -> Result<Self, Self::Error> {
+        let action = match value.get_action() {
+            Some(a) => OrderItemActionType::try_from(a.as_str())?,
+            None => OrderItemActionType::default(),
+        };
+        let product_offering = value.get_offer().map(|p| ProductOfferingRef {
+            id: p.id.unwrap_or_default(),
+            href: p.href.unwrap_or_default(),
+            name: p.name.unwrap_or_default(),
+        });
+        let item_price: Vec<OrderPrice> = value.get_price().iter().map(OrderPrice::from).collect();
+        Ok(ProductOrderItem {
+            id: value.id.clone(),
+            quantity: value.quantity,
+            action,
+            product_offering,
+            item_price: match item_price.is_empty() {
+                true => None,
+                false => Some(item_price),
+            },
+            ..Default::default()
+        })
+    }
+}
+
+/// Convert all items of a quote, including child items, into order items.
+/// Parent items are related to their children with a `bundles` relationship
+/// and every order item references the quote item it came from.
+pub(crate) fn order_items_from_quote(quote: &Quote) -> Result<Vec<ProductOrderItem>, TMFError> {
+    fn convert(
+        quote: &Quote,
+        items: &[QuoteItem],
+        out: &mut Vec<ProductOrderItem>,
+    ) -> Result<(), TMFError> {
+        for item in items {
+            let mut order_item = ProductOrderItem::try_from(item)?;
+            order_item.quote_item = Some(QuoteItemRef {
+                id: item.id.clone().unwrap_or_default(),
+                quote_id: quote.get_id(),
+                quote_href: quote.href.clone(),
+            });
+            let children = item.quote_item.as_deref().unwrap_or_default();
+            for child in children.iter().filter_map(|c| c.id.clone()) {
+                vec_insert(
+                    &mut order_item.product_order_item_relationship,
+                    OrderItemRelationship::new("bundles", child),
+                );
+            }
+            out.push(order_item);
+            convert(quote, children, out)?;
+        }
+        Ok(())
+    }
+    let mut out = vec![];
+    convert(
+        quote,
+        quote.quote_item.as_deref().unwrap_or_default(),
+        &mut out,
+    )?;
+    Ok(out)
+}
+
+/// Parts of a product order taken from a quote, shared by the V4 and V5 product orders
+pub(crate) struct QuoteOrder {
+    pub description: String,
+    pub items: Vec<ProductOrderItem>,
+    pub total_price: Option<Vec<OrderPrice>>,
+    pub quote: QuoteRef,
+}
+
+/// Check a quote can be ordered and convert it into the parts of a product order.
+/// Fails if the quote is not accepted, or its validity has ended or cannot be parsed.
+pub(crate) fn order_from_quote(quote: &Quote) -> Result<QuoteOrder, TMFError> {
+    if quote.state != Some(QuoteStateType::Accepted) {
+        return Err(TMFError::StateError(format!(
+            "{} {} is {:?}, only accepted quotes can be ordered",
+            Quote::get_class(),
+            quote.get_id(),
+            quote.state
+        )));
+    }
+    if let Some(end) = quote
+        .valid_for
+        .as_ref()
+        .and_then(|v| v.end_date_time.as_ref())
+    {
+        let end = chrono::DateTime::parse_from_rfc3339(end).map_err(|e| {
+            TMFError::InvalidTimePeriod(format!("Invalid end {end} of quote validity: {e}"))
+        })?;
+        if end < chrono::Utc::now() {
+            return Err(TMFError::StateError(format!(
+                "{} {} has expired",
+                Quote::get_class(),
+                quote.get_id()
+            )));
+        }
+    }
+    Ok(QuoteOrder {
+        description: format!("Order from {}", quote.description()),
+        items: order_items_from_quote(quote)?,
+        total_price: quote
+            .quote_total_price
+            .as_ref()
+            .map(|p| p.iter().map(OrderPrice::from).collect()),
+        quote: QuoteRef::from(quote),
+    })
 }
 
 impl From<ProductOffering> for ProductOrderItem {
@@ -133,6 +431,14 @@ mod test {
         assert_eq!(orderitemtype, OrderItemActionType::Add);
     }
 
+    #[test]
+    fn test_orderitem_dependencies() {
+        let item = ProductOrderItem::default().id("2").depends_on("1");
+
+        assert_eq!(item.get_item_id(), Some("2".to_string()));
+        assert_eq!(item.get_dependencies(), vec!["1".to_string()]);
+    }
+
     #[test]
     fn test_productorderitem_deserialize() {
         let orderitem: ProductOrderItem = serde_json::from_str(ORDERITEM_JSON).unwrap();
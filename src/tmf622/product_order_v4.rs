@@ -1,11 +1,15 @@
 //! Product Order Module
 
-use super::product_order_item::ProductOrderItem;
+use super::product_order_item::{order_from_quote, price_items, OrderPrice, ProductOrderItem};
+use super::ProductOrderStateType;
+use crate::common::dependency::{DependencyGraph, ExecutionPlan};
 use crate::common::event::{Event, EventPayload};
 use crate::common::note::Note;
 use crate::common::related_party::RelatedParty;
 use crate::common::tmf_error::TMFError;
+use crate::tmf620::pricing::{PricingEngine, PricingResult};
 use crate::tmf641::service_order::ServiceOrder;
+use crate::tmf648::quote::{Quote, QuoteRef};
 use crate::tmf651::agreement::AgreementRef;
 use crate::tmf663::shopping_cart::ShoppingCart;
 use crate::{
@@ -164,6 +168,12 @@ pub struct ProductOrder {
     /// Product Order Items
     #[serde(skip_serializing_if = "Option::is_none")]
     pub product_order_item: Option<Vec<ProductOrderItem>>,
+    /// Order total prices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_total_price: Option<Vec<OrderPrice>>,
+    /// Order State
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<ProductOrderStateType>,
     /// Related Parties
     #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<RelatedParty>>,
@@ -174,6 +184,9 @@ pub struct ProductOrder {
     /// Agreements
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agreement: Option<Vec<AgreementRef>>,
+    /// Quotes this order was created from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<Vec<QuoteRef>>,
 }
 
 impl HasLastUpdate for ProductOrder {
@@ -205,6 +218,41 @@ impl ProductOrder {
         vec_insert(&mut self.product_order_item, order_item);
         // self.product_order_item.as_mut().unwrap().push(order_item);
     }
+
+    /// Move the order to a new state, validating the transition against the current state.
+    /// Orders without a state are treated as [`ProductOrderStateType::Acknowledged`].
+    /// Returns the previous state.
+    pub fn set_state(
+        &mut self,
+        state: ProductOrderStateType,
+    ) -> Result<ProductOrderStateType, TMFError> {
+        let current = self.state.clone().unwrap_or_default();
+        if !current.can_transition(&state) {
+            return Err(TMFError::StateError(format!(
+                "Cannot move order {} from {:?} to {:?}",
+                self.get_id(),
+                current,
+                state
+            )));
+        }
+        self.state = Some(state);
+        Ok(current)
+    }
+
+    /// Price all items in the order, setting the item prices and the order total.
+    /// Items without a product offering have nothing to price and are skipped.
+    pub fn price(&mut self, engine: &PricingEngine) -> Result<PricingResult, TMFError> {
+        let items = self.product_order_item.as_deref_mut().unwrap_or_default();
+        let result = price_items(items, engine)?;
+        self.order_total_price = Some(result.totals.iter().map(OrderPrice::from).collect());
+        Ok(result)
+    }
+
+    /// Generate a dependency based execution plan for the items of this order
+    pub fn execution_plan(&self) -> Result<ExecutionPlan, TMFError> {
+        DependencyGraph::new(self.product_order_item.as_deref().unwrap_or_default())?
+            .execution_plan()
+    }
 }
 
 impl From<ServiceOrder> for ProductOrder {
@@ -245,6 +293,27 @@ impl From<ServiceOrder> for ProductOrder {
     }
 }
 
+impl TryFrom<Quote> for ProductOrder {
+    type Error = TMFError;
+    /// Convert an accepted quote into a product order.
+    /// Fails if the quote is not accepted or its validity has ended.
+    fn try_from(value: Quote) -> Result<Self, Self::Error> {
+        let parts = order_from_quote(&value)?;
+        let mut order = ProductOrder::new();
+        order.description = Some(parts.description);
+        order.category.clone_from(&value.category);
+        for item in parts.items {
+            order.add_order_item(item);
+        }
+        order.order_total_price = parts.total_price;
+        order.quote = Some(vec![parts.quote]);
+        order.related_party = value.related_party;
+        order.note = value.note;
+        order.agreement = value.agreement;
+        Ok(order)
+    }
+}
+
 impl From<ShoppingCart> for ProductOrder {
     fn from(value: ShoppingCart) -> Self {
         // Convert a Shopping cart into a product order.
@@ -252,18 +321,14 @@ impl From<ShoppingCart> for ProductOrder {
         let mut order = ProductOrder::new();
         order.description = Some("Order from Cart".into());
         // Bring across the cart items
-        if value.cart_item.is_some() {
-            value.cart_item.unwrap().into_iter().for_each(|i| {
-                order
-                    .product_order_item
-                    .as_mut()
-                    .unwrap()
-                    .push(ProductOrderItem::from(i));
+        if let Some(cart_item) = value.cart_item {
+            cart_item.into_iter().for_each(|i| {
+                order.add_order_item(ProductOrderItem::from(i));
             });
         }
         // Bring across the related parties
-        if value.related_party.is_some() {
-            value.related_party.unwrap().into_iter().for_each(|rp| {
+        if let Some(related_party) = value.related_party {
+            related_party.into_iter().for_each(|rp| {
                 order.add_party(rp);
             });
         }
@@ -274,8 +339,15 @@ impl From<ShoppingCart> for ProductOrder {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::common::money::Money;
+    use crate::tmf620::product_offering::ProductOffering;
+    use crate::tmf620::product_offering_price::ProductOfferingPrice;
     use crate::tmf641::service_order::ServiceOrder;
+    use crate::tmf648::quote::QuoteStateType;
+    use crate::tmf648::quote_item::QuoteItem;
+    use crate::tmf648::quote_price::QuotePrice;
     use crate::tmf663::shopping_cart::ShoppingCart;
+    use crate::TimePeriod;
 
     const SERVICE_CAT: &str = "ServiceCategory";
 
@@ -319,4 +391,111 @@ mod test {
         assert_eq!(order.related_party.is_none(), true);
         assert_eq!(order.product_order_item.is_none(), true);
     }
+
+    #[test]
+    fn test_productorder_price() {
+        let offer = ProductOffering::new("Broadband");
+        let mut engine = PricingEngine::new();
+        engine.add_price(
+            offer.get_id(),
+            ProductOfferingPrice::new("Connection").price(Money::from(100)),
+        );
+        let mut order = ProductOrder::new();
+        order.add_order_item(ProductOrderItem::default().id("0"));
+        order.add_order_item(ProductOrderItem::from(offer).id("1"));
+
+        let result = order.price(&engine).unwrap();
+
+        assert_eq!(result.items.len(), 1);
+        assert_eq!(result.items[0].item_id, Some("1".to_string()));
+        let items = order.product_order_item.as_ref().unwrap();
+        assert!(items[0].item_price.is_none());
+        assert_eq!(items[1].item_price.as_ref().unwrap().len(), 1);
+        let total = &order.order_total_price.as_ref().unwrap()[0];
+        assert_eq!(
+            total.price.as_ref().unwrap().tax_included_amount,
+            Money::from(110)
+        );
+    }
+
+    #[test]
+    fn test_productorder_from_quote() {
+        let offer = ProductOffering::new("Broadband");
+        let mut child = QuoteItem::new();
+        child.price(QuotePrice::new("Modem").period("month"));
+        let mut parent = QuoteItem::new().product(offer.clone()).action("add");
+        parent.quote_item = Some(vec![child.clone()]);
+        let mut quote = Quote::new();
+        quote.add_quote_item(parent.clone()).unwrap();
+        quote.add_note(Note::from("Customer note"));
+        quote.price(QuotePrice::new("Total"));
+
+        let order = ProductOrder::try_from(quote.clone()).unwrap();
+
+        let items = order.product_order_item.as_ref().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].id, parent.id);
+        assert_eq!(
+            items[0].quote_item.as_ref().unwrap().quote_id,
+            quote.get_id()
+        );
+        assert_eq!(
+            items[0].product_order_item_relationship.as_ref().unwrap()[0].id,
+            child.id.unwrap()
+        );
+        assert_eq!(items[1].item_price.as_ref().unwrap().len(), 1);
+        assert_eq!(order.quote.as_ref().unwrap()[0].id, quote.get_id());
+        assert_eq!(order.note.as_ref().unwrap().len(), 1);
+        assert_eq!(order.order_total_price.as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_productorder_from_quote_not_accepted() {
+        let mut quote = Quote::new();
+        quote.state = Some(QuoteStateType::Pending);
+
+        assert!(ProductOrder::try_from(quote.clone()).is_err());
+
+        // A quote without a state has not been accepted
+        quote.state = None;
+        assert!(matches!(
+            ProductOrder::try_from(quote),
+            Err(TMFError::StateError(_))
+        ));
+    }
+
+    #[test]
+    fn test_productorder_from_quote_expired() {
+        let mut quote = Quote::new();
+        quote.valid_for = Some(TimePeriod {
+            start_date_time: "2020-01-01T00:00:00+00:00".to_string(),
+            end_date_time: Some("2020-02-01T00:00:00+00:00".to_string()),
+        });
+
+        assert!(ProductOrder::try_from(quote).is_err());
+    }
+
+    #[test]
+    fn test_productorder_from_quote_bad_validity() {
+        let mut quote = Quote::new();
+        quote.valid_for = Some(TimePeriod {
+            start_date_time: "2020-01-01T00:00:00+00:00".to_string(),
+            end_date_time: Some("next week".to_string()),
+        });
+
+        assert!(matches!(
+            ProductOrder::try_from(quote),
+            Err(TMFError::InvalidTimePeriod(_))
+        ));
+    }
+
+    #[test]
+    fn test_productorder_from_quote_bad_action() {
+        let mut quote = Quote::new();
+        quote
+            .add_quote_item(QuoteItem::new().action("replace"))
+            .unwrap();
+
+        assert!(ProductOrder::try_from(quote).is_err());
+    }
 }
@@ -2,10 +2,14 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::ProductOrderStateType;
+use crate::common::dependency::{DependencyGraph, ExecutionPlan};
 use crate::common::note::Note;
 use crate::common::related_party::RelatedParty;
 use crate::common::tmf_error::TMFError;
+use crate::tmf620::pricing::{PricingEngine, PricingResult};
 use crate::tmf641::service_order::ServiceOrder;
+use crate::tmf648::quote::{Quote, QuoteRef};
 use crate::tmf651::agreement::AgreementRef;
 use crate::tmf663::shopping_cart::ShoppingCart;
 use crate::{DateTime, HasId, HasLastUpdate, HasNote, HasRelatedParty, Uri};
@@ -15,7 +19,7 @@ use tmflib_derive::{HasId, HasNote, HasRelatedParty};
 use super::MOD_PATH;
 
 use super::milestone::Milestone;
-use super::product_order_item::ProductOrderItem;
+use super::product_order_item::{order_from_quote, price_items, OrderPrice, ProductOrderItem};
 
 const CLASS_PATH: &str = "order";
 
@@ -94,6 +98,12 @@ pub struct ProductOrder {
     /// Product Order Items
     #[serde(skip_serializing_if = "Option::is_none")]
     pub product_order_item: Option<Vec<ProductOrderItem>>,
+    /// Order total prices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_total_price: Option<Vec<OrderPrice>>,
+    /// Order State
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<ProductOrderStateType>,
     /// Related Parties
     #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<RelatedParty>>,
@@ -104,6 +114,9 @@ pub struct ProductOrder {
     /// Agreements
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agreement: Option<Vec<AgreementRef>>,
+    /// Quotes this order was created from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<Vec<QuoteRef>>,
 
     product_order_milestone: Option<Vec<Milestone>>,
 }
@@ -135,6 +148,41 @@ impl ProductOrder {
     pub fn add_order_item(&mut self, order_item: ProductOrderItem) {
         self.product_order_item.as_mut().unwrap().push(order_item);
     }
+
+    /// Move the order to a new state, validating the transition against the current state.
+    /// Orders without a state are treated as [`ProductOrderStateType::Acknowledged`].
+    /// Returns the previous state.
+    pub fn set_state(
+        &mut self,
+        state: ProductOrderStateType,
+    ) -> Result<ProductOrderStateType, TMFError> {
+        let current = self.state.clone().unwrap_or_default();
+        if !current.can_transition(&state) {
+            return Err(TMFError::StateError(format!(
+                "Cannot move order {} from {:?} to {:?}",
+                self.get_id(),
+                current,
+                state
+            )));
+        }
+        self.state = Some(state);
+        Ok(current)
+    }
+
+    /// Price all items in the order, setting the item prices and the order total.
+    /// Items without a product offering have nothing to price and are skipped.
+    pub fn price(&mut self, engine: &PricingEngine) -> Result<PricingResult, TMFError> {
+        let items = self.product_order_item.as_deref_mut().unwrap_or_default();
+        let result = price_items(items, engine)?;
+        self.order_total_price = Some(result.totals.iter().map(OrderPrice::from).collect());
+        Ok(result)
+    }
+
+    /// Generate a dependency based execution plan for the items of this order
+    pub fn execution_plan(&self) -> Result<ExecutionPlan, TMFError> {
+        DependencyGraph::new(self.product_order_item.as_deref().unwrap_or_default())?
+            .execution_plan()
+    }
 }
 
 impl From<ServiceOrder> for ProductOrder {
@@ -175,6 +223,27 @@ impl From<ServiceOrder> for ProductOrder {
     }
 }
 
+impl TryFrom<Quote> for ProductOrder {
+    type Error = TMFError;
+    /// Convert an accepted quote into a product order.
+    /// Fails if the quote is not accepted or its validity has ended.
+    fn try_from(value: Quote) -> Result<Self, Self::Error> {
+        let parts = order_from_quote(&value)?;
+        let mut order = ProductOrder::new();
+        order.description = Some(parts.description);
+        order.category.clone_from(&value.category);
+        for item in parts.items {
+            order.add_order_item(item);
+        }
+        order.order_total_price = parts.total_price;
+        order.quote = Some(vec![parts.quote]);
+        order.related_party = value.related_party;
+        order.note = value.note;
+        order.agreement = value.agreement;
+        Ok(order)
+    }
+}
+
 impl From<ShoppingCart> for ProductOrder {
     fn from(value: ShoppingCart) -> Self {
         // Convert a Shopping cart into a product order.
@@ -182,18 +251,14 @@ impl From<ShoppingCart> for ProductOrder {
         let mut order = ProductOrder::new();
         order.description = Some("Order from Cart".into());
         // Bring across the cart items
-        if value.cart_item.is_some() {
-            value.cart_item.unwrap().into_iter().for_each(|i| {
-                order
-                    .product_order_item
-                    .as_mut()
-                    .unwrap()
-                    .push(ProductOrderItem::from(i));
+        if let Some(cart_item) = value.cart_item {
+            cart_item.into_iter().for_each(|i| {
+                order.add_order_item(ProductOrderItem::from(i));
             });
         }
         // Bring across the related parties
-        if value.related_party.is_some() {
-            value.related_party.unwrap().into_iter().for_each(|rp| {
+        if let Some(related_party) = value.related_party {
+            related_party.into_iter().for_each(|rp| {
                 order.add_party(rp);
             });
         }
@@ -0,0 +1,502 @@
+//! KPI Formula Module
+//!
+//! Derives performance indicators from other indicators using the formula held in the
+//! `derivationAlgorithm` of a [`PerformanceIndicatorSpecification`], e.g. `successRate = 100 * success / attempts`.
+//! Formulas support numbers, indicator names, `+ - * /`, unary minus and parentheses.
+//! - A missing input indicator results in [`TMFError::NoDataError`]
+//! - Division by zero results in [`TMFError::DivisionByZero`]
+//! - Formulas nested deeper than [`MAX_DEPTH`] are rejected when parsed
+//! - Formulas that depend on each other in a cycle result in [`TMFError::DependencyError`]
+//! ```
+//! use tmflib::tmf628::kpi::KpiEvaluator;
+//! use tmflib::tmf628::PerformanceIndicatorValue;
+//!
+//! let mut kpi = KpiEvaluator::new();
+//! kpi.add("successRate", "100 * success / attempts").unwrap();
+//! let values = vec![
+//!     PerformanceIndicatorValue::new("success", 45),
+//!     PerformanceIndicatorValue::new("attempts", 50),
+//! ];
+//! let derived = kpi.evaluate(&values).unwrap();
+//! assert_eq!(derived[0].get_value(), Some(90.0));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use super::{
+    PerformanceIndicatorGroupSpecification, PerformanceIndicatorSpecification,
+    PerformanceIndicatorValue,
+};
+use crate::common::tmf_error::TMFError;
+
+/// Maximum nesting of parentheses, negation and operators within a formula
+pub const MAX_DEPTH: usize = 64;
+
+/// Parsed KPI formula
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// Constant
+    Number(f64),
+    /// Value of another indicator
+    Indicator(String),
+    /// Negation
+    Neg(Box<Expr>),
+    /// Addition
+    Add(Box<Expr>, Box<Expr>),
+    /// Subtraction
+    Sub(Box<Expr>, Box<Expr>),
+    /// Multiplication
+    Mul(Box<Expr>, Box<Expr>),
+    /// Division
+    Div(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Op(char),
+}
+
+fn tokenize(formula: &str) -> Result<Vec<Token>, TMFError> {
+    let mut tokens = vec![];
+    let mut chars = formula.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '+' | '-' | '*' | '/' | '(' | ')' => tokens.push(Token::Op(c)),
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = c.to_string();
+                while let Some(d) = chars.next_if(|d| d.is_ascii_digit() || *d == '.') {
+                    number.push(d);
+                }
+                let value = number.parse::<f64>().map_err(|_| {
+                    TMFError::GenericError(format!("Invalid number {number} in {formula}"))
+                })?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(d) = chars.next_if(|d| d.is_alphanumeric() || *d == '_' || *d == '.')
+                {
+                    name.push(d);
+                }
+                tokens.push(Token::Name(name));
+            }
+            c => {
+                return Err(TMFError::GenericError(format!(
+                    "Unexpected '{c}' in {formula}"
+                )))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    formula: &'a str,
+    // Current nesting of parentheses and negation
+    level: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn error(&self, msg: &str) -> TMFError {
+        TMFError::GenericError(format!("{msg} in {}", self.formula))
+    }
+
+    /// Depth of a new node above a subtree of the given depth
+    fn nest(&self, depth: usize) -> Result<usize, TMFError> {
+        match depth < MAX_DEPTH {
+            true => Ok(depth + 1),
+            false => Err(self.error(&format!("Nesting deeper than {MAX_DEPTH}"))),
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<(Expr, usize), TMFError> {
+        let (mut left, mut depth) = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.pos += 1;
+            let (right, right_depth) = self.term()?;
+            depth = self.nest(depth.max(right_depth))?;
+            left = match op {
+                '+' => Expr::Add(Box::new(left), Box::new(right)),
+                _ => Expr::Sub(Box::new(left), Box::new(right)),
+            };
+        }
+        Ok((left, depth))
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<(Expr, usize), TMFError> {
+        let (mut left, mut depth) = self.factor()?;
+        while let Some(Token::Op(op @ ('*' | '/'))) = self.peek().cloned() {
+            self.pos += 1;
+            let (right, right_depth) = self.factor()?;
+            depth = self.nest(depth.max(right_depth))?;
+            left = match op {
+                '*' => Expr::Mul(Box::new(left), Box::new(right)),
+                _ => Expr::Div(Box::new(left), Box::new(right)),
+            };
+        }
+        Ok((left, depth))
+    }
+
+    // factor := number | name | '-' factor | '(' expr ')'
+    fn factor(&mut self) -> Result<(Expr, usize), TMFError> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok((Expr::Number(n), 1)),
+            Some(Token::Name(name)) => Ok((Expr::Indicator(name), 1)),
+            Some(Token::Op(op @ ('-' | '('))) => {
+                // Check before recursing so deep nesting cannot overflow the stack
+                self.level = self.nest(self.level)?;
+                let inner = match op {
+                    '-' => {
+                        let (inner, depth) = self.factor()?;
+                        (Expr::Neg(Box::new(inner)), self.nest(depth)?)
+                    }
+                    _ => {
+                        let inner = self.expr()?;
+                        match self.next() {
+                            Some(Token::Op(')')) => inner,
+                            _ => return Err(self.error("Missing ')'")),
+                        }
+                    }
+                };
+                self.level -= 1;
+                Ok(inner)
+            }
+            Some(token) => Err(self.error(&format!("Unexpected {token:?}"))),
+            None => Err(self.error("Unexpected end of formula")),
+        }
+    }
+}
+
+impl Expr {
+    /// Parse a formula. A leading `name =` is ignored.
+    pub fn parse(formula: &str) -> Result<Expr, TMFError> {
+        let body = match formula.split_once('=') {
+            Some((_, rhs)) => rhs,
+            None => formula,
+        };
+        let mut parser = Parser {
+            tokens: tokenize(body)?,
+            pos: 0,
+            formula,
+            level: 0,
+        };
+        let (expr, _) = parser.expr()?;
+        match parser.peek() {
+            Some(token) => Err(parser.error(&format!("Unexpected {token:?}"))),
+            None => Ok(expr),
+        }
+    }
+
+    /// Names of the indicators this formula depends on
+    pub fn references(&self) -> Vec<String> {
+        let mut names = vec![];
+        self.collect(&mut names);
+        names
+    }
+
+    fn collect(&self, names: &mut Vec<String>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Indicator(name) => {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            Expr::Neg(e) => e.collect(names),
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) | Expr::Div(a, b) => {
+                a.collect(names);
+                b.collect(names);
+            }
+        }
+    }
+
+    /// Evaluate this formula using the supplied indicator values
+    pub fn evaluate(&self, values: &HashMap<String, f64>) -> Result<f64, TMFError> {
+        match self {
+            Expr::Number(n) => Ok(*n),
+            Expr::Indicator(name) => values
+                .get(name)
+                .copied()
+                .ok_or(TMFError::NoDataError(format!("indicator {name}"))),
+            Expr::Neg(e) => Ok(-e.evaluate(values)?),
+            Expr::Add(a, b) => Ok(a.evaluate(values)? + b.evaluate(values)?),
+            Expr::Sub(a, b) => Ok(a.evaluate(values)? - b.evaluate(values)?),
+            Expr::Mul(a, b) => Ok(a.evaluate(values)? * b.evaluate(values)?),
+            Expr::Div(a, b) => {
+                let divisor = b.evaluate(values)?;
+                if divisor == 0.0 {
+                    return Err(TMFError::DivisionByZero(self.to_string()));
+                }
+                Ok(a.evaluate(values)? / divisor)
+            }
+        }
+    }
+}
+
+impl Expr {
+    /// Binding strength, used to decide where parentheses are needed when displayed
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Add(..) | Expr::Sub(..) => 1,
+            Expr::Mul(..) | Expr::Div(..) => 2,
+            Expr::Neg(_) => 3,
+            Expr::Number(_) | Expr::Indicator(_) => 4,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut std::fmt::Formatter, min: u8) -> std::fmt::Result {
+        match self.precedence() < min {
+            true => write!(f, "({self})"),
+            false => write!(f, "{self}"),
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (a, op, b) = match self {
+            Expr::Number(n) => return write!(f, "{n}"),
+            Expr::Indicator(name) => return write!(f, "{name}"),
+            Expr::Neg(e) => {
+                write!(f, "-")?;
+                return e.fmt_operand(f, self.precedence());
+            }
+            Expr::Add(a, b) => (a, '+', b),
+            Expr::Sub(a, b) => (a, '-', b),
+            Expr::Mul(a, b) => (a, '*', b),
+            Expr::Div(a, b) => (a, '/', b),
+        };
+        // Operators are left associative, so a right operand of equal precedence needs parentheses
+        a.fmt_operand(f, self.precedence())?;
+        write!(f, " {op} ")?;
+        b.fmt_operand(f, self.precedence() + 1)
+    }
+}
+
+/// Evaluator for a set of derived indicators
+#[derive(Clone, Debug, Default)]
+pub struct KpiEvaluator {
+    formulas: Vec<(String, Expr)>,
+}
+
+impl KpiEvaluator {
+    /// Create an evaluator with no formulas
+    pub fn new() -> KpiEvaluator {
+        KpiEvaluator::default()
+    }
+
+    /// Add a derived indicator, replacing any existing formula with the same name
+    pub fn add(&mut self, name: impl Into<String>, formula: &str) -> Result<(), TMFError> {
+        let name = name.into();
+        let expr = Expr::parse(formula)?;
+        self.formulas.retain(|(n, _)| n != &name);
+        self.formulas.push((name, expr));
+        Ok(())
+    }
+
+    /// Create an evaluator from the specifications that have a derivation algorithm
+    pub fn from_specifications(
+        specs: &[PerformanceIndicatorSpecification],
+    ) -> Result<KpiEvaluator, TMFError> {
+        let mut evaluator = KpiEvaluator::new();
+        for spec in specs {
+            if let (Some(name), Some(formula)) = (&spec.name, &spec.derivation_algorithm) {
+                evaluator.add(name.clone(), formula)?;
+            }
+        }
+        evaluator.order()?;
+        Ok(evaluator)
+    }
+
+    /// Create an evaluator for the specifications included in a group, checking there are no cycles
+    pub fn from_group(
+        group: &PerformanceIndicatorGroupSpecification,
+        specs: &[PerformanceIndicatorSpecification],
+    ) -> Result<KpiEvaluator, TMFError> {
+        let members: Vec<PerformanceIndicatorSpecification> = specs
+            .iter()
+            .filter(|s| {
+                group.performance_indicator_specification.iter().any(|r| {
+                    (r.id.is_some() && r.id == s.id) || (r.name.is_some() && r.name == s.name)
+                })
+            })
+            .cloned()
+            .collect();
+        KpiEvaluator::from_specifications(&members)
+    }
+
+    /// Names of the derived indicators in the order they must be evaluated, dependencies first
+    pub fn order(&self) -> Result<Vec<String>, TMFError> {
+        // 0 = unvisited, 1 = in progress, 2 = done
+        let mut mark: HashMap<&str, u8> = HashMap::new();
+        let mut order = vec![];
+        for (name, _) in self.formulas.iter() {
+            self.visit(name, &mut mark, &mut order, &mut vec![])?;
+        }
+        Ok(order)
+    }
+
+    fn visit<'a>(
+        &'a self,
+        name: &'a str,
+        mark: &mut HashMap<&'a str, u8>,
+        order: &mut Vec<String>,
+        path: &mut Vec<&'a str>,
+    ) -> Result<(), TMFError> {
+        let expr = match self.formulas.iter().find(|(n, _)| n == name) {
+            Some((_, e)) => e,
+            // Not derived, must be supplied as an input
+            None => return Ok(()),
+        };
+        match mark.get(name) {
+            Some(2) => return Ok(()),
+            Some(1) => {
+                path.push(name);
+                return Err(TMFError::DependencyError(format!(
+                    "Circular indicator dependency: {}",
+                    path.join(" -> ")
+                )));
+            }
+            _ => {}
+        }
+        mark.insert(name, 1);
+        path.push(name);
+        for reference in expr.references() {
+            let reference = self
+                .formulas
+                .iter()
+                .find(|(n, _)| *n == reference)
+                .map(|(n, _)| n.as_str());
+            if let Some(r) = reference {
+                self.visit(r, mark, order, path)?;
+            }
+        }
+        path.pop();
+        mark.insert(name, 2);
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    /// Derive indicator values from the supplied input values, returning only the derived values
+    pub fn evaluate(
+        &self,
+        values: &[PerformanceIndicatorValue],
+    ) -> Result<Vec<PerformanceIndicatorValue>, TMFError> {
+        let mut inputs: HashMap<String, f64> = values
+            .iter()
+            .filter_map(|v| Some((v.get_indicator()?, v.get_value()?)))
+            .collect();
+        let mut derived = vec![];
+        for name in self.order()? {
+            if let Some((_, expr)) = self.formulas.iter().find(|(n, _)| *n == name) {
+                let value = expr.evaluate(&inputs)?;
+                inputs.insert(name.clone(), value);
+                derived.push(PerformanceIndicatorValue::new(name, value));
+            }
+        }
+        Ok(derived)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn inputs(values: &[(&str, f64)]) -> HashMap<String, f64> {
+        values.iter().map(|(n, v)| (n.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_expr_parse() {
+        let expr = Expr::parse("successRate = 100 * success / (attempts - -1)").unwrap();
+
+        assert_eq!(expr.references(), vec!["success", "attempts"]);
+        assert_eq!(
+            expr.evaluate(&inputs(&[("success", 9.0), ("attempts", 9.0)]))
+                .unwrap(),
+            90.0
+        );
+        assert!(Expr::parse("100 * (success").is_err());
+        assert!(Expr::parse("100 success").is_err());
+    }
+
+    #[test]
+    fn test_expr_errors() {
+        let expr = Expr::parse("success / attempts").unwrap();
+
+        assert!(matches!(
+            expr.evaluate(&inputs(&[("success", 1.0)])),
+            Err(TMFError::NoDataError(_))
+        ));
+        assert!(matches!(
+            expr.evaluate(&inputs(&[("success", 1.0), ("attempts", 0.0)])),
+            Err(TMFError::DivisionByZero(e)) if e == "success / attempts"
+        ));
+    }
+
+    #[test]
+    fn test_expr_display() {
+        let formula = "100 * -success / (attempts - (retries + 1)) - 2";
+
+        assert_eq!(Expr::parse(formula).unwrap().to_string(), formula);
+    }
+
+    #[test]
+    fn test_expr_depth() {
+        let nested = format!("{}1{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        let too_deep = format!(
+            "{}1{}",
+            "(".repeat(MAX_DEPTH + 1),
+            ")".repeat(MAX_DEPTH + 1)
+        );
+
+        assert!(Expr::parse(&nested).is_ok());
+        assert!(Expr::parse(&too_deep).is_err());
+        assert!(Expr::parse(&"-".repeat(100_000)).is_err());
+        assert!(Expr::parse(&vec!["1"; 100].join(" + ")).is_err());
+    }
+
+    #[test]
+    fn test_kpi_order() {
+        let mut kpi = KpiEvaluator::new();
+        kpi.add("failureRate", "100 - successRate").unwrap();
+        kpi.add("successRate", "100 * success / attempts").unwrap();
+
+        assert_eq!(kpi.order().unwrap(), vec!["successRate", "failureRate"]);
+        let derived = kpi
+            .evaluate(&[
+                PerformanceIndicatorValue::new("success", 3),
+                PerformanceIndicatorValue::new("attempts", 4),
+            ])
+            .unwrap();
+        assert_eq!(derived[1].get_value(), Some(25.0));
+    }
+
+    #[test]
+    fn test_kpi_cycle() {
+        let mut kpi = KpiEvaluator::new();
+        kpi.add("a", "b + 1").unwrap();
+        kpi.add("b", "c * 2").unwrap();
+        kpi.add("c", "a").unwrap();
+
+        assert!(matches!(kpi.order(), Err(TMFError::DependencyError(_))));
+    }
+}
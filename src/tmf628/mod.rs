@@ -218,6 +218,7 @@ mod indicator_type;
 mod intent_ref;
 mod intent_ref_fvo;
 mod intent_ref_mvo;
+pub mod kpi;
 mod logical_resource;
 mod logical_resource_fvo;
 mod logical_resource_mvo;
@@ -295,6 +296,8 @@ mod resource_usage_state_type;
 mod schedule_definition;
 mod schedule_definition_fvo;
 mod schedule_definition_mvo;
+#[cfg(feature = "tmf642")]
+pub mod threshold;
 mod time_period;
 mod tracking_record;
 mod tracking_record_fvo;
@@ -1,7 +1,21 @@
 use serde::{Deserialize, Serialize};
 ///A product to be created defined by value or existing defined by reference. The polymorphic attributes @type, @schemaLocation & @referredType are related to the product entity and not the RelatedProductRefOrValue class itself
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct PerformanceIndicatorSpecificationRefOrValue {}
+pub struct PerformanceIndicatorSpecificationRefOrValue {
+    ///The identifier of the performance indicator specification.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    ///The URI of the performance indicator specification.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    ///Name of the performance indicator specification.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    ///The actual type of the target instance when needed for disambiguation.
+    #[serde(rename = "@referredType")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referred_type: Option<String>,
+}
 impl std::fmt::Display for PerformanceIndicatorSpecificationRefOrValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
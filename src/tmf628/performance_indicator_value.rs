@@ -15,6 +15,35 @@ pub struct PerformanceIndicatorValue {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tag: Option<serde_json::Value>,
 }
+impl PerformanceIndicatorValue {
+    /// Create an observed value for a named indicator
+    pub fn new(indicator: impl Into<String>, value: impl ToString) -> PerformanceIndicatorValue {
+        PerformanceIndicatorValue {
+            observed_value: Some(value.to_string()),
+            performance_indicator_specification: Some(
+                PerformanceIndicatorSpecificationRefOrValue {
+                    name: Some(indicator.into()),
+                    ..Default::default()
+                },
+            ),
+            tag: None,
+        }
+    }
+
+    /// Name of the indicator this value was observed for
+    pub fn get_indicator(&self) -> Option<String> {
+        self.performance_indicator_specification
+            .as_ref()
+            .and_then(|s| s.name.clone())
+    }
+
+    /// Observed value as a number, None if not set or not numeric
+    pub fn get_value(&self) -> Option<f64> {
+        self.observed_value
+            .as_ref()
+            .and_then(|v| v.trim().parse::<f64>().ok())
+    }
+}
 impl std::fmt::Display for PerformanceIndicatorValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
@@ -13,7 +13,10 @@
 // limitations under the License.
 //! Performance Measurement object model for TMF628 Performance Management
 
-use super::{MeasurementCollectionJobRef, PerformanceMeasurementRelationship, MOD_PATH};
+use super::{
+    MeasurementCollectionJobRef, PerformanceIndicatorValue, PerformanceMeasurementRelationship,
+    MOD_PATH,
+};
 use crate::{common::entity::Entity, HasDescription, HasEntity, TimePeriod};
 use serde::{Deserialize, Serialize};
 use tmflib_derive::HasDescription;
@@ -33,6 +36,10 @@ pub struct PerformanceMeasurement {
     #[serde(rename = "measurementCollectionJob")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub measurement_collection_job: Option<MeasurementCollectionJobRef>,
+    ///Observed values of performance indicators
+    #[serde(rename = "performanceIndicatorValue")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub performance_indicator_value: Vec<PerformanceIndicatorValue>,
     ///related Performance measurements array
     #[serde(rename = "relatedMeasurement")]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -46,6 +53,27 @@ pub struct PerformanceMeasurement {
     pub valid_for: Option<TimePeriod>,
 }
 
+impl PerformanceMeasurement {
+    /// Builder pattern to add an observed indicator value
+    pub fn value(
+        mut self,
+        indicator: impl Into<String>,
+        value: impl ToString,
+    ) -> PerformanceMeasurement {
+        self.performance_indicator_value
+            .push(PerformanceIndicatorValue::new(indicator, value));
+        self
+    }
+
+    /// Numeric value observed for a named indicator
+    pub fn get_value(&self, indicator: &str) -> Option<f64> {
+        self.performance_indicator_value
+            .iter()
+            .find(|v| v.get_indicator().as_deref() == Some(indicator))
+            .and_then(|v| v.get_value())
+    }
+}
+
 impl HasEntity for PerformanceMeasurement {
     fn generate_id(&mut self) {
         let id = PerformanceMeasurement::get_uuid();
@@ -0,0 +1,421 @@
+//! Performance Threshold Module
+//!
+//! Evaluates [`ThresholdRule`]s over a stream of [`PerformanceMeasurement`]s, raising, updating and
+//! clearing TMF642 [`Alarm`]s when indicator values cross the configured levels.
+//! - A rule has one or more raise levels, each with a severity. The most severe level crossed sets the alarm severity.
+//! - Hysteresis stops an alarm flapping, the value must move back past a level by the hysteresis before the severity
+//!   is lowered or the alarm cleared.
+//! - An explicit clear level may be given instead of relying on the lowest raise level and hysteresis.
+//! - A rule may require a number of consecutive periods to be crossed (or clear) before an alarm is raised (or cleared).
+//! ```
+//! use tmflib::tmf628::performance_measurement::PerformanceMeasurement;
+//! use tmflib::tmf628::threshold::{ThresholdDirection, ThresholdMonitor, ThresholdOutcome, ThresholdRule};
+//! use tmflib::tmf642::{AlarmedObjectRef, PerceivedSeverity};
+//!
+//! let rule = ThresholdRule::new("High CPU", "cpuUtilisation", ThresholdDirection::Up)
+//!     .level(PerceivedSeverity::Major, 90.0);
+//! let mut monitor = ThresholdMonitor::new().rule(rule);
+//! let router = AlarmedObjectRef::new("router-01");
+//!
+//! let outcomes = monitor.observe(&router, &PerformanceMeasurement::default().value("cpuUtilisation", 95.0));
+//! assert!(matches!(outcomes[0], ThresholdOutcome::Raised(_)));
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::performance_measurement::PerformanceMeasurement;
+use crate::now;
+use crate::tmf642::{
+    Alarm, AlarmType, AlarmedObjectRef, CrossedThresholdInformation, PerceivedSeverity,
+    ProbableCause, ThresholdRef,
+};
+
+/// Direction in which a threshold is crossed
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThresholdDirection {
+    /// Crossed when the value rises to or above a level
+    #[default]
+    Up,
+    /// Crossed when the value falls to or below a level
+    Down,
+}
+
+impl std::fmt::Display for ThresholdDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThresholdDirection::Up => write!(f, "up"),
+            ThresholdDirection::Down => write!(f, "down"),
+        }
+    }
+}
+
+/// Level at which an alarm of a given severity is raised
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThresholdLevel {
+    /// Severity of the alarm raised at this level
+    pub severity: PerceivedSeverity,
+    /// Value at which this level is crossed
+    pub raise: f64,
+}
+
+/// Threshold rule over a single performance indicator
+#[derive(Clone, Debug)]
+pub struct ThresholdRule {
+    /// Unique Id of this rule
+    pub id: String,
+    /// Name of this rule
+    pub name: String,
+    /// Name of the performance indicator this rule applies to
+    pub indicator: String,
+    /// Direction of crossing
+    pub direction: ThresholdDirection,
+    /// Raise levels, ordered from least to most severe
+    pub levels: Vec<ThresholdLevel>,
+    /// Explicit level at which an alarm is cleared
+    pub clear: Option<f64>,
+    /// Distance a value must move back past a level before the severity is lowered or the alarm cleared
+    pub hysteresis: f64,
+    /// Number of consecutive periods required to raise or clear an alarm
+    pub consecutive: u32,
+    /// Granularity of the indicator, e.g. g_15mn
+    pub granularity: Option<String>,
+    /// Unit of the indicator
+    pub unit: Option<String>,
+}
+
+impl ThresholdRule {
+    /// Create a new rule with no levels
+    pub fn new(
+        name: impl Into<String>,
+        indicator: impl Into<String>,
+        direction: ThresholdDirection,
+    ) -> ThresholdRule {
+        ThresholdRule {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.into(),
+            indicator: indicator.into(),
+            direction,
+            levels: vec![],
+            clear: None,
+            hysteresis: 0.0,
+            consecutive: 1,
+            granularity: None,
+            unit: None,
+        }
+    }
+
+    /// Builder pattern to add a raise level
+    pub fn level(mut self, severity: PerceivedSeverity, raise: f64) -> ThresholdRule {
+        self.levels.push(ThresholdLevel { severity, raise });
+        self.levels.sort_by(|a, b| a.severity.cmp(&b.severity));
+        self
+    }
+
+    /// Builder pattern to set an explicit clear level
+    pub fn clear(mut self, clear: f64) -> ThresholdRule {
+        self.clear = Some(clear);
+        self
+    }
+
+    /// Builder pattern to set the hysteresis
+    pub fn hysteresis(mut self, hysteresis: f64) -> ThresholdRule {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Builder pattern to set the number of consecutive periods required to raise or clear an alarm
+    pub fn consecutive(mut self, periods: u32) -> ThresholdRule {
+        self.consecutive = periods.max(1);
+        self
+    }
+
+    /// Builder pattern to set the granularity and unit of the indicator
+    pub fn indicator_detail(
+        mut self,
+        granularity: Option<String>,
+        unit: Option<String>,
+    ) -> ThresholdRule {
+        self.granularity = granularity;
+        self.unit = unit;
+        self
+    }
+
+    /// Is the value at or beyond the level in the direction of this rule
+    fn beyond(&self, value: f64, level: f64) -> bool {
+        match self.direction {
+            ThresholdDirection::Up => value >= level,
+            ThresholdDirection::Down => value <= level,
+        }
+    }
+
+    /// Level moved back by the hysteresis
+    fn relaxed(&self, level: f64) -> f64 {
+        match self.direction {
+            ThresholdDirection::Up => level - self.hysteresis,
+            ThresholdDirection::Down => level + self.hysteresis,
+        }
+    }
+
+    /// Most severe level crossed by a value
+    pub fn crossed(&self, value: f64) -> Option<&ThresholdLevel> {
+        self.levels
+            .iter()
+            .filter(|l| self.beyond(value, l.raise))
+            .max_by(|a, b| a.severity.cmp(&b.severity))
+    }
+
+    /// Is the value clear of this rule, i.e. should an active alarm be cleared
+    pub fn is_clear(&self, value: f64) -> bool {
+        let clear = match (self.clear, self.levels.first()) {
+            (Some(clear), _) => clear,
+            (None, Some(lowest)) => self.relaxed(lowest.raise),
+            (None, None) => return true,
+        };
+        !self.beyond(value, clear)
+    }
+
+    /// Severity for a value given the current severity of an active alarm, applying hysteresis
+    fn severity(&self, value: f64, current: &PerceivedSeverity) -> PerceivedSeverity {
+        let crossed = self.crossed(value).map(|l| l.severity.clone());
+        if crossed.as_ref().is_some_and(|c| c >= current) {
+            return crossed.unwrap_or(current.clone());
+        }
+        // Hold the current severity until the value has moved back past its level by the hysteresis
+        let held = self
+            .levels
+            .iter()
+            .find(|l| &l.severity == current)
+            .is_some_and(|l| self.beyond(value, self.relaxed(l.raise)));
+        match (held, crossed) {
+            (true, _) => current.clone(),
+            (false, Some(c)) => c,
+            (false, None) => self
+                .levels
+                .first()
+                .map(|l| l.severity.clone())
+                .unwrap_or(current.clone()),
+        }
+    }
+
+    fn crossing(&self, value: f64) -> CrossedThresholdInformation {
+        CrossedThresholdInformation {
+            direction: Some(self.direction.to_string()),
+            granularity: self.granularity.clone(),
+            indicator_name: Some(self.indicator.clone()),
+            indicator_unit: self.unit.clone(),
+            observed_value: Some(value.to_string()),
+            threshold: Some(ThresholdRef {
+                id: Some(self.id.clone()),
+                name: Some(self.name.clone()),
+                ..Default::default()
+            }),
+            threshold_crossing_description: Some(format!(
+                "{} crossed {} with value {}",
+                self.indicator, self.direction, value
+            )),
+        }
+    }
+}
+
+/// Result of evaluating a measurement against a rule
+#[derive(Clone, Debug)]
+pub enum ThresholdOutcome {
+    /// A new alarm was raised
+    Raised(Alarm),
+    /// The severity of an active alarm changed
+    Updated(Alarm),
+    /// An active alarm was cleared
+    Cleared(Alarm),
+}
+
+#[derive(Clone, Debug, Default)]
+struct RuleState {
+    alarm: Option<Alarm>,
+    raise_count: u32,
+    clear_count: u32,
+}
+
+/// Evaluates threshold rules over measurements for any number of monitored objects
+#[derive(Clone, Debug, Default)]
+pub struct ThresholdMonitor {
+    rules: Vec<ThresholdRule>,
+    state: HashMap<(String, String), RuleState>,
+}
+
+impl ThresholdMonitor {
+    /// Create a monitor with no rules
+    pub fn new() -> ThresholdMonitor {
+        ThresholdMonitor::default()
+    }
+
+    /// Builder pattern to add a rule
+    pub fn rule(mut self, rule: ThresholdRule) -> ThresholdMonitor {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Active alarms raised by this monitor
+    pub fn active(&self) -> impl Iterator<Item = &Alarm> {
+        self.state.values().filter_map(|s| s.alarm.as_ref())
+    }
+
+    /// Evaluate a measurement taken from an object against all rules.
+    /// Rules whose indicator is not present in the measurement are skipped.
+    pub fn observe(
+        &mut self,
+        object: &AlarmedObjectRef,
+        measurement: &PerformanceMeasurement,
+    ) -> Vec<ThresholdOutcome> {
+        let time = measurement
+            .valid_for
+            .as_ref()
+            .map(|p| p.end_date_time.clone().unwrap_or(p.start_date_time.clone()))
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(now);
+        let mut outcomes = vec![];
+        for rule in self.rules.iter() {
+            let value = match measurement.get_value(&rule.indicator) {
+                Some(v) => v,
+                None => continue,
+            };
+            let state = self
+                .state
+                .entry((rule.id.clone(), object.id.clone()))
+                .or_default();
+            match state.alarm.as_mut() {
+                None => match rule.crossed(value) {
+                    Some(level) => {
+                        state.raise_count += 1;
+                        if state.raise_count >= rule.consecutive {
+                            let mut alarm = Alarm::new(
+                                object.clone(),
+                                AlarmType::QualityOfServiceAlarm,
+                                level.severity.clone(),
+                            );
+                            alarm.alarmed_object_type = object.referred_type.clone();
+                            alarm.alarm_raised_time = Some(time.clone());
+                            alarm.probable_cause = Some(ProbableCause::ThresholdCrossed);
+                            alarm.specific_problem = Some(rule.name.clone());
+                            alarm.crossed_threshold_information = Some(rule.crossing(value));
+                            state.raise_count = 0;
+                            state.alarm = Some(alarm.clone());
+                            outcomes.push(ThresholdOutcome::Raised(alarm));
+                        }
+                    }
+                    None => state.raise_count = 0,
+                },
+                Some(alarm) => {
+                    if rule.is_clear(value) {
+                        state.clear_count += 1;
+                        if state.clear_count >= rule.consecutive {
+                            alarm.crossed_threshold_information = Some(rule.crossing(value));
+                            // Alarms held by the monitor are never cleared so this cannot fail
+                            let _ = alarm.clear(None, None, time.clone());
+                            outcomes.push(ThresholdOutcome::Cleared(alarm.clone()));
+                            state.clear_count = 0;
+                            state.alarm = None;
+                        }
+                        continue;
+                    }
+                    state.clear_count = 0;
+                    let current = alarm
+                        .perceived_severity
+                        .clone()
+                        .unwrap_or(PerceivedSeverity::Indeterminate);
+                    let severity = rule.severity(value, &current);
+                    if severity != current && alarm.set_severity(severity).is_ok() {
+                        alarm.crossed_threshold_information = Some(rule.crossing(value));
+                        alarm.alarm_changed_time = Some(time.clone());
+                        outcomes.push(ThresholdOutcome::Updated(alarm.clone()));
+                    }
+                }
+            }
+        }
+        outcomes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cpu(value: f64) -> PerformanceMeasurement {
+        PerformanceMeasurement::default().value("cpu", value)
+    }
+
+    fn rule() -> ThresholdRule {
+        ThresholdRule::new("High CPU", "cpu", ThresholdDirection::Up)
+            .level(PerceivedSeverity::Minor, 80.0)
+            .level(PerceivedSeverity::Major, 90.0)
+            .hysteresis(5.0)
+    }
+
+    #[test]
+    fn test_threshold_lifecycle() {
+        let mut monitor = ThresholdMonitor::new().rule(rule());
+        let object = AlarmedObjectRef::new("router-01");
+
+        let raised = monitor.observe(&object, &cpu(82.0));
+        let updated = monitor.observe(&object, &cpu(93.0));
+        // Within hysteresis of the major level so no change
+        let held = monitor.observe(&object, &cpu(87.0));
+        let lowered = monitor.observe(&object, &cpu(83.0));
+        let cleared = monitor.observe(&object, &cpu(70.0));
+
+        match &raised[0] {
+            ThresholdOutcome::Raised(alarm) => {
+                assert_eq!(alarm.perceived_severity, Some(PerceivedSeverity::Minor));
+                let crossing = alarm.crossed_threshold_information.as_ref().unwrap();
+                assert_eq!(crossing.direction.as_deref(), Some("up"));
+                assert_eq!(crossing.observed_value.as_deref(), Some("82"));
+            }
+            _ => panic!("Expected raised alarm"),
+        }
+        assert!(
+            matches!(&updated[0], ThresholdOutcome::Updated(a) if a.perceived_severity == Some(PerceivedSeverity::Major))
+        );
+        assert!(held.is_empty());
+        assert!(
+            matches!(&lowered[0], ThresholdOutcome::Updated(a) if a.perceived_severity == Some(PerceivedSeverity::Minor))
+        );
+        assert!(matches!(&cleared[0], ThresholdOutcome::Cleared(a) if a.is_cleared()));
+        assert_eq!(monitor.active().count(), 0);
+    }
+
+    #[test]
+    fn test_threshold_hysteresis_clear() {
+        let mut monitor = ThresholdMonitor::new().rule(rule());
+        let object = AlarmedObjectRef::new("router-01");
+
+        monitor.observe(&object, &cpu(85.0));
+
+        assert!(monitor.observe(&object, &cpu(77.0)).is_empty());
+        assert_eq!(monitor.observe(&object, &cpu(74.0)).len(), 1);
+    }
+
+    #[test]
+    fn test_threshold_consecutive() {
+        let rule = ThresholdRule::new("Low signal", "rssi", ThresholdDirection::Down)
+            .level(PerceivedSeverity::Warning, -80.0)
+            .clear(-70.0)
+            .consecutive(2);
+        let mut monitor = ThresholdMonitor::new().rule(rule);
+        let object = AlarmedObjectRef::new("cell-01");
+        let rssi = |v: f64| PerformanceMeasurement::default().value("rssi", v);
+
+        assert!(monitor.observe(&object, &rssi(-85.0)).is_empty());
+        assert!(monitor.observe(&object, &rssi(-60.0)).is_empty());
+        assert!(monitor.observe(&object, &rssi(-85.0)).is_empty());
+        assert_eq!(monitor.observe(&object, &rssi(-90.0)).len(), 1);
+        // Between raise and clear levels the alarm stays active
+        assert!(monitor.observe(&object, &rssi(-75.0)).is_empty());
+        assert!(monitor.observe(&object, &rssi(-65.0)).is_empty());
+        assert!(matches!(
+            monitor.observe(&object, &rssi(-65.0))[0],
+            ThresholdOutcome::Cleared(_)
+        ));
+    }
+}
@@ -1,8 +1,47 @@
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-///An empty data structure
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct Any {}
+///Value of any type
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(transparent)]
+pub struct Any(pub serde_json::Value);
+
+impl Any {
+    /// Get the value as a number, accepting numeric strings
+    pub fn as_f64(&self) -> Option<f64> {
+        match &self.0 {
+            serde_json::Value::Number(n) => n.as_f64(),
+            serde_json::Value::String(s) => s.trim().parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a decimal, accepting numeric strings. Numbers are read from their JSON
+    /// text, so are not rounded via floating point.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        let text = match &self.0 {
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::String(s) => s.trim().to_string(),
+            _ => return None,
+        };
+        Decimal::from_str(&text)
+            .or_else(|_| Decimal::from_scientific(&text))
+            .ok()
+    }
+
+    /// Get the value as a string
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.as_str()
+    }
+}
+
+impl From<serde_json::Value> for Any {
+    fn from(value: serde_json::Value) -> Self {
+        Any(value)
+    }
+}
 impl std::fmt::Display for Any {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
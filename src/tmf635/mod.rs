@@ -13,6 +13,7 @@
 // limitations under the License.
 //! TMF635 Usage Management
 
+pub use crate::common::money::Money;
 pub use any::*;
 pub use association_specification_ref::*;
 pub use attachment_ref_or_value::*;
@@ -24,7 +25,6 @@ pub use constraint_ref::*;
 pub use entity_specification_relationship::*;
 pub use event_subscription::*;
 pub use event_subscription_input::*;
-pub use money::*;
 pub use product_ref::*;
 pub use quantity::*;
 pub use rated_product_usage::*;
@@ -65,10 +65,11 @@ mod constraint_ref;
 mod entity_specification_relationship;
 mod event_subscription;
 mod event_subscription_input;
-mod money;
 mod product_ref;
 mod quantity;
 mod rated_product_usage;
+#[cfg(all(feature = "tmf620", feature = "tmf637"))]
+pub mod rating;
 mod related_party;
 mod target_entity_schema;
 mod time_period;
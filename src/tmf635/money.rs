@@ -1,34 +0,0 @@
-use serde::{Deserialize, Serialize};
-///A base / value business entity used to represent money
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct Money {
-    ///When sub-classing, this defines the super-class
-    #[serde(rename = "@baseType")]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub base_type: Option<String>,
-    ///A URI to a JSON-Schema file that defines additional attributes and relationships
-    #[serde(rename = "@schemaLocation")]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub schema_location: Option<String>,
-    ///When sub-classing, this defines the sub-class Extensible name
-    #[serde(rename = "@type")]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub type_: Option<String>,
-    ///Hyperlink reference
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub href: Option<String>,
-    ///unique identifier
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub id: Option<String>,
-    ///Currency (ISO4217 norm uses 3 letters to define the currency)
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub unit: Option<String>,
-    ///A positive floating point number
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub value: Option<f64>,
-}
-impl std::fmt::Display for Money {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}", serde_json::to_string(self).unwrap())
-    }
-}
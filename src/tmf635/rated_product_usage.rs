@@ -17,7 +17,11 @@ pub struct RatedProductUsage {
     pub type_: Option<String>,
     ///A base / value business entity used to represent money
     #[serde(rename = "bucketValueConvertedInAmount")]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::common::money::optional_number"
+    )]
     pub bucket_value_converted_in_amount: Option<Money>,
     ///Boolean indicating if usage have been billed or not
     #[serde(rename = "isBilled")]
@@ -45,11 +49,19 @@ pub struct RatedProductUsage {
     pub rating_date: Option<crate::DateTime>,
     ///A base / value business entity used to represent money
     #[serde(rename = "taxExcludedRatingAmount")]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::common::money::optional_number"
+    )]
     pub tax_excluded_rating_amount: Option<Money>,
     ///A base / value business entity used to represent money
     #[serde(rename = "taxIncludedRatingAmount")]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::common::money::optional_number"
+    )]
     pub tax_included_rating_amount: Option<Money>,
     ///Tax rate
     #[serde(rename = "taxRate")]
@@ -0,0 +1,488 @@
+//! Usage Rating Module
+//!
+//! Rates [`Usage`] records against the usage prices ([`ProductOfferingPrice`] with [`PriceType::Usage`])
+//! of the product offering the customer's product was created from.
+//!
+//! Rating rules:
+//! - The product is taken from an existing rated product usage, otherwise from the `productId` characteristic.
+//! - Only usage that is received, guided or recycled can be rated.
+//! - Each usage price is rated using the characteristic named after its unit of measure, e.g. `GB`.
+//!   If there is no such characteristic, a characteristic in a unit that can be converted is used instead.
+//! - Tiered prices are applied to the quantity rounded up to whole units, flat prices to the exact quantity.
+//! - Tax is taken from the combined rate of the price tax items if present, otherwise the engine
+//!   default is used.
+//! - Amounts are rounded to two decimal places.
+//!
+//! Rated usage moves to [`UsageStatusType::Rated`], usage that cannot be rated moves to
+//! [`UsageStatusType::Rejected`] and the reason is reported.
+//! ```
+//! # #[cfg(feature = "build-V4")]
+//! # use tmflib::tmf620::product_offering::{ProductOffering, ProductOfferingRef};
+//! # #[cfg(feature = "build-V5")]
+//! # use tmflib::tmf620::product_offering_v5::{ProductOffering, ProductOfferingRef};
+//! # #[cfg(feature = "build-V4")]
+//! # use tmflib::tmf637::v4::product::Product;
+//! # #[cfg(feature = "build-V5")]
+//! # use tmflib::tmf637::v5::product::Product;
+//! use tmflib::common::money::Money;
+//! use tmflib::tmf620::product_offering_price::{PriceType, ProductOfferingPrice};
+//! use tmflib::tmf635::rating::RatingEngine;
+//! use tmflib::tmf635::{Any, Usage, UsageCharacteristic, UsageStatusType};
+//! use tmflib::HasId;
+//!
+//! let offer = ProductOffering::new("Mobile Data");
+//! let product = Product::new("My Mobile").product_offering(ProductOfferingRef::from(offer.clone()));
+//! let per_gb = ProductOfferingPrice::new("Data")
+//!     .price_type(PriceType::Usage)
+//!     .unit_of_measure("GB")
+//!     .price(Money::from(10));
+//!
+//! let mut engine = RatingEngine::new();
+//! engine.add_product(&product);
+//! engine.add_price(offer.get_id(), per_gb);
+//!
+//! let mut usage = Usage::default();
+//! usage.usage_characteristic = Some(vec![
+//!     UsageCharacteristic { name: "productId".into(), value: Any(product.get_id().into()), ..Default::default() },
+//!     UsageCharacteristic { name: "GB".into(), value: Any(1.5.into()), ..Default::default() },
+//! ]);
+//! engine.rate(&mut usage).unwrap();
+//! assert_eq!(usage.status, Some(UsageStatusType::Rated));
+//! ```
+
+use std::collections::HashMap;
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::{Money, ProductRef, RatedProductUsage, Usage, UsageStatusType};
+use crate::common::tmf_error::TMFError;
+use crate::tmf620::product_offering_price::{PriceType, ProductOfferingPrice};
+#[cfg(feature = "build-V4")]
+use crate::tmf637::v4::product::Product;
+#[cfg(feature = "build-V5")]
+use crate::tmf637::v5::product::Product;
+use crate::{now, HasId, HasName};
+
+/// Default tax rate, matching the default used by the pricing engine
+const DEFAULT_TAX_RATE: Decimal = Decimal::from_parts(10, 0, 0, false, 2);
+const RATING_DP: u32 = 2;
+/// Name of the usage characteristic holding the id of the product that was used
+pub const CHAR_PRODUCT_ID: &str = "productId";
+/// Rating tag for usage rated outside a usage bundle
+pub const TAG_USAGE: &str = "usage";
+
+/// Reason a usage record could not be rated
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingRejection {
+    /// Id of the rejected usage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage_id: Option<String>,
+    /// Why the usage was rejected
+    pub reason: String,
+}
+
+/// Usage Rating Engine
+#[derive(Clone, Debug)]
+pub struct RatingEngine {
+    products: HashMap<String, String>,
+    prices: HashMap<String, Vec<ProductOfferingPrice>>,
+    conversions: Vec<(String, String, f64)>,
+    tax_rate: Decimal,
+}
+
+impl Default for RatingEngine {
+    fn default() -> Self {
+        RatingEngine {
+            products: HashMap::new(),
+            prices: HashMap::new(),
+            conversions: vec![],
+            tax_rate: DEFAULT_TAX_RATE,
+        }
+    }
+}
+
+impl RatingEngine {
+    /// Create a new rating engine
+    pub fn new() -> RatingEngine {
+        RatingEngine::default()
+    }
+
+    /// Builder pattern to set the default tax rate, used when a price has no tax items
+    pub fn tax(mut self, rate: f32) -> RatingEngine {
+        self.tax_rate = Decimal::from_f32(rate).unwrap_or_default();
+        self
+    }
+
+    /// Builder pattern to add a unit conversion, e.g. `convert("MB", "GB", 1.0 / 1024.0)`
+    pub fn convert(
+        mut self,
+        from: impl Into<String>,
+        to: impl Into<String>,
+        factor: f64,
+    ) -> RatingEngine {
+        self.conversions.push((from.into(), to.into(), factor));
+        self
+    }
+
+    /// Add a customer product, usage of this product is rated using the prices of its offering
+    pub fn add_product(&mut self, product: &Product) {
+        if let Some(offering) = product.get_product_offering() {
+            self.products.insert(product.get_id(), offering.id.clone());
+        }
+    }
+
+    /// Add a price for a product offering, only usage prices are used for rating
+    pub fn add_price(&mut self, offering_id: impl Into<String>, price: ProductOfferingPrice) {
+        self.prices
+            .entry(offering_id.into())
+            .or_default()
+            .push(price);
+    }
+
+    /// Rate a single usage record. On success the usage holds the rated product usage and is
+    /// marked as rated. On failure the usage is marked as rejected and the reason returned.
+    pub fn rate(&self, usage: &mut Usage) -> Result<(), TMFError> {
+        match usage.status.clone().unwrap_or_default() {
+            UsageStatusType::Received | UsageStatusType::Guided | UsageStatusType::Recycled => {}
+            status => {
+                return Err(TMFError::StateError(format!(
+                    "Usage in state {status:?} cannot be rated"
+                )))
+            }
+        }
+        match self.rated_usage(usage) {
+            Ok(rated) => {
+                usage.rated_product_usage = Some(rated);
+                usage.status = Some(UsageStatusType::Rated);
+                Ok(())
+            }
+            Err(e) => {
+                usage.status = Some(UsageStatusType::Rejected);
+                Err(e)
+            }
+        }
+    }
+
+    /// Rate a batch of usage records, returning the rejected records
+    pub fn rate_all(&self, usage: &mut [Usage]) -> Vec<RatingRejection> {
+        usage
+            .iter_mut()
+            .filter_map(|u| {
+                self.rate(u).err().map(|e| RatingRejection {
+                    usage_id: u.id.clone(),
+                    reason: e.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    fn product_id(usage: &Usage) -> Result<String, TMFError> {
+        let rated = usage
+            .rated_product_usage
+            .iter()
+            .flatten()
+            .find_map(|r| r.product_ref.as_ref().map(|p| p.id.clone()));
+        if let Some(id) = rated {
+            return Ok(id);
+        }
+        usage
+            .get_characteristic(CHAR_PRODUCT_ID)
+            .and_then(|c| c.value.as_str())
+            .map(|s| s.to_string())
+            .ok_or(TMFError::NoDataError(format!(
+                "Usage {} does not identify a product",
+                usage.id.clone().unwrap_or_default()
+            )))
+    }
+
+    /// Find the quantity of usage in the given unit, converting units if required
+    fn quantity(&self, usage: &Usage, unit: &str) -> Result<Option<f64>, TMFError> {
+        let direct = std::iter::once((unit, 1.0));
+        let converted = self
+            .conversions
+            .iter()
+            .filter(|(_, to, _)| to.eq_ignore_ascii_case(unit))
+            .map(|(from, _, factor)| (from.as_str(), *factor));
+        for (name, factor) in direct.chain(converted) {
+            if let Some(c) = usage.get_characteristic(name) {
+                let value = c.value.as_f64().ok_or(TMFError::GenericError(format!(
+                    "Characteristic {} is not a number",
+                    c.name
+                )))?;
+                if value < 0.0 {
+                    return Err(TMFError::GenericError(format!(
+                        "Characteristic {} is negative",
+                        c.name
+                    )));
+                }
+                return Ok(Some(value * factor));
+            }
+        }
+        Ok(None)
+    }
+
+    fn amount(pop: &ProductOfferingPrice, quantity: f64) -> Result<Money, TMFError> {
+        let has_tiers = pop.price_tier.as_ref().is_some_and(|t| !t.is_empty());
+        let amount = match has_tiers {
+            true => {
+                let units = quantity.ceil().to_u32().ok_or(TMFError::TierError(format!(
+                    "Quantity {quantity} is out of range for price {}",
+                    pop.get_name()
+                )))?;
+                pop.tier_amount(units)?
+            }
+            false => {
+                let price = pop.get_price().ok_or(TMFError::NoDataError(format!(
+                    "Price {} has no amount",
+                    pop.get_name()
+                )))?;
+                let invalid = || {
+                    TMFError::InvalidQuantity(format!(
+                        "Quantity {quantity} cannot be rated by price {}",
+                        pop.get_name()
+                    ))
+                };
+                let units = Decimal::from_f64(quantity).ok_or_else(invalid)?;
+                Money {
+                    unit: price.unit.clone(),
+                    value: price.value.checked_mul(units).ok_or_else(invalid)?,
+                }
+            }
+        };
+        Ok(Money {
+            unit: amount.unit,
+            value: amount.value.round_dp(RATING_DP),
+        })
+    }
+
+    fn rated_usage(&self, usage: &Usage) -> Result<Vec<RatedProductUsage>, TMFError> {
+        let product_id = RatingEngine::product_id(usage)?;
+        let offering_id = self
+            .products
+            .get(&product_id)
+            .ok_or(TMFError::InvalidReference(format!(
+                "Unknown product {product_id}"
+            )))?;
+        let prices: Vec<&ProductOfferingPrice> = self
+            .prices
+            .get(offering_id)
+            .iter()
+            .flat_map(|p| p.iter())
+            .filter(|p| matches!(p.get_price_type(), Ok(PriceType::Usage)))
+            .collect();
+        if prices.is_empty() {
+            return Err(TMFError::NoDataError(format!(
+                "Offering {offering_id} has no usage prices"
+            )));
+        }
+        let rating_date = now();
+        let mut rated = vec![];
+        for pop in prices {
+            let unit = match pop.get_unit_of_measure() {
+                Some(u) => u,
+                None => continue,
+            };
+            let quantity = match self.quantity(usage, &unit)? {
+                Some(q) => q,
+                None => continue,
+            };
+            let amount = RatingEngine::amount(pop, quantity)?;
+            let rate = pop
+                .get_tax_rate()
+                .and_then(Decimal::from_f32)
+                .unwrap_or(self.tax_rate);
+            let tax_included = Money {
+                unit: amount.unit.clone(),
+                value: (amount.value + amount.value * rate).round_dp(RATING_DP),
+            };
+            rated.push(RatedProductUsage {
+                is_billed: Some(false),
+                is_tax_exempt: Some(rate.is_zero()),
+                offer_tariff_type: pop.name.clone(),
+                product_ref: Some(ProductRef {
+                    id: product_id.clone(),
+                    referred_type: Some("Product".to_string()),
+                    ..Default::default()
+                }),
+                rating_amount_type: Some(PriceType::Usage.to_string()),
+                rating_date: Some(rating_date.clone()),
+                tax_excluded_rating_amount: Some(amount),
+                tax_included_rating_amount: Some(tax_included),
+                tax_rate: rate.to_f64(),
+                usage_rating_tag: Some(TAG_USAGE.to_string()),
+                ..Default::default()
+            });
+        }
+        if rated.is_empty() {
+            return Err(TMFError::NoDataError(format!(
+                "Usage {} has no quantity in a unit priced by offering {offering_id}",
+                usage.id.clone().unwrap_or_default()
+            )));
+        }
+        Ok(rated)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::tax_item::TaxItem;
+    #[cfg(feature = "build-V4")]
+    use crate::tmf620::product_offering::{ProductOffering, ProductOfferingRef};
+    #[cfg(feature = "build-V5")]
+    use crate::tmf620::product_offering_v5::{ProductOffering, ProductOfferingRef};
+    use crate::tmf635::{Any, UsageCharacteristic};
+
+    fn characteristic(name: &str, value: serde_json::Value) -> UsageCharacteristic {
+        UsageCharacteristic {
+            name: name.to_string(),
+            value: Any(value),
+            ..Default::default()
+        }
+    }
+
+    fn usage(product: &Product, unit: &str, quantity: f64) -> Usage {
+        Usage {
+            id: Some("U1".to_string()),
+            usage_characteristic: Some(vec![
+                characteristic(CHAR_PRODUCT_ID, product.get_id().into()),
+                characteristic(unit, quantity.into()),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    fn engine() -> (RatingEngine, Product) {
+        let offer = ProductOffering::new("Mobile Data");
+        let product =
+            Product::new("My Mobile").product_offering(ProductOfferingRef::from(offer.clone()));
+        let data = ProductOfferingPrice::new("Data")
+            .price_type(PriceType::Usage)
+            .unit_of_measure("GB")
+            .tier(0, Some(10), Money::from(5))
+            .tier(10, None, Money::from(2));
+        let mut engine = RatingEngine::new()
+            .tax(0.0)
+            .convert("MB", "GB", 1.0 / 1024.0);
+        engine.add_product(&product);
+        engine.add_price(offer.get_id(), data);
+        (engine, product)
+    }
+
+    #[test]
+    fn test_rate_tiered() {
+        let (engine, product) = engine();
+        let mut usage = usage(&product, "GB", 11.5);
+
+        engine.rate(&mut usage).unwrap();
+
+        let rated = usage.rated_product_usage.unwrap();
+        // 10 GB at 5 plus 2 GB (rounded up) at 2
+        assert_eq!(
+            rated[0].tax_excluded_rating_amount.as_ref().unwrap().value,
+            Decimal::from(54)
+        );
+        assert_eq!(rated[0].product_ref.as_ref().unwrap().id, product.get_id());
+        assert_eq!(usage.status, Some(UsageStatusType::Rated));
+        // TMF635 amounts are encoded as numbers
+        let json = serde_json::to_value(&rated[0]).unwrap();
+        assert!(json["taxExcludedRatingAmount"]["value"].is_number());
+    }
+
+    #[test]
+    fn test_rate_converted() {
+        let (engine, product) = engine();
+        let mut usage = usage(&product, "MB", 2048.0);
+
+        engine.rate(&mut usage).unwrap();
+
+        let rated = usage.rated_product_usage.unwrap();
+        assert_eq!(
+            rated[0].tax_included_rating_amount.as_ref().unwrap().value,
+            Decimal::from(10)
+        );
+    }
+
+    #[test]
+    fn test_rate_rejected() {
+        let (engine, product) = engine();
+        let mut usages = vec![usage(&product, "minutes", 10.0), Usage::default()];
+
+        let rejected = engine.rate_all(&mut usages);
+
+        assert_eq!(rejected.len(), 2);
+        assert_eq!(rejected[0].usage_id, Some("U1".to_string()));
+        assert_eq!(usages[1].status, Some(UsageStatusType::Rejected));
+        // Rejected usage must be recycled before it can be rated again
+        assert!(matches!(
+            engine.rate(&mut usages[0]),
+            Err(TMFError::StateError(_))
+        ));
+    }
+
+    #[test]
+    fn test_rate_quantity_out_of_range() {
+        let (engine, product) = engine();
+        let mut usage = usage(&product, "GB", 1e12);
+
+        assert!(matches!(
+            engine.rate(&mut usage),
+            Err(TMFError::TierError(_))
+        ));
+        assert_eq!(usage.status, Some(UsageStatusType::Rejected));
+    }
+
+    #[test]
+    fn test_rate_multiple_tax() {
+        let offer = ProductOffering::new("Mobile Data");
+        let product =
+            Product::new("My Mobile").product_offering(ProductOfferingRef::from(offer.clone()));
+        let data = ProductOfferingPrice::new("Data")
+            .price_type(PriceType::Usage)
+            .unit_of_measure("GB")
+            .price(Money::from(10))
+            .tax(TaxItem::new("GST", 0.05, Money::default()))
+            .tax(TaxItem::new("PST", 0.07, Money::default()));
+        let mut engine = RatingEngine::new();
+        engine.add_product(&product);
+        engine.add_price(offer.get_id(), data);
+        let mut usage = usage(&product, "GB", 10.0);
+
+        engine.rate(&mut usage).unwrap();
+
+        let rated = usage.rated_product_usage.unwrap();
+        assert_eq!(rated[0].tax_included_rating_amount, Some(Money::from(112)));
+        assert_eq!(rated[0].tax_rate, Some(0.12));
+    }
+
+    #[test]
+    fn test_rate_flat() {
+        let offer = ProductOffering::new("Voice");
+        let product =
+            Product::new("My Phone").product_offering(ProductOfferingRef::from(offer.clone()));
+        let minutes = ProductOfferingPrice::new("Minutes")
+            .price_type(PriceType::Usage)
+            .unit_of_measure("minutes")
+            .price(Money::from(2));
+        let mut engine = RatingEngine::new();
+        engine.add_product(&product);
+        engine.add_price(offer.get_id(), minutes);
+
+        let mut record = usage(&product, "minutes", 5.0);
+        engine.rate(&mut record).unwrap();
+        let rated = record.rated_product_usage.unwrap();
+        // The default tax rate is written exactly
+        assert_eq!(rated[0].tax_rate, Some(0.1));
+        assert_eq!(rated[0].tax_included_rating_amount, Some(Money::from(11)));
+
+        // Too large to rate, rather than rated as nothing
+        let mut record = usage(&product, "minutes", 1e30);
+        assert!(matches!(
+            engine.rate(&mut record),
+            Err(TMFError::InvalidQuantity(_))
+        ));
+    }
+}
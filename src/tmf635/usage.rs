@@ -3,7 +3,7 @@ use super::{
 };
 use serde::{Deserialize, Serialize};
 ///An occurrence of employing a Product, Service, or Resource for its intended purpose, which is of interest to the business and can have charges applied to it. It is comprised of characteristics, which represent attributes of usage.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Usage {
     ///When sub-classing, this defines the super-class
     #[serde(rename = "@baseType")]
@@ -54,6 +54,15 @@ pub struct Usage {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub usage_type: Option<String>,
 }
+impl Usage {
+    /// Find a usage characteristic by name, ignoring case
+    pub fn get_characteristic(&self, name: &str) -> Option<&UsageCharacteristic> {
+        self.usage_characteristic
+            .iter()
+            .flatten()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+}
 impl std::fmt::Display for Usage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
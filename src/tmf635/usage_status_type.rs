@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
 ///Possible values for the status of the Usage
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 pub enum UsageStatusType {
     ///The usage is planned
+    #[default]
     #[serde(rename = "received")]
     Received,
     ///The usage is accepted
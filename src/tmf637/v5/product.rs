@@ -39,6 +39,48 @@ pub struct ProductPrice {
     billing_account: Option<BillingAccountRef>,
 }
 
+impl ProductPrice {
+    /// Create a new product price of the given type, e.g. recurring
+    pub fn new(
+        name: impl Into<String>,
+        price_type: impl Into<String>,
+        price: Price,
+    ) -> ProductPrice {
+        ProductPrice {
+            name: name.into(),
+            price_type: price_type.into(),
+            price: Some(price),
+            ..Default::default()
+        }
+    }
+
+    /// Builder pattern to set the recurring charge period, e.g. month
+    pub fn recurring_charge_period(mut self, period: impl Into<String>) -> ProductPrice {
+        self.recurring_charge_period = period.into();
+        self
+    }
+
+    /// Get the name of this price
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Get the type of this price, e.g. recurring
+    pub fn get_price_type(&self) -> String {
+        self.price_type.clone()
+    }
+
+    /// Get the recurring charge period
+    pub fn get_recurring_charge_period(&self) -> String {
+        self.recurring_charge_period.clone()
+    }
+
+    /// Get the price if set
+    pub fn get_price(&self) -> Option<&Price> {
+        self.price.as_ref()
+    }
+}
+
 /// Pricing alteration
 #[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -100,6 +142,72 @@ impl Product {
         product.name = Some(name.into());
         product
     }
+
+    /// Builder pattern to set the product offering this product was created from
+    pub fn product_offering(mut self, offering: ProductOfferingRef) -> Product {
+        self.product_offering = Some(offering);
+        self
+    }
+
+    /// Get the product offering this product was created from
+    pub fn get_product_offering(&self) -> Option<&ProductOfferingRef> {
+        self.product_offering.as_ref()
+    }
+
+    /// Builder pattern to set the status of this product
+    pub fn status(mut self, status: ProductStatusType) -> Product {
+        self.status = status;
+        self
+    }
+
+    /// Get the status of this product
+    pub fn get_status(&self) -> &ProductStatusType {
+        &self.status
+    }
+
+    /// Builder pattern to set the date this product started
+    pub fn start_date(mut self, date: impl Into<DateTime>) -> Product {
+        self.start_date = Some(date.into());
+        self
+    }
+
+    /// Get the date this product started
+    pub fn get_start_date(&self) -> Option<&DateTime> {
+        self.start_date.as_ref()
+    }
+
+    /// Builder pattern to set the date this product was terminated
+    pub fn termination_date(mut self, date: impl Into<DateTime>) -> Product {
+        self.termination_date = Some(date.into());
+        self
+    }
+
+    /// Get the date this product was terminated
+    pub fn get_termination_date(&self) -> Option<&DateTime> {
+        self.termination_date.as_ref()
+    }
+
+    /// Builder pattern to set the billing account charges for this product are billed to
+    pub fn billing_account(mut self, account: BillingAccountRef) -> Product {
+        self.billing_account = Some(account);
+        self
+    }
+
+    /// Get the billing account charges for this product are billed to
+    pub fn get_billing_account(&self) -> Option<&BillingAccountRef> {
+        self.billing_account.as_ref()
+    }
+
+    /// Builder pattern to add a price to this product
+    pub fn price(mut self, price: ProductPrice) -> Product {
+        self.product_price.get_or_insert_with(Vec::new).push(price);
+        self
+    }
+
+    /// Get the prices of this product
+    pub fn get_product_price(&self) -> &[ProductPrice] {
+        self.product_price.as_deref().unwrap_or_default()
+    }
 }
 
 /// Product Term
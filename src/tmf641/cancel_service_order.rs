@@ -0,0 +1,245 @@
+//! Cancel Service Order Module
+//!
+//! A [`CancelServiceOrder`] is a task resource requesting the cancellation of an existing
+//! [`ServiceOrder`]. Applying the task drives the target order into `PendingCancellation` and
+//! completing it moves the order to `Cancelled`, with the task state tracking the outcome.
+//! ```
+//! use tmflib::common::cancel::CancelTask;
+//! use tmflib::tmf641::service_order::{ServiceOrder, ServiceOrderStateType};
+//! use tmflib::tmf641::cancel_service_order::CancelServiceOrder;
+//! use tmflib::tmf641::TaskStateType;
+//!
+//! let mut order = ServiceOrder::new();
+//! let mut cancel = CancelServiceOrder::new(&order, "Service no longer required");
+//! cancel.apply(&mut order).unwrap();
+//! cancel.complete(&mut order).unwrap();
+//! assert_eq!(order.state, Some(ServiceOrderStateType::Cancelled));
+//! assert_eq!(cancel.state, Some(TaskStateType::Done));
+//! ```
+
+use serde::{Deserialize, Serialize};
+use tmflib_derive::HasId;
+use uuid::Uuid;
+
+use super::service_order::{ServiceOrder, ServiceOrderRef, ServiceOrderStateType};
+use super::MOD_PATH;
+use crate::common::cancel::{CancelTask, Cancellable, TaskStateType};
+use crate::common::event::{Event, EventPayload};
+use crate::common::tmf_error::TMFError;
+use crate::{now, DateTime, HasId, TMFEvent, Uri};
+
+const CLASS_PATH: &str = "cancelServiceOrder";
+
+/// Request to cancel a Service Order
+#[derive(Clone, Debug, Default, Deserialize, HasId, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelServiceOrder {
+    /// Unique Id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// HTTP Uri
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub href: Option<Uri>,
+    /// Reason the order is being cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancellation_reason: Option<String>,
+    /// Message describing the outcome of the task, e.g. why it was refused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_message: Option<String>,
+    /// Date the order was actually cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_cancellation_date: Option<DateTime>,
+    /// Date the requester would like the order cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requested_cancellation_date: Option<DateTime>,
+    /// Order to be cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_order: Option<ServiceOrderRef>,
+    /// Task State
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<TaskStateType>,
+}
+
+impl CancelServiceOrder {
+    /// Create a new cancellation request for a service order
+    pub fn new(order: &ServiceOrder, reason: impl Into<String>) -> CancelServiceOrder {
+        CancelServiceOrder {
+            cancellation_reason: Some(reason.into()),
+            service_order: Some(ServiceOrderRef::from(order)),
+            state: Some(TaskStateType::Acknowledged),
+            ..CancelServiceOrder::create()
+        }
+    }
+
+    /// Builder pattern to set the requested cancellation date
+    pub fn requested_date(mut self, date: impl Into<DateTime>) -> CancelServiceOrder {
+        self.requested_cancellation_date = Some(date.into());
+        self
+    }
+}
+
+impl Cancellable for ServiceOrder {
+    fn pending_cancellation(&mut self, reason: Option<String>) -> Result<(), TMFError> {
+        self.set_state(ServiceOrderStateType::PendingCancellation)?;
+        self.cancellation_reason = reason;
+        Ok(())
+    }
+
+    fn cancel(&mut self, reason: Option<String>, date: DateTime) -> Result<(), TMFError> {
+        self.set_state(ServiceOrderStateType::Cancelled)?;
+        self.cancellation_reason = reason;
+        self.cancellation_date = Some(date);
+        Ok(())
+    }
+}
+
+impl CancelTask for CancelServiceOrder {
+    type Order = ServiceOrder;
+
+    fn target_id(&self) -> Option<String> {
+        self.service_order.as_ref().map(|r| r.id.clone())
+    }
+
+    fn reason(&self) -> Option<String> {
+        self.cancellation_reason.clone()
+    }
+
+    fn task_state(&self) -> Option<TaskStateType> {
+        self.state.clone()
+    }
+
+    fn set_task_state(&mut self, state: TaskStateType, message: Option<String>) {
+        self.state = Some(state);
+        if message.is_some() {
+            self.completion_message = message;
+        }
+    }
+
+    fn set_effective_date(&mut self, date: DateTime) {
+        self.effective_cancellation_date = Some(date);
+    }
+}
+
+/// Cancel Service Order Event Type
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub enum CancelServiceOrderEventType {
+    /// Cancellation request created
+    #[default]
+    CancelServiceOrderCreateEvent,
+    /// Cancellation request changed state
+    CancelServiceOrderStateChangeEvent,
+    /// Cancellation request requires more information
+    CancelServiceOrderInformationRequiredEvent,
+}
+
+/// Cancel Service Order Event Container
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelServiceOrderEvent {
+    /// Impacted cancellation request
+    pub cancel_service_order: CancelServiceOrder,
+}
+
+impl TMFEvent<CancelServiceOrderEvent> for CancelServiceOrder {
+    fn event(&self) -> CancelServiceOrderEvent {
+        CancelServiceOrderEvent {
+            cancel_service_order: self.clone(),
+        }
+    }
+}
+
+impl EventPayload<CancelServiceOrderEvent> for CancelServiceOrder {
+    type Subject = CancelServiceOrder;
+    type EventType = CancelServiceOrderEventType;
+
+    fn to_event(
+        &self,
+        event_type: Self::EventType,
+    ) -> Event<CancelServiceOrderEvent, Self::EventType> {
+        let desc = format!("{:?} for cancellation {}", event_type, self.get_id());
+        let event_time = now();
+        Event {
+            event_id: Uuid::new_v4().to_string(),
+            id: Some(self.get_id()),
+            href: Some(self.get_href()),
+            description: Some(desc),
+            domain: Some(CancelServiceOrder::get_class()),
+            time_occurred: Some(event_time.clone()),
+            event_time,
+            event_type,
+            event: self.event(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const REASON: &str = "No longer required";
+
+    #[test]
+    fn test_cancel_new() {
+        let order = ServiceOrder::new();
+        let cancel = CancelServiceOrder::new(&order, REASON);
+
+        assert_eq!(cancel.state, Some(TaskStateType::Acknowledged));
+        assert_eq!(cancel.service_order.unwrap().id, order.get_id());
+    }
+
+    #[test]
+    fn test_cancel_apply_complete() {
+        let mut order = ServiceOrder::new();
+        let mut cancel = CancelServiceOrder::new(&order, REASON);
+
+        cancel.apply(&mut order).unwrap();
+        assert_eq!(
+            order.state,
+            Some(ServiceOrderStateType::PendingCancellation)
+        );
+        assert_eq!(cancel.state, Some(TaskStateType::InProgress));
+
+        cancel.complete(&mut order).unwrap();
+        assert_eq!(order.state, Some(ServiceOrderStateType::Cancelled));
+        assert_eq!(order.cancellation_reason, Some(REASON.to_string()));
+        assert!(order.cancellation_date.is_some());
+        assert_eq!(cancel.state, Some(TaskStateType::Done));
+    }
+
+    #[test]
+    fn test_cancel_completed_order() {
+        let mut order = ServiceOrder::new();
+        order.set_state(ServiceOrderStateType::InProgress).unwrap();
+        order.set_state(ServiceOrderStateType::Completed).unwrap();
+        let mut cancel = CancelServiceOrder::new(&order, REASON);
+
+        let result = cancel.apply(&mut order);
+
+        assert!(result.is_err());
+        assert_eq!(cancel.state, Some(TaskStateType::TerminatedWithError));
+        assert!(cancel.completion_message.is_some());
+        assert_eq!(order.state, Some(ServiceOrderStateType::Completed));
+    }
+
+    #[test]
+    fn test_cancel_wrong_order() {
+        let order = ServiceOrder::new();
+        let mut other = ServiceOrder::new();
+        let mut cancel = CancelServiceOrder::new(&order, REASON);
+
+        assert!(cancel.apply(&mut other).is_err());
+    }
+
+    #[test]
+    fn test_cancel_event() {
+        let order = ServiceOrder::new();
+        let cancel = CancelServiceOrder::new(&order, REASON);
+
+        let event =
+            cancel.to_event(CancelServiceOrderEventType::CancelServiceOrderStateChangeEvent);
+
+        assert_eq!(event.id, Some(cancel.get_id()));
+        assert_eq!(event.event.cancel_service_order.get_id(), cancel.get_id());
+    }
+}
@@ -18,6 +18,9 @@
 
 const MOD_PATH: &str = "serviceOrder/v4";
 
+pub mod cancel_service_order;
 /// Service Order
 pub mod service_order;
 pub mod service_order_item;
+
+pub use crate::common::cancel::TaskStateType;
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 // URL Path components
 use super::service_order_item::ServiceOrderItem;
 use super::MOD_PATH;
+use crate::common::cancel::OrderStateType;
+use crate::common::dependency::{DependencyGraph, ExecutionPlan};
 use crate::common::note::Note;
 use crate::common::related_party::RelatedParty;
 use crate::common::tmf_error::TMFError;
@@ -40,6 +42,65 @@ pub enum ServiceOrderStateType {
     PendingCancellation,
 }
 
+impl ServiceOrderStateType {
+    /// Returns true if no further state changes are possible
+    pub fn is_final(&self) -> bool {
+        OrderStateType::from(self).is_final()
+    }
+
+    /// Returns true if the order state machine allows moving to the new state,
+    /// see [`OrderStateType::can_transition`]
+    pub fn can_transition(&self, to: &ServiceOrderStateType) -> bool {
+        OrderStateType::from(self).can_transition(&OrderStateType::from(to))
+    }
+}
+
+impl From<&ServiceOrderStateType> for OrderStateType {
+    fn from(value: &ServiceOrderStateType) -> Self {
+        match value {
+            ServiceOrderStateType::Acknowledged => OrderStateType::Acknowledged,
+            ServiceOrderStateType::Rejected => OrderStateType::Rejected,
+            ServiceOrderStateType::Pending => OrderStateType::Pending,
+            ServiceOrderStateType::Held => OrderStateType::Held,
+            ServiceOrderStateType::InProgress => OrderStateType::InProgress,
+            ServiceOrderStateType::Cancelled => OrderStateType::Cancelled,
+            ServiceOrderStateType::Completed => OrderStateType::Completed,
+            ServiceOrderStateType::Failed => OrderStateType::Failed,
+            ServiceOrderStateType::Partial => OrderStateType::Partial,
+            ServiceOrderStateType::AccessingCancellation => OrderStateType::AssessingCancellation,
+            ServiceOrderStateType::PendingCancellation => OrderStateType::PendingCancellation,
+        }
+    }
+}
+
+/// Reference to a Service Order
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceOrderRef {
+    /// Unique Id of Service Order
+    pub id: String,
+    /// Link to Service Order
+    pub href: String,
+    /// Name or description of service order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Type of object referred to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "@referredType")]
+    pub referred_type: Option<String>,
+}
+
+impl From<&ServiceOrder> for ServiceOrderRef {
+    fn from(value: &ServiceOrder) -> Self {
+        ServiceOrderRef {
+            id: value.get_id(),
+            href: value.get_href(),
+            name: value.description.clone(),
+            referred_type: Some(ServiceOrder::get_class()),
+        }
+    }
+}
+
 /// Service Order Object
 #[derive(
     Clone, Debug, Default, Deserialize, HasId, HasNote, HasRelatedParty, HasDescription, Serialize,
@@ -119,6 +180,32 @@ impl ServiceOrder {
             None => self.service_order_item = Some(vec![item]),
         }
     }
+
+    /// Move the order to a new state, validating the transition against the current state.
+    /// Orders without a state are treated as [`ServiceOrderStateType::Acknowledged`].
+    /// Returns the previous state.
+    pub fn set_state(
+        &mut self,
+        state: ServiceOrderStateType,
+    ) -> Result<ServiceOrderStateType, TMFError> {
+        let current = self.state.clone().unwrap_or_default();
+        if !current.can_transition(&state) {
+            return Err(TMFError::StateError(format!(
+                "Cannot move order {} from {:?} to {:?}",
+                self.get_id(),
+                current,
+                state
+            )));
+        }
+        self.state = Some(state);
+        Ok(current)
+    }
+
+    /// Generate a dependency based execution plan for the items of this order
+    pub fn execution_plan(&self) -> Result<ExecutionPlan, TMFError> {
+        DependencyGraph::new(self.service_order_item.as_deref().unwrap_or_default())?
+            .execution_plan()
+    }
 }
 
 #[cfg(test)]
@@ -146,6 +233,28 @@ mod test {
         assert_eq!(serviceorder.description.unwrap().as_str(), "Description");
     }
 
+    #[test]
+    fn test_serviceorder_set_state() {
+        let mut serviceorder = ServiceOrder::new();
+
+        let previous = serviceorder.set_state(ServiceOrderStateType::InProgress);
+        assert_eq!(previous.unwrap(), ServiceOrderStateType::Acknowledged);
+        assert!(serviceorder
+            .set_state(ServiceOrderStateType::Cancelled)
+            .is_err());
+        assert_eq!(serviceorder.state, Some(ServiceOrderStateType::InProgress));
+    }
+
+    #[test]
+    fn test_serviceorderref_from() {
+        let serviceorder = ServiceOrder::new();
+
+        let order_ref = ServiceOrderRef::from(&serviceorder);
+
+        assert_eq!(order_ref.id, serviceorder.get_id());
+        assert_eq!(order_ref.href, serviceorder.get_href());
+    }
+
     #[test]
     fn test_serviceorder_hasnote() {
         let mut serviceorder = ServiceOrder::new();
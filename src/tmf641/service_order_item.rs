@@ -2,6 +2,7 @@
 //!
 use serde::{Deserialize, Serialize};
 
+use crate::common::dependency::{is_dependency, HasDependency};
 use crate::tmf633::service_specification::ServiceSpecificationRef;
 use crate::tmf646::appointment::AppointmentRef;
 use crate::HasDescription;
@@ -70,6 +71,25 @@ pub struct ServiceOrderItem {
     /// Service
     pub service: ServiceRefOrValue,
 }
+
+impl HasDependency for ServiceOrderItem {
+    fn get_item_id(&self) -> Option<String> {
+        match self.id.is_empty() {
+            true => None,
+            false => Some(self.id.clone()),
+        }
+    }
+
+    fn get_dependencies(&self) -> Vec<String> {
+        self.service_order_item_relationship
+            .iter()
+            .flatten()
+            .filter(|r| is_dependency(&r.relationship_type))
+            .filter_map(|r| r.order_item.as_ref().map(|i| i.item_id.clone()))
+            .collect()
+    }
+}
+
 /// Reference to and external Service Order Item
 #[derive(Clone, Default, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -78,6 +98,22 @@ pub struct ServiceOrderItemRelationship {
     order_item: Option<ServiceOrderItemRef>,
 }
 
+impl ServiceOrderItemRelationship {
+    /// Create a relationship to another item within the same service order
+    pub fn new(
+        relationship_type: impl Into<String>,
+        item_id: impl Into<String>,
+    ) -> ServiceOrderItemRelationship {
+        ServiceOrderItemRelationship {
+            relationship_type: relationship_type.into(),
+            order_item: Some(ServiceOrderItemRef {
+                item_id: item_id.into(),
+                ..Default::default()
+            }),
+        }
+    }
+}
+
 /// Reference to an external
 #[derive(Clone, Default, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -90,9 +126,10 @@ pub struct ServiceOrderItemRef {
 #[cfg(test)]
 mod test {
     use super::{
-        ServiceOrderItemRef, ServiceOrderItemRelationship, ServiceOrderItemStateType,
-        ServiceRefOrValue,
+        ServiceOrderItem, ServiceOrderItemRef, ServiceOrderItemRelationship,
+        ServiceOrderItemStateType, ServiceRefOrValue,
     };
+    use crate::common::dependency::HasDependency;
 
     const SOI_STATUSTYPE_JSON: &str = "\"Acknowledged\"";
     const SERVICEREF_JSON: &str = "{
@@ -137,6 +174,19 @@ mod test {
         assert_eq!(soi_relationship.relationship_type.as_str(), "Parent/Child");
     }
 
+    #[test]
+    fn test_soi_dependencies() {
+        let mut soi = ServiceOrderItem::default();
+        soi.id = "2".to_string();
+        soi.service_order_item_relationship = Some(vec![
+            ServiceOrderItemRelationship::new("reliesOn", "1"),
+            ServiceOrderItemRelationship::new("bundles", "3"),
+        ]);
+
+        assert_eq!(soi.get_item_id(), Some("2".to_string()));
+        assert_eq!(soi.get_dependencies(), vec!["1".to_string()]);
+    }
+
     #[test]
     fn test_soiref_deserialize() {
         let soiref: ServiceOrderItemRef = serde_json::from_str(SOI_REF_JSON).unwrap();
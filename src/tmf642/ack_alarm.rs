@@ -1,4 +1,6 @@
-use super::AlarmRefOrValue;
+use super::alarm_ref_or_value::matches_any;
+use super::{Alarm, AlarmRefOrValue, TASK_DONE};
+use crate::now;
 use crate::{common::entity::Entity, DateTime};
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +34,29 @@ pub struct AckAlarm {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub state: Option<String>,
 }
+impl AckAlarm {
+    /// Acknowledge all alarms matching any of the alarm patterns, returning the alarms acknowledged.
+    /// Alarms that are already acknowledged are not affected.
+    pub fn apply(&mut self, alarms: &mut [Alarm]) -> Vec<AlarmRefOrValue> {
+        let time = self.ack_time.get_or_insert_with(now).clone();
+        let acked: Vec<AlarmRefOrValue> = alarms
+            .iter_mut()
+            .filter(|a| matches_any(&self.alarm_pattern, a))
+            .filter_map(|a| {
+                a.acknowledge(
+                    self.ack_user_id.clone(),
+                    self.ack_system_id.clone(),
+                    time.clone(),
+                )
+                .then(|| AlarmRefOrValue::reference(a))
+            })
+            .collect();
+        self.acked_alarm.extend(acked.iter().cloned());
+        self.state = Some(TASK_DONE.to_string());
+        acked
+    }
+}
+
 impl std::fmt::Display for AckAlarm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
@@ -48,3 +73,32 @@ impl std::ops::DerefMut for AckAlarm {
         &mut self.entity
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tmf642::{AlarmType, AlarmedObjectRef, PerceivedSeverity};
+
+    #[test]
+    fn test_ack_alarm_apply() {
+        let alarm = Alarm::new(
+            AlarmedObjectRef::new("router-01"),
+            AlarmType::EquipmentAlarm,
+            PerceivedSeverity::Critical,
+        );
+        let mut alarms = vec![alarm.clone()];
+        let mut task = AckAlarm {
+            alarm_pattern: vec![AlarmRefOrValue::reference(&alarm)],
+            ack_user_id: Some("user".to_string()),
+            ack_system_id: Some("nms".to_string()),
+            ..Default::default()
+        };
+
+        let acked = task.apply(&mut alarms);
+
+        assert_eq!(acked.len(), 1);
+        assert!(alarms[0].is_acknowledged());
+        assert_eq!(alarms[0].ack_system_id, Some("nms".to_string()));
+        assert_eq!(alarms[0].alarm_changed_time, task.ack_time);
+    }
+}
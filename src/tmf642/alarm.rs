@@ -14,11 +14,15 @@
 //! Alarm defines an alarm for use in TMForum Open-APIs - When used for in a schema it means that the Entity described by the schema  MUST be extended with the @type
 
 use super::{
-    AlarmRef, AlarmType, AlarmedObjectRef, Comment, CrossedThresholdInformation, PerceivedSeverity,
-    RelatedPlace, ServiceRef, MOD_PATH,
+    AckStateType, AlarmRef, AlarmStateType, AlarmType, AlarmedObjectRef, Comment,
+    CrossedThresholdInformation, PerceivedSeverity, ProbableCause, RelatedPlace, ServiceRef,
+    MOD_PATH,
 };
-use crate::{common::entity::Entity, DateTime, HasId, Uri};
+use crate::common::event::{Event, EventPayload};
+use crate::common::tmf_error::TMFError;
+use crate::{common::entity::Entity, now, DateTime, HasId, TMFEvent, Uri};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 const CLASS_PATH: &str = "alarm";
 
@@ -37,7 +41,7 @@ pub struct Alarm {
     ///Provides the Acknowledgement State of the alarm (unacknowledged, acknowledged).
     #[serde(rename = "ackState")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub ack_state: Option<String>,
+    pub ack_state: Option<AckStateType>,
     ///Provides the name of the system that last changed the ackState of an alarm, i.e. acknowledged or unacknowledged the alarm.
     #[serde(rename = "ackSystemId")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -133,7 +137,7 @@ pub struct Alarm {
     ///Provides the probable cause of the alarm. The values are consistent with ITU-T Recommendation X.733 or 3GPP TS 32.111-2 Annex B.
     #[serde(rename = "probableCause")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub probable_cause: Option<String>,
+    pub probable_cause: Option<ProbableCause>,
     ///Indicates proposed repair actions, if known to the system emitting the alarm.
     #[serde(rename = "proposedRepairedActions")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -156,8 +160,203 @@ pub struct Alarm {
     pub specific_problem: Option<String>,
     ///Defines the alarm state during its life cycle (raised, updated, cleared).
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub state: Option<String>,
+    pub state: Option<AlarmStateType>,
 }
+
+impl Alarm {
+    /// Raise a new alarm against an alarmed object
+    pub fn new(
+        alarmed_object: AlarmedObjectRef,
+        alarm_type: AlarmType,
+        severity: PerceivedSeverity,
+    ) -> Alarm {
+        let time = now();
+        Alarm {
+            alarmed_object: Some(alarmed_object),
+            alarm_type: Some(alarm_type),
+            perceived_severity: Some(severity),
+            state: Some(AlarmStateType::Raised),
+            ack_state: Some(AckStateType::Unacknowledged),
+            alarm_raised_time: Some(time.clone()),
+            alarm_reporting_time: Some(time),
+            ..Alarm::create()
+        }
+    }
+
+    /// Has this alarm been cleared
+    pub fn is_cleared(&self) -> bool {
+        self.state == Some(AlarmStateType::Cleared)
+            || self.perceived_severity == Some(PerceivedSeverity::Cleared)
+    }
+
+    /// Is this alarm acknowledged
+    pub fn is_acknowledged(&self) -> bool {
+        self.ack_state == Some(AckStateType::Acknowledged)
+    }
+
+    /// Change the perceived severity, returning the previous severity.
+    /// Once cleared the severity can no longer be changed, use [`Alarm::clear`] to clear an alarm.
+    pub fn set_severity(
+        &mut self,
+        severity: PerceivedSeverity,
+    ) -> Result<Option<PerceivedSeverity>, TMFError> {
+        if self.is_cleared() {
+            return Err(TMFError::StateError(format!(
+                "Alarm {} is cleared, severity can no longer be changed",
+                self.get_id()
+            )));
+        }
+        if severity == PerceivedSeverity::Cleared {
+            return Err(TMFError::StateError(format!(
+                "Alarm {} must be cleared using clear()",
+                self.get_id()
+            )));
+        }
+        let previous = self.perceived_severity.replace(severity);
+        self.state = Some(AlarmStateType::Updated);
+        self.alarm_changed_time = Some(now());
+        Ok(previous)
+    }
+
+    /// Acknowledge this alarm. Returns false if the alarm was already acknowledged.
+    pub fn acknowledge(
+        &mut self,
+        user_id: Option<String>,
+        system_id: Option<String>,
+        time: DateTime,
+    ) -> bool {
+        if self.is_acknowledged() {
+            return false;
+        }
+        self.ack_state = Some(AckStateType::Acknowledged);
+        self.ack_user_id = user_id;
+        self.ack_system_id = system_id;
+        self.alarm_changed_time = Some(time);
+        true
+    }
+
+    /// Remove the acknowledgement from this alarm. Returns false if the alarm was not acknowledged.
+    pub fn unacknowledge(
+        &mut self,
+        user_id: Option<String>,
+        system_id: Option<String>,
+        time: DateTime,
+    ) -> bool {
+        if !self.is_acknowledged() {
+            return false;
+        }
+        self.ack_state = Some(AckStateType::Unacknowledged);
+        self.ack_user_id = user_id;
+        self.ack_system_id = system_id;
+        self.alarm_changed_time = Some(time);
+        true
+    }
+
+    /// Clear this alarm, forcing the perceived severity to cleared.
+    pub fn clear(
+        &mut self,
+        user_id: Option<String>,
+        system_id: Option<String>,
+        time: DateTime,
+    ) -> Result<(), TMFError> {
+        if self.is_cleared() {
+            return Err(TMFError::StateError(format!(
+                "Alarm {} is already cleared",
+                self.get_id()
+            )));
+        }
+        self.state = Some(AlarmStateType::Cleared);
+        self.perceived_severity = Some(PerceivedSeverity::Cleared);
+        self.clear_user_id = user_id;
+        self.clear_system_id = system_id;
+        self.alarm_cleared_time = Some(time.clone());
+        self.alarm_changed_time = Some(time);
+        Ok(())
+    }
+
+    /// Probable cause of this alarm
+    pub fn get_probable_cause(&self) -> Option<ProbableCause> {
+        self.probable_cause.clone()
+    }
+
+    /// Set the probable cause, validating it against the alarm type if one is set.
+    /// A vendor specific cause naming a known cause is stored as the known cause.
+    pub fn set_probable_cause(&mut self, cause: ProbableCause) -> Result<(), TMFError> {
+        let cause = cause.normalised();
+        if let Some(alarm_type) = &self.alarm_type {
+            cause.validate(alarm_type)?;
+        }
+        self.probable_cause = Some(cause);
+        Ok(())
+    }
+
+    /// Validate the probable cause of this alarm is legal for its alarm type
+    pub fn validate_probable_cause(&self) -> Result<(), TMFError> {
+        match (self.get_probable_cause(), &self.alarm_type) {
+            (Some(cause), Some(alarm_type)) => cause.validate(alarm_type),
+            _ => Ok(()),
+        }
+    }
+
+    /// Add a comment to this alarm, setting the comment time if not already set.
+    pub fn add_comment(&mut self, mut comment: Comment) {
+        let time = comment.time.get_or_insert_with(now).clone();
+        self.comment.push(comment);
+        self.alarm_changed_time = Some(time);
+    }
+}
+
+/// Alarm Event Type
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum AlarmEventType {
+    /// Alarm Created
+    #[default]
+    AlarmCreateEvent,
+    /// Alarm attribute changed, the changed attribute is given by the event field path
+    AlarmAttributeValueChangeEvent,
+    /// Alarm state changed, e.g. cleared
+    AlarmStateChangeEvent,
+    /// Alarm Deleted
+    AlarmDeleteEvent,
+}
+
+/// Alarm Event Container
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct AlarmEvent {
+    /// Impacted alarm
+    pub alarm: Alarm,
+}
+
+impl TMFEvent<AlarmEvent> for Alarm {
+    fn event(&self) -> AlarmEvent {
+        AlarmEvent {
+            alarm: self.clone(),
+        }
+    }
+}
+
+impl EventPayload<AlarmEvent> for Alarm {
+    type Subject = Alarm;
+    type EventType = AlarmEventType;
+
+    fn to_event(&self, event_type: Self::EventType) -> Event<AlarmEvent, Self::EventType> {
+        let desc = format!("{:?} for [{}]", event_type, self.get_id());
+        let event_time = now();
+        Event {
+            description: Some(desc),
+            domain: Some(Alarm::get_class()),
+            event_id: Uuid::new_v4().to_string(),
+            event_time: event_time.clone(),
+            href: Some(self.get_href()),
+            id: Some(self.get_id()),
+            time_occurred: Some(event_time),
+            event_type,
+            event: self.event(),
+            ..Default::default()
+        }
+    }
+}
+
 impl std::fmt::Display for Alarm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
@@ -174,3 +373,97 @@ impl std::ops::DerefMut for Alarm {
         &mut self.entity
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OBJECT: &str = "router-01";
+    const STATE_JSON: &str = "{ \"state\" : \"cleared\", \"ackState\" : \"acknowledged\" }";
+
+    fn alarm() -> Alarm {
+        Alarm::new(
+            AlarmedObjectRef::new(OBJECT),
+            AlarmType::CommunicationsAlarm,
+            PerceivedSeverity::Major,
+        )
+    }
+
+    #[test]
+    fn test_alarm_new() {
+        let alarm = alarm();
+
+        assert_eq!(alarm.state, Some(AlarmStateType::Raised));
+        assert_eq!(alarm.ack_state, Some(AckStateType::Unacknowledged));
+        assert!(alarm.alarm_raised_time.is_some());
+    }
+
+    #[test]
+    fn test_alarm_acknowledge() {
+        let mut alarm = alarm();
+
+        assert!(alarm.acknowledge(Some("user".to_string()), None, now()));
+        assert!(!alarm.acknowledge(None, None, now()));
+        assert_eq!(alarm.ack_user_id, Some("user".to_string()));
+        assert!(alarm.unacknowledge(None, None, now()));
+        assert!(!alarm.is_acknowledged());
+    }
+
+    #[test]
+    fn test_alarm_clear() {
+        let mut alarm = alarm();
+
+        alarm.clear(None, Some("nms".to_string()), now()).unwrap();
+
+        assert!(alarm.is_cleared());
+        assert_eq!(alarm.perceived_severity, Some(PerceivedSeverity::Cleared));
+        assert!(alarm.alarm_cleared_time.is_some());
+        assert!(alarm.clear(None, None, now()).is_err());
+        assert!(alarm.set_severity(PerceivedSeverity::Critical).is_err());
+    }
+
+    #[test]
+    fn test_alarm_set_severity() {
+        let mut alarm = alarm();
+
+        let previous = alarm.set_severity(PerceivedSeverity::Critical).unwrap();
+
+        assert_eq!(previous, Some(PerceivedSeverity::Major));
+        assert_eq!(alarm.state, Some(AlarmStateType::Updated));
+        assert!(alarm.set_severity(PerceivedSeverity::Cleared).is_err());
+    }
+
+    #[test]
+    fn test_alarm_probable_cause() {
+        let mut alarm = alarm();
+
+        assert!(alarm
+            .set_probable_cause(ProbableCause::PowerProblem)
+            .is_err());
+        alarm
+            .set_probable_cause(ProbableCause::Other("LOSS_OF_SIGNAL".to_string()))
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&alarm).unwrap()["probableCause"],
+            "lossOfSignal"
+        );
+        assert_eq!(
+            alarm.get_probable_cause(),
+            Some(ProbableCause::LossOfSignal)
+        );
+        alarm.probable_cause = Some(ProbableCause::from("fireDetected"));
+        assert!(matches!(
+            alarm.validate_probable_cause(),
+            Err(TMFError::ProbableCauseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_alarm_state_deserialize() {
+        let alarm: Alarm = serde_json::from_str(STATE_JSON).unwrap();
+
+        assert_eq!(alarm.state, Some(AlarmStateType::Cleared));
+        assert_eq!(alarm.ack_state, Some(AckStateType::Acknowledged));
+    }
+}
@@ -1,10 +1,37 @@
 use serde::{Deserialize, Serialize};
 
+use super::Alarm;
+use crate::HasId;
+
 ///Reference to an alarm for use in TMForum Open-APIs - When used for in a schema it means that the Entity described by the schema  MUST be extended with the @type
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct AlarmRef {}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct AlarmRef {
+    ///The identifier of the referred alarm.
+    pub id: String,
+    ///The URI of the referred alarm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    ///Name of the referred alarm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    ///The actual type of the target instance when needed for disambiguation.
+    #[serde(rename = "@referredType")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referred_type: Option<String>,
+}
 impl std::fmt::Display for AlarmRef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
     }
 }
+
+impl From<&Alarm> for AlarmRef {
+    fn from(value: &Alarm) -> Self {
+        AlarmRef {
+            id: value.get_id(),
+            href: value.href.clone(),
+            name: value.specific_problem.clone(),
+            referred_type: Some("Alarm".to_string()),
+        }
+    }
+}
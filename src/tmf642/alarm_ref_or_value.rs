@@ -1,9 +1,71 @@
 use serde::{Deserialize, Serialize};
+
+use super::Alarm;
 ///An alarm defined by reference or value. The polymorphic attributes @type, @schemaLocation & @referredType are related to the Alarm entity and not the AlarmRefOrValue class itself
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct AlarmRefOrValue {}
+pub struct AlarmRefOrValue {
+    ///Alarm attributes, only id and href are set when used as a reference.
+    #[serde(flatten)]
+    pub alarm: Alarm,
+}
 impl std::fmt::Display for AlarmRefOrValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
     }
 }
+
+impl AlarmRefOrValue {
+    /// Create a reference to an alarm, containing only the id and href
+    pub fn reference(alarm: &Alarm) -> AlarmRefOrValue {
+        AlarmRefOrValue {
+            alarm: Alarm {
+                id: alarm.id.clone(),
+                href: alarm.href.clone(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Create a value containing a copy of the whole alarm
+    pub fn value(alarm: &Alarm) -> AlarmRefOrValue {
+        AlarmRefOrValue {
+            alarm: alarm.clone(),
+        }
+    }
+
+    /// Use this as a pattern: an alarm matches if every attribute set here
+    /// compares equal to the same attribute of the alarm.
+    /// A pattern with no attributes set matches nothing.
+    pub fn matches(&self, alarm: &Alarm) -> bool {
+        let p = &self.alarm;
+        let checks = [
+            attr_match(&p.id, &alarm.id),
+            attr_match(&p.href, &alarm.href),
+            attr_match(
+                &p.alarmed_object.as_ref().map(|o| o.id.clone()),
+                &alarm.alarmed_object.as_ref().map(|o| o.id.clone()),
+            ),
+            attr_match(&p.alarmed_object_type, &alarm.alarmed_object_type),
+            attr_match(&p.alarm_type, &alarm.alarm_type),
+            attr_match(&p.probable_cause, &alarm.probable_cause),
+            attr_match(&p.specific_problem, &alarm.specific_problem),
+            attr_match(&p.perceived_severity, &alarm.perceived_severity),
+            attr_match(&p.state, &alarm.state),
+            attr_match(&p.ack_state, &alarm.ack_state),
+            attr_match(&p.external_alarm_id, &alarm.external_alarm_id),
+            attr_match(&p.source_system_id, &alarm.source_system_id),
+            attr_match(&p.reporting_system_id, &alarm.reporting_system_id),
+        ];
+        checks.contains(&Some(true)) && !checks.contains(&Some(false))
+    }
+}
+
+/// Returns true if the alarm matches any of the patterns
+pub(crate) fn matches_any(patterns: &[AlarmRefOrValue], alarm: &Alarm) -> bool {
+    patterns.iter().any(|p| p.matches(alarm))
+}
+
+/// None if the pattern does not set this attribute, otherwise whether it matches
+fn attr_match<T: PartialEq>(pattern: &Option<T>, value: &Option<T>) -> Option<bool> {
+    pattern.as_ref().map(|p| Some(p) == value.as_ref())
+}
@@ -0,0 +1,33 @@
+// Copyright [2026] [Ryan Ruckley]
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Alarm State Type
+//!
+//! Defines the lifecycle state of an [`Alarm`](super::Alarm)
+
+use serde::{Deserialize, Serialize};
+
+///Defines the alarm state during its life cycle (raised, updated, cleared).
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AlarmStateType {
+    /// Alarm has been raised
+    #[default]
+    #[serde(rename = "raised")]
+    Raised,
+    /// Alarm has been updated since being raised
+    #[serde(rename = "updated")]
+    Updated,
+    /// Alarm has been cleared
+    #[serde(rename = "cleared")]
+    Cleared,
+}
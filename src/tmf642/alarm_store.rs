@@ -0,0 +1,301 @@
+// Copyright [2026] [Ryan Ruckley]
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Alarm Store Module
+//!
+//! Deduplicates alarms using their X.733 identity, i.e. alarmed object, alarm type, probable cause
+//! and specific problem. Raising an alarm that is already active updates the stored alarm instead of
+//! creating a duplicate, tracking each change of severity.
+//! ```
+//! use tmflib::tmf642::alarm_store::AlarmStore;
+//! use tmflib::tmf642::{Alarm, AlarmType, AlarmedObjectRef, PerceivedSeverity};
+//!
+//! let mut store = AlarmStore::new();
+//! let object = AlarmedObjectRef::new("router-01");
+//! store.raise(Alarm::new(object.clone(), AlarmType::EquipmentAlarm, PerceivedSeverity::Minor));
+//! let events = store.raise(Alarm::new(object, AlarmType::EquipmentAlarm, PerceivedSeverity::Major));
+//! assert_eq!(store.len(), 1);
+//! assert_eq!(events[0].field_path.as_deref(), Some("perceivedSeverity"));
+//! ```
+
+use std::collections::HashMap;
+
+use super::{Alarm, AlarmEvent, AlarmEventType, AlarmType, PerceivedSeverity, ProbableCause};
+use crate::common::event::{Event, EventPayload};
+use crate::now;
+use crate::{DateTime, HasId};
+
+/// Field path used when the severity of an alarm changes
+const PATH_SEVERITY: &str = "perceivedSeverity";
+/// Field path used when an alarm is re-raised without a change in severity
+const PATH_CHANGED: &str = "alarmChangedTime";
+/// Field path used when an alarm is cleared
+const PATH_STATE: &str = "state";
+
+/// X.733 identity of an alarm, used to detect duplicates
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AlarmKey {
+    /// Id of the alarmed object
+    pub alarmed_object: Option<String>,
+    /// Alarm type
+    pub alarm_type: Option<AlarmType>,
+    /// Probable cause
+    pub probable_cause: Option<ProbableCause>,
+    /// Specific problem
+    pub specific_problem: Option<String>,
+}
+
+impl From<&Alarm> for AlarmKey {
+    fn from(value: &Alarm) -> Self {
+        AlarmKey {
+            alarmed_object: value.alarmed_object.as_ref().map(|o| o.id.clone()),
+            alarm_type: value.alarm_type.clone(),
+            probable_cause: value.probable_cause.clone(),
+            specific_problem: value.specific_problem.clone(),
+        }
+    }
+}
+
+/// Record of a change of severity
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeverityChange {
+    /// Severity from this time onwards
+    pub severity: PerceivedSeverity,
+    /// Time of the change
+    pub time: DateTime,
+}
+
+/// Store of active alarms, deduplicated by [`AlarmKey`]
+#[derive(Clone, Debug, Default)]
+pub struct AlarmStore {
+    alarms: HashMap<AlarmKey, Alarm>,
+    history: HashMap<String, Vec<SeverityChange>>,
+}
+
+impl AlarmStore {
+    /// Create an empty store
+    pub fn new() -> AlarmStore {
+        AlarmStore::default()
+    }
+
+    /// Raise an alarm, returning the events generated.
+    /// - A new alarm, or one whose previous occurrence has been cleared, is stored and generates a create event.
+    /// - A re-raised alarm updates the stored alarm's changed time and severity, generating an attribute value change event.
+    /// - A re-raised alarm with a severity of cleared clears the stored alarm, generating a state change event.
+    /// - A cleared alarm with no active occurrence is ignored and generates no events.
+    pub fn raise(&mut self, mut alarm: Alarm) -> Vec<Event<AlarmEvent, AlarmEventType>> {
+        let key = AlarmKey::from(&alarm);
+        let time = alarm
+            .alarm_changed_time
+            .clone()
+            .or(alarm.alarm_raised_time.clone())
+            .unwrap_or_else(now);
+        let severity = alarm
+            .perceived_severity
+            .clone()
+            .unwrap_or(PerceivedSeverity::Indeterminate);
+
+        if let Some(existing) = self.alarms.get_mut(&key).filter(|a| !a.is_cleared()) {
+            let id = existing.get_id();
+            if severity == PerceivedSeverity::Cleared {
+                // Only fails if already cleared which has been filtered out above
+                let _ = existing.clear(alarm.clear_user_id, alarm.clear_system_id, time.clone());
+                self.history
+                    .entry(id)
+                    .or_default()
+                    .push(SeverityChange { severity, time });
+                return vec![existing
+                    .to_event(AlarmEventType::AlarmStateChangeEvent)
+                    .path(PATH_STATE)];
+            }
+            let path = if existing.perceived_severity.as_ref() != Some(&severity) {
+                let _ = existing.set_severity(severity.clone());
+                self.history.entry(id).or_default().push(SeverityChange {
+                    severity,
+                    time: time.clone(),
+                });
+                PATH_SEVERITY
+            } else {
+                PATH_CHANGED
+            };
+            existing.alarm_changed_time = Some(time);
+            return vec![existing
+                .to_event(AlarmEventType::AlarmAttributeValueChangeEvent)
+                .path(path)];
+        }
+
+        // Nothing to clear, and a cleared alarm is never raised as a new active alarm
+        if severity == PerceivedSeverity::Cleared {
+            return vec![];
+        }
+        if alarm.id.is_none() {
+            alarm.generate_id();
+        }
+        self.history.insert(
+            alarm.get_id(),
+            vec![SeverityChange {
+                severity,
+                time: alarm.alarm_raised_time.clone().unwrap_or(time),
+            }],
+        );
+        let event = alarm.to_event(AlarmEventType::AlarmCreateEvent);
+        // Any cleared occurrence of the same alarm is replaced
+        if let Some(previous) = self.alarms.insert(key, alarm) {
+            self.history.remove(&previous.get_id());
+        }
+        vec![event]
+    }
+
+    /// Retrieve the stored alarm for a given identity
+    pub fn get(&self, key: &AlarmKey) -> Option<&Alarm> {
+        self.alarms.get(key)
+    }
+
+    /// Find the stored alarm with the same identity as the supplied alarm
+    pub fn find(&self, alarm: &Alarm) -> Option<&Alarm> {
+        self.alarms.get(&AlarmKey::from(alarm))
+    }
+
+    /// Severity history for an alarm id, oldest first
+    pub fn history(&self, id: &str) -> &[SeverityChange] {
+        self.history
+            .get(id)
+            .map(|h| h.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Iterate over the stored alarms
+    pub fn alarms(&self) -> impl Iterator<Item = &Alarm> {
+        self.alarms.values()
+    }
+
+    /// Number of stored alarms
+    pub fn len(&self) -> usize {
+        self.alarms.len()
+    }
+
+    /// Returns true if the store is empty
+    pub fn is_empty(&self) -> bool {
+        self.alarms.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tmf642::AlarmedObjectRef;
+
+    fn alarm(severity: PerceivedSeverity, cause: &str) -> Alarm {
+        let mut alarm = Alarm::new(
+            AlarmedObjectRef::new("router-01"),
+            AlarmType::EquipmentAlarm,
+            severity,
+        );
+        alarm.probable_cause = Some(ProbableCause::from(cause));
+        alarm
+    }
+
+    #[test]
+    fn test_store_dedup() {
+        let mut store = AlarmStore::new();
+        let first = alarm(PerceivedSeverity::Minor, "powerProblem");
+        let id = first.get_id();
+
+        store.raise(first);
+        let events = store.raise(alarm(PerceivedSeverity::Minor, "powerProblem"));
+
+        assert_eq!(store.len(), 1);
+        assert_eq!(
+            events[0].event_type,
+            AlarmEventType::AlarmAttributeValueChangeEvent
+        );
+        assert_eq!(events[0].field_path.as_deref(), Some(PATH_CHANGED));
+        assert_eq!(events[0].id, Some(id.clone()));
+        assert_eq!(store.history(&id).len(), 1);
+    }
+
+    #[test]
+    fn test_store_distinct_cause() {
+        let mut store = AlarmStore::new();
+
+        store.raise(alarm(PerceivedSeverity::Minor, "powerProblem"));
+        let events = store.raise(alarm(PerceivedSeverity::Minor, "fanFailure"));
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(events[0].event_type, AlarmEventType::AlarmCreateEvent);
+    }
+
+    #[test]
+    fn test_store_severity_history() {
+        let mut store = AlarmStore::new();
+        let first = alarm(PerceivedSeverity::Minor, "powerProblem");
+        let id = first.get_id();
+
+        store.raise(first);
+        store.raise(alarm(PerceivedSeverity::Critical, "powerProblem"));
+        let events = store.raise(alarm(PerceivedSeverity::Cleared, "powerProblem"));
+
+        let history: Vec<PerceivedSeverity> = store
+            .history(&id)
+            .iter()
+            .map(|h| h.severity.clone())
+            .collect();
+        assert_eq!(
+            history,
+            vec![
+                PerceivedSeverity::Minor,
+                PerceivedSeverity::Critical,
+                PerceivedSeverity::Cleared
+            ]
+        );
+        assert_eq!(events[0].event_type, AlarmEventType::AlarmStateChangeEvent);
+        assert_eq!(events[0].field_path.as_deref(), Some(PATH_STATE));
+        assert!(store
+            .find(&alarm(PerceivedSeverity::Minor, "powerProblem"))
+            .unwrap()
+            .is_cleared());
+    }
+
+    #[test]
+    fn test_store_reraise_after_clear() {
+        let mut store = AlarmStore::new();
+        let first = alarm(PerceivedSeverity::Minor, "powerProblem");
+        let first_id = first.get_id();
+
+        store.raise(first);
+        store.raise(alarm(PerceivedSeverity::Cleared, "powerProblem"));
+        let second = alarm(PerceivedSeverity::Major, "powerProblem");
+        let second_id = second.get_id();
+        let events = store.raise(second);
+
+        assert_eq!(store.len(), 1);
+        assert_eq!(events[0].event_type, AlarmEventType::AlarmCreateEvent);
+        assert!(store.history(&first_id).is_empty());
+        assert_eq!(store.history(&second_id).len(), 1);
+    }
+
+    #[test]
+    fn test_store_clear_unknown() {
+        let mut store = AlarmStore::new();
+
+        let events = store.raise(alarm(PerceivedSeverity::Cleared, "powerProblem"));
+        assert!(events.is_empty());
+        assert!(store.is_empty());
+
+        store.raise(alarm(PerceivedSeverity::Minor, "powerProblem"));
+        store.raise(alarm(PerceivedSeverity::Cleared, "powerProblem"));
+        let events = store.raise(alarm(PerceivedSeverity::Cleared, "powerProblem"));
+        assert!(events.is_empty());
+        assert_eq!(store.len(), 1);
+    }
+}
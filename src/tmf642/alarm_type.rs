@@ -15,7 +15,7 @@
 
 use serde::{Deserialize, Serialize};
 ///Categorizes the alarm (X.733 8.1.1, 3GPP TS 32.111-2 Annex A)
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub enum AlarmType {
     ///No alarm type specified
     #[serde(rename = "communicationsAlarm")]
@@ -15,8 +15,31 @@
 
 use serde::{Deserialize, Serialize};
 ///Reference to object which affected by Alarm (AlarmedObject).
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct AlarmedObjectRef {}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct AlarmedObjectRef {
+    ///The identifier of the alarmed object.
+    pub id: String,
+    ///The URI of the alarmed object.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    ///Name of the alarmed object.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    ///The actual type of the alarmed object, e.g. Resource.
+    #[serde(rename = "@referredType")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referred_type: Option<String>,
+}
+
+impl AlarmedObjectRef {
+    /// Create a reference to an alarmed object by id
+    pub fn new(id: impl Into<String>) -> AlarmedObjectRef {
+        AlarmedObjectRef {
+            id: id.into(),
+            ..Default::default()
+        }
+    }
+}
 impl std::fmt::Display for AlarmedObjectRef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
@@ -1,4 +1,6 @@
-use super::AlarmRefOrValue;
+use super::alarm_ref_or_value::matches_any;
+use super::{Alarm, AlarmRefOrValue, TASK_DONE};
+use crate::now;
 use crate::{common::entity::Entity, DateTime};
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +34,30 @@ pub struct ClearAlarm {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub state: Option<String>,
 }
+impl ClearAlarm {
+    /// Clear all alarms matching any of the alarm patterns, returning the alarms cleared.
+    /// Alarms that are already cleared are not affected.
+    pub fn apply(&mut self, alarms: &mut [Alarm]) -> Vec<AlarmRefOrValue> {
+        let time = self.alarm_cleared_time.get_or_insert_with(now).clone();
+        let cleared: Vec<AlarmRefOrValue> = alarms
+            .iter_mut()
+            .filter(|a| matches_any(&self.alarm_pattern, a))
+            .filter_map(|a| {
+                a.clear(
+                    self.clear_user_id.clone(),
+                    self.clear_system_id.clone(),
+                    time.clone(),
+                )
+                .ok()
+                .map(|_| AlarmRefOrValue::reference(a))
+            })
+            .collect();
+        self.cleared_alarm.extend(cleared.iter().cloned());
+        self.state = Some(TASK_DONE.to_string());
+        cleared
+    }
+}
+
 impl std::fmt::Display for ClearAlarm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
@@ -48,3 +74,53 @@ impl std::ops::DerefMut for ClearAlarm {
         &mut self.entity
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tmf642::{AlarmType, AlarmedObjectRef, PerceivedSeverity};
+
+    #[test]
+    fn test_clear_alarm_apply() {
+        let mut alarms = vec![
+            Alarm::new(
+                AlarmedObjectRef::new("router-01"),
+                AlarmType::CommunicationsAlarm,
+                PerceivedSeverity::Major,
+            ),
+            Alarm::new(
+                AlarmedObjectRef::new("router-02"),
+                AlarmType::CommunicationsAlarm,
+                PerceivedSeverity::Minor,
+            ),
+        ];
+        let mut pattern = AlarmRefOrValue::default();
+        pattern.alarm.alarmed_object = Some(AlarmedObjectRef::new("router-01"));
+        let mut task = ClearAlarm {
+            alarm_pattern: vec![pattern],
+            clear_user_id: Some("user".to_string()),
+            ..Default::default()
+        };
+
+        let cleared = task.apply(&mut alarms);
+
+        assert_eq!(cleared.len(), 1);
+        assert_eq!(cleared[0].alarm.id, alarms[0].id);
+        assert!(alarms[0].is_cleared());
+        assert!(!alarms[1].is_cleared());
+        assert_eq!(task.state, Some(TASK_DONE.to_string()));
+        // Clearing again affects nothing
+        assert!(task.apply(&mut alarms).is_empty());
+    }
+
+    #[test]
+    fn test_clear_alarm_empty_pattern() {
+        let mut alarms = vec![Alarm::default()];
+        let mut task = ClearAlarm {
+            alarm_pattern: vec![AlarmRefOrValue::default()],
+            ..Default::default()
+        };
+
+        assert!(task.apply(&mut alarms).is_empty());
+    }
+}
@@ -1,5 +1,8 @@
-use super::{AlarmRefOrValue, Comment};
+use super::alarm_ref_or_value::matches_any;
+use super::{Alarm, AlarmRefOrValue, Comment, TASK_DONE};
 use crate::common::entity::Entity;
+use crate::common::tmf_error::TMFError;
+use crate::now;
 use serde::{Deserialize, Serialize};
 
 /// CommentAlarm defines a comment alarm for use in TMForum Open-APIs - When used for in a schema it means that the Entity described by the schema  MUST be extended with the @type
@@ -29,6 +32,27 @@ pub struct CommentAlarm {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub state: Option<String>,
 }
+impl CommentAlarm {
+    /// Add the comment to all alarms matching any of the alarm patterns, returning the alarms commented.
+    pub fn apply(&mut self, alarms: &mut [Alarm]) -> Result<Vec<AlarmRefOrValue>, TMFError> {
+        let mut comment = self.comment.clone().ok_or(TMFError::NoDataError(
+            "CommentAlarm has no comment".to_string(),
+        ))?;
+        comment.time.get_or_insert_with(now);
+        let commented: Vec<AlarmRefOrValue> = alarms
+            .iter_mut()
+            .filter(|a| matches_any(&self.alarm_pattern, a))
+            .map(|a| {
+                a.add_comment(comment.clone());
+                AlarmRefOrValue::reference(a)
+            })
+            .collect();
+        self.commented_alarm.extend(commented.iter().cloned());
+        self.state = Some(TASK_DONE.to_string());
+        Ok(commented)
+    }
+}
+
 impl std::fmt::Display for CommentAlarm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
@@ -45,3 +69,35 @@ impl std::ops::DerefMut for CommentAlarm {
         &mut self.entity
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tmf642::{AlarmType, AlarmedObjectRef, PerceivedSeverity};
+
+    #[test]
+    fn test_comment_alarm_apply() {
+        let alarm = Alarm::new(
+            AlarmedObjectRef::new("router-01"),
+            AlarmType::EnvironmentalAlarm,
+            PerceivedSeverity::Warning,
+        );
+        let mut alarms = vec![alarm.clone()];
+        let mut task = CommentAlarm {
+            alarm_pattern: vec![AlarmRefOrValue::reference(&alarm)],
+            ..Default::default()
+        };
+
+        assert!(task.apply(&mut alarms).is_err());
+
+        task.comment = Some(Comment {
+            user_id: Some("user".to_string()),
+            ..Default::default()
+        });
+        let commented = task.apply(&mut alarms).unwrap();
+
+        assert_eq!(commented.len(), 1);
+        assert_eq!(alarms[0].comment.len(), 1);
+        assert!(alarms[0].comment[0].time.is_some());
+    }
+}
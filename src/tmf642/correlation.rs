@@ -0,0 +1,462 @@
+// Copyright [2026] [Ryan Ruckley]
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Alarm Correlation Module
+//!
+//! Groups related alarms using pluggable [`CorrelationRule`]s. Each group is expressed as a
+//! [`GroupAlarm`] task which, when applied, marks the root cause alarm and links the
+//! correlated alarms to it. Applying an [`UnGroupAlarm`](super::UnGroupAlarm) reverses this.
+//! A group can be summarised with [`AlarmSummary`] to raise a ticket or incident from it.
+//! ```
+//! use tmflib::tmf642::correlation::{CorrelationEngine, SameAlarmedObject};
+//! use tmflib::tmf642::{Alarm, AlarmType, AlarmedObjectRef, PerceivedSeverity};
+//!
+//! let mut alarms = vec![
+//!     Alarm::new(AlarmedObjectRef::new("router-01"), AlarmType::CommunicationsAlarm, PerceivedSeverity::Major),
+//!     Alarm::new(AlarmedObjectRef::new("router-01"), AlarmType::EquipmentAlarm, PerceivedSeverity::Critical),
+//! ];
+//! let engine = CorrelationEngine::new().rule(SameAlarmedObject);
+//! let groups = engine.correlate(&mut alarms);
+//! assert_eq!(groups.len(), 1);
+//! assert_eq!(alarms[0].is_root_cause, Some(true));
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, FixedOffset};
+
+use super::{Alarm, AlarmRefOrValue, GroupAlarm, PerceivedSeverity, ServiceRef};
+use crate::common::tmf_error::TMFError;
+use crate::HasId;
+
+/// Rule deciding whether two alarms are correlated
+pub trait CorrelationRule {
+    /// Returns true if the two alarms belong in the same group
+    fn correlates(&self, first: &Alarm, second: &Alarm) -> bool;
+
+    /// Optionally nominate the root cause of a group, as an index into `group`.
+    /// If no rule nominates a root cause the earliest raised alarm is used.
+    fn root_cause(&self, _group: &[&Alarm]) -> Option<usize> {
+        None
+    }
+}
+
+/// Correlate alarms raised against the same alarmed object
+pub struct SameAlarmedObject;
+
+impl CorrelationRule for SameAlarmedObject {
+    fn correlates(&self, first: &Alarm, second: &Alarm) -> bool {
+        match (&first.alarmed_object, &second.alarmed_object) {
+            (Some(a), Some(b)) => a.id == b.id,
+            _ => false,
+        }
+    }
+}
+
+/// Correlate alarms sharing a related place and raised within a time window of each other
+pub struct SamePlace {
+    window: Duration,
+}
+
+impl SamePlace {
+    /// Create a rule using a window of the given number of seconds
+    pub fn new(window_secs: i64) -> SamePlace {
+        SamePlace {
+            window: Duration::seconds(window_secs),
+        }
+    }
+}
+
+impl CorrelationRule for SamePlace {
+    fn correlates(&self, first: &Alarm, second: &Alarm) -> bool {
+        let shared = first.place.iter().any(|p| {
+            let id = p.related_place.as_ref().and_then(|rp| rp.id.clone());
+            id.is_some()
+                && second
+                    .place
+                    .iter()
+                    .any(|q| q.related_place.as_ref().and_then(|rq| rq.id.clone()) == id)
+        });
+        match (raised_time(first), raised_time(second)) {
+            (Some(a), Some(b)) => shared && (a - b).abs() <= self.window,
+            _ => false,
+        }
+    }
+}
+
+/// Correlate alarms on objects related by a parent / child topology, e.g. a card within a shelf.
+/// The alarm on the topmost object is the root cause.
+#[derive(Default)]
+pub struct Topology {
+    parents: HashMap<String, String>,
+}
+
+impl Topology {
+    /// Create an empty topology
+    pub fn new() -> Topology {
+        Topology::default()
+    }
+
+    /// Builder pattern to record that `child` is contained within `parent`
+    pub fn link(mut self, child: impl Into<String>, parent: impl Into<String>) -> Topology {
+        self.parents.insert(child.into(), parent.into());
+        self
+    }
+
+    /// Returns true if `ancestor` is above `object` in the topology
+    pub fn is_ancestor(&self, ancestor: &str, object: &str) -> bool {
+        let mut current = object;
+        // Bound the walk to guard against loops in the supplied topology
+        for _ in 0..=self.parents.len() {
+            match self.parents.get(current) {
+                Some(p) if p == ancestor => return true,
+                Some(p) => current = p,
+                None => return false,
+            }
+        }
+        false
+    }
+}
+
+fn object_id(alarm: &Alarm) -> Option<&str> {
+    alarm.alarmed_object.as_ref().map(|o| o.id.as_str())
+}
+
+impl CorrelationRule for Topology {
+    fn correlates(&self, first: &Alarm, second: &Alarm) -> bool {
+        match (object_id(first), object_id(second)) {
+            (Some(a), Some(b)) => self.is_ancestor(a, b) || self.is_ancestor(b, a),
+            _ => false,
+        }
+    }
+
+    fn root_cause(&self, group: &[&Alarm]) -> Option<usize> {
+        group.iter().position(|candidate| {
+            object_id(candidate).is_some_and(|c| {
+                !group
+                    .iter()
+                    .filter_map(|other| object_id(other))
+                    .any(|o| self.is_ancestor(o, c))
+            })
+        })
+    }
+}
+
+fn raised_time(alarm: &Alarm) -> Option<DateTime<FixedOffset>> {
+    alarm
+        .alarm_raised_time
+        .as_ref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+}
+
+/// Alarm correlation engine
+#[derive(Default)]
+pub struct CorrelationEngine {
+    rules: Vec<Box<dyn CorrelationRule>>,
+}
+
+impl CorrelationEngine {
+    /// Create an engine with no rules
+    pub fn new() -> CorrelationEngine {
+        CorrelationEngine::default()
+    }
+
+    /// Builder pattern to add a correlation rule
+    pub fn rule(mut self, rule: impl CorrelationRule + 'static) -> CorrelationEngine {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Correlate a batch of alarms. Alarms correlated by any rule, directly or through other
+    /// alarms, form a group. Each group is applied to the alarms and returned as a [`GroupAlarm`].
+    /// Cleared alarms and alarms without an id are ignored.
+    pub fn correlate(&self, alarms: &mut [Alarm]) -> Vec<GroupAlarm> {
+        let candidates: Vec<usize> = (0..alarms.len())
+            .filter(|i| !alarms[*i].is_cleared() && alarms[*i].id.is_some())
+            .collect();
+        // Union-find over candidate positions
+        let mut leader: Vec<usize> = (0..candidates.len()).collect();
+        fn find(leader: &mut [usize], i: usize) -> usize {
+            let mut root = i;
+            while leader[root] != root {
+                root = leader[root];
+            }
+            leader[i] = root;
+            root
+        }
+        for (x, &i) in candidates.iter().enumerate() {
+            for (y, &j) in candidates.iter().enumerate().skip(x + 1) {
+                if self
+                    .rules
+                    .iter()
+                    .any(|r| r.correlates(&alarms[i], &alarms[j]))
+                {
+                    let (a, b) = (find(&mut leader, x), find(&mut leader, y));
+                    leader[b] = a;
+                }
+            }
+        }
+        let mut groups: Vec<Vec<usize>> = vec![];
+        let mut group_of: HashMap<usize, usize> = HashMap::new();
+        for (x, &i) in candidates.iter().enumerate() {
+            let root = find(&mut leader, x);
+            match group_of.get(&root) {
+                Some(g) => groups[*g].push(i),
+                None => {
+                    group_of.insert(root, groups.len());
+                    groups.push(vec![i]);
+                }
+            }
+        }
+
+        let mut results = vec![];
+        for group in groups.into_iter().filter(|g| g.len() > 1) {
+            let members: Vec<&Alarm> = group.iter().map(|i| &alarms[*i]).collect();
+            let root = self.root_cause(&members);
+            let mut task = GroupAlarm {
+                parent_alarm: Some(AlarmRefOrValue::reference(members[root])),
+                correlated_alarm: members
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != root)
+                    .map(|(_, a)| AlarmRefOrValue::reference(a))
+                    .collect(),
+                ..Default::default()
+            };
+            // The parent was taken from the batch so it is always found
+            if task.apply(alarms).is_ok() {
+                results.push(task);
+            }
+        }
+        results
+    }
+
+    fn root_cause(&self, group: &[&Alarm]) -> usize {
+        self.rules
+            .iter()
+            .find_map(|r| r.root_cause(group))
+            .unwrap_or_else(|| {
+                // Earliest raised alarm, alarms without a raised time sort last
+                (0..group.len())
+                    .min_by_key(|i| raised_time(group[*i]).map_or((1, None), |t| (0, Some(t))))
+                    .unwrap_or_default()
+            })
+    }
+}
+
+/// Summary of a group of correlated alarms, used to raise tickets and incidents from the group
+#[derive(Clone, Debug)]
+pub struct AlarmSummary<'a> {
+    /// Root cause alarm, or the first alarm if none is flagged
+    pub root: &'a Alarm,
+    /// Highest severity across the group
+    pub severity: PerceivedSeverity,
+    /// Name from the root cause alarm type and alarmed object, e.g. `EquipmentAlarm on router-01`
+    pub name: String,
+    /// Alarm details, specific problem or probable cause of the root cause alarm
+    pub details: Option<String>,
+    /// Affected services across the group, each service listed once. Services without an id are skipped.
+    pub services: Vec<&'a ServiceRef>,
+}
+
+impl<'a> AlarmSummary<'a> {
+    /// Summarise a group of correlated alarms, returning an error if the group is empty
+    pub fn new(alarms: &'a [Alarm]) -> Result<AlarmSummary<'a>, TMFError> {
+        let root = alarms
+            .iter()
+            .find(|a| a.is_root_cause == Some(true))
+            .or(alarms.first())
+            .ok_or(TMFError::NoDataError(String::from("alarms")))?;
+        let severity = alarms
+            .iter()
+            .filter_map(|a| a.perceived_severity.clone())
+            .max()
+            .unwrap_or(PerceivedSeverity::Indeterminate);
+        let object = root
+            .alarmed_object
+            .as_ref()
+            .map(|o| o.name.clone().unwrap_or(o.id.clone()))
+            .unwrap_or_default();
+        let name = match &root.alarm_type {
+            Some(t) => format!("{t:?} on {object}"),
+            None => format!("Alarm on {object}"),
+        };
+        let mut services: Vec<&ServiceRef> = vec![];
+        for service in alarms.iter().flat_map(|a| a.affected_service.iter()) {
+            if service.id.is_some() && !services.iter().any(|s| s.id == service.id) {
+                services.push(service);
+            }
+        }
+        Ok(AlarmSummary {
+            root,
+            severity,
+            name,
+            details: root
+                .alarm_details
+                .clone()
+                .or(root.specific_problem.clone())
+                .or(root.probable_cause.as_ref().map(|c| c.name())),
+            services,
+        })
+    }
+
+    /// Returns true if `alarm` is the root cause of the group
+    pub fn is_root(&self, alarm: &Alarm) -> bool {
+        alarm.get_id() == self.root.get_id()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::entity::Entity;
+    use crate::tmf642::{
+        AlarmType, AlarmedObjectRef, PerceivedSeverity, Place, RelatedPlace, UnGroupAlarm,
+    };
+    use crate::HasId;
+
+    fn alarm(object: &str, raised: &str) -> Alarm {
+        let mut alarm = Alarm::new(
+            AlarmedObjectRef::new(object),
+            AlarmType::CommunicationsAlarm,
+            PerceivedSeverity::Major,
+        );
+        alarm.alarm_raised_time = Some(raised.to_string());
+        alarm
+    }
+
+    fn place(alarm: &mut Alarm, id: &str) {
+        alarm.place.push(RelatedPlace {
+            related_place: Some(Place {
+                entity: Entity {
+                    id: Some(id.to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_correlate_same_object() {
+        let mut alarms = vec![
+            alarm("router-01", "2024-01-01T00:00:10+00:00"),
+            alarm("router-01", "2024-01-01T00:00:00+00:00"),
+            alarm("router-02", "2024-01-01T00:00:00+00:00"),
+        ];
+        let engine = CorrelationEngine::new().rule(SameAlarmedObject);
+
+        let groups = engine.correlate(&mut alarms);
+
+        assert_eq!(groups.len(), 1);
+        // Earliest raised alarm is the root cause
+        assert_eq!(alarms[1].is_root_cause, Some(true));
+        assert_eq!(alarms[1].correlated_alarm[0].id, alarms[0].get_id());
+        assert_eq!(alarms[0].is_root_cause, Some(false));
+        assert_eq!(alarms[0].parent_alarm[0].id, alarms[1].get_id());
+        assert!(alarms[2].is_root_cause.is_none());
+    }
+
+    #[test]
+    fn test_correlate_same_place_window() {
+        let mut alarms = vec![
+            alarm("router-01", "2024-01-01T00:00:00+00:00"),
+            alarm("router-02", "2024-01-01T00:00:30+00:00"),
+            alarm("router-03", "2024-01-01T01:00:00+00:00"),
+        ];
+        alarms.iter_mut().for_each(|a| place(a, "exchange-01"));
+        let engine = CorrelationEngine::new().rule(SamePlace::new(60));
+
+        let groups = engine.correlate(&mut alarms);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].grouped_alarm.len(), 1);
+        assert!(alarms[2].is_root_cause.is_none());
+    }
+
+    #[test]
+    fn test_correlate_topology() {
+        let mut alarms = vec![
+            alarm("card-01", "2024-01-01T00:00:00+00:00"),
+            alarm("port-01", "2024-01-01T00:00:00+00:00"),
+            alarm("shelf-01", "2024-01-01T00:00:05+00:00"),
+        ];
+        let topology = Topology::new()
+            .link("port-01", "card-01")
+            .link("card-01", "shelf-01");
+        let engine = CorrelationEngine::new().rule(topology);
+
+        engine.correlate(&mut alarms);
+
+        assert_eq!(alarms[2].is_root_cause, Some(true));
+        assert_eq!(alarms[2].correlated_alarm.len(), 2);
+    }
+
+    #[test]
+    fn test_ungroup() {
+        let mut alarms = vec![
+            alarm("router-01", "2024-01-01T00:00:00+00:00"),
+            alarm("router-01", "2024-01-01T00:00:10+00:00"),
+        ];
+        let engine = CorrelationEngine::new().rule(SameAlarmedObject);
+        let group = engine.correlate(&mut alarms).remove(0);
+        let mut ungroup = UnGroupAlarm {
+            parent_alarm: group.parent_alarm.clone(),
+            correlated_alarm: group.correlated_alarm.clone(),
+            ..Default::default()
+        };
+
+        let ungrouped = ungroup.apply(&mut alarms).unwrap();
+
+        assert_eq!(ungrouped.len(), 1);
+        assert!(alarms[0].correlated_alarm.is_empty());
+        assert!(alarms[0].is_root_cause.is_none());
+        assert!(alarms[1].parent_alarm.is_empty());
+        assert!(alarms[1].is_root_cause.is_none());
+    }
+
+    #[test]
+    fn test_group_missing_parent() {
+        let mut alarms = vec![alarm("router-01", "2024-01-01T00:00:00+00:00")];
+        let mut group = GroupAlarm::default();
+
+        assert!(group.apply(&mut alarms).is_err());
+    }
+
+    #[test]
+    fn test_alarm_summary() {
+        let mut alarms = vec![
+            alarm("router-01", "2024-01-01T00:00:00+00:00"),
+            alarm("router-02", "2024-01-01T00:00:10+00:00"),
+        ];
+        alarms[1].is_root_cause = Some(true);
+        alarms[1].alarm_type = Some(AlarmType::EquipmentAlarm);
+        alarms[0].perceived_severity = Some(PerceivedSeverity::Critical);
+        let service = ServiceRef {
+            id: Some("svc-01".to_string()),
+            ..Default::default()
+        };
+        alarms[0].affected_service.push(service.clone());
+        alarms[1].affected_service.push(service);
+        alarms[1].affected_service.push(ServiceRef::default());
+
+        let summary = AlarmSummary::new(&alarms).unwrap();
+
+        assert!(summary.is_root(&alarms[1]));
+        assert_eq!(summary.name, "EquipmentAlarm on router-02".to_string());
+        assert_eq!(summary.severity, PerceivedSeverity::Critical);
+        assert_eq!(summary.services.len(), 1);
+        assert!(AlarmSummary::new(&[]).is_err());
+    }
+}
@@ -1,4 +1,7 @@
-use super::AlarmRefOrValue;
+use super::alarm_ref_or_value::matches_any;
+use super::{Alarm, AlarmRef, AlarmRefOrValue, TASK_DONE};
+use crate::common::tmf_error::TMFError;
+use crate::now;
 use crate::{common::entity::Entity, DateTime};
 use serde::{Deserialize, Serialize};
 
@@ -38,6 +41,48 @@ pub struct GroupAlarm {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub state: Option<String>,
 }
+impl GroupAlarm {
+    /// Group all alarms matching the correlated alarm patterns under the alarm matching the
+    /// parent alarm pattern, which is marked as the root cause. Returns the alarms grouped.
+    pub fn apply(&mut self, alarms: &mut [Alarm]) -> Result<Vec<AlarmRefOrValue>, TMFError> {
+        let pattern = self.parent_alarm.as_ref().ok_or(TMFError::NoDataError(
+            "GroupAlarm has no parent alarm".to_string(),
+        ))?;
+        let parent_idx =
+            alarms
+                .iter()
+                .position(|a| pattern.matches(a))
+                .ok_or(TMFError::InvalidReference(
+                    "Parent alarm not found".to_string(),
+                ))?;
+        let time = self.alarm_changed_time.get_or_insert_with(now).clone();
+        let parent_ref = AlarmRef::from(&alarms[parent_idx]);
+        let mut grouped = vec![];
+        for (idx, child) in alarms.iter_mut().enumerate() {
+            if idx == parent_idx || !matches_any(&self.correlated_alarm, child) {
+                continue;
+            }
+            if !child.parent_alarm.contains(&parent_ref) {
+                child.parent_alarm.push(parent_ref.clone());
+            }
+            child.is_root_cause = Some(false);
+            child.alarm_changed_time = Some(time.clone());
+            grouped.push(AlarmRefOrValue::reference(child));
+        }
+        let parent = &mut alarms[parent_idx];
+        for child in grouped.iter().map(|g| AlarmRef::from(&g.alarm)) {
+            if !parent.correlated_alarm.iter().any(|c| c.id == child.id) {
+                parent.correlated_alarm.push(child);
+            }
+        }
+        parent.is_root_cause = Some(true);
+        parent.alarm_changed_time = Some(time);
+        self.grouped_alarm.extend(grouped.iter().cloned());
+        self.state = Some(TASK_DONE.to_string());
+        Ok(grouped)
+    }
+}
+
 impl std::fmt::Display for GroupAlarm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
@@ -14,11 +14,13 @@
 //! Module for TMF642 - Alarm Management
 
 pub use ack_alarm::*;
+pub use ack_state_type::*;
 pub use addressable::*;
 pub use alarm::*;
 pub use alarm_ref::*;
 pub use alarm_ref_mvo::*;
 pub use alarm_ref_or_value::*;
+pub use alarm_state_type::*;
 pub use alarm_type::*;
 pub use alarmed_object_ref::*;
 pub use alarmed_object_ref_mvo::*;
@@ -41,6 +43,7 @@ pub use perceived_severity::*;
 pub use place::*;
 pub use place_fvo::*;
 pub use place_mvo::*;
+pub use probable_cause::*;
 pub use related_party_ref_or_party_role_ref::*;
 pub use related_place::*;
 pub use related_place_fvo::*;
@@ -52,11 +55,14 @@ pub use threshold_ref_mvo::*;
 pub use un_ack_alarm::*;
 pub use un_group_alarm::*;
 mod ack_alarm;
+pub mod ack_state_type;
 mod addressable;
 pub mod alarm;
 mod alarm_ref;
 pub mod alarm_ref_mvo;
 mod alarm_ref_or_value;
+pub mod alarm_state_type;
+pub mod alarm_store;
 pub mod alarm_type;
 pub mod alarmed_object_ref;
 mod alarmed_object_ref_mvo;
@@ -66,6 +72,7 @@ mod clear_alarm;
 mod comment;
 mod comment_alarm;
 mod comment_mvo;
+pub mod correlation;
 mod crossed_threshold_information;
 mod crossed_threshold_information_mvo;
 mod extensible;
@@ -79,6 +86,7 @@ pub mod perceived_severity;
 pub mod place;
 mod place_fvo;
 mod place_mvo;
+pub mod probable_cause;
 mod related_party_ref_or_party_role_ref;
 mod related_place;
 mod related_place_fvo;
@@ -91,3 +99,5 @@ mod un_ack_alarm;
 mod un_group_alarm;
 
 const MOD_PATH: &str = "alarmManagement/v5";
+/// Task state set once an alarm operation has been applied
+const TASK_DONE: &str = "done";
@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 /**Lists the possible severities that can be allocated to an Alarm. The values are consistent with ITU-T Recommendation X.733.
 Once an alarm has been cleared, its perceived severity is set to 'cleared' and can no longer be set.*/
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub enum PerceivedSeverity {
     /// Critical Severity
     #[serde(rename = "critical")]
@@ -24,3 +24,52 @@ pub enum PerceivedSeverity {
     #[serde(rename = "cleared")]
     Cleared,
 }
+
+impl PerceivedSeverity {
+    /// Rank of this severity, cleared being the lowest and critical the highest
+    fn rank(&self) -> u8 {
+        match self {
+            PerceivedSeverity::Cleared => 0,
+            PerceivedSeverity::Indeterminate => 1,
+            PerceivedSeverity::Warning => 2,
+            PerceivedSeverity::Minor => 3,
+            PerceivedSeverity::Major => 4,
+            PerceivedSeverity::Critical => 5,
+        }
+    }
+}
+
+impl PartialOrd for PerceivedSeverity {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Severities are ordered from cleared (lowest) through to critical (highest)
+impl Ord for PerceivedSeverity {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_severity_order() {
+        let mut severities = [
+            PerceivedSeverity::Minor,
+            PerceivedSeverity::Critical,
+            PerceivedSeverity::Cleared,
+            PerceivedSeverity::Warning,
+            PerceivedSeverity::Major,
+            PerceivedSeverity::Indeterminate,
+        ];
+        severities.sort();
+
+        assert_eq!(severities.first(), Some(&PerceivedSeverity::Cleared));
+        assert_eq!(severities.last(), Some(&PerceivedSeverity::Critical));
+        assert!(PerceivedSeverity::Major >= PerceivedSeverity::Minor);
+    }
+}
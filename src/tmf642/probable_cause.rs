@@ -0,0 +1,556 @@
+// Copyright [2026] [Ryan Ruckley]
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Probable cause of an alarm as defined by ITU-T X.733 / X.721
+//!
+//! Probable causes are grouped by the [`AlarmType`] they may be raised with. Vendor specific
+//! causes not covered by X.733 are kept as [`ProbableCause::Other`] and are valid for any alarm type.
+//! Names are matched ignoring case and punctuation, so `LOSS_OF_SIGNAL` is [`ProbableCause::LossOfSignal`].
+
+use serde::{Deserialize, Serialize};
+
+use super::AlarmType;
+use crate::common::tmf_error::TMFError;
+
+/// Probable cause of an alarm (X.733 8.1.2.10)
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum ProbableCause {
+    /// Loss of signal
+    LossOfSignal,
+    /// Framing error
+    FramingError,
+    /// Local node transmission error
+    LocalNodeTransmissionError,
+    /// Remote node transmission error
+    RemoteNodeTransmissionError,
+    /// Call establishment error
+    CallEstablishmentError,
+    /// Degraded signal
+    DegradedSignal,
+    /// Communications subsystem failure
+    CommunicationsSubsystemFailure,
+    /// Communications protocol error
+    CommunicationsProtocolError,
+    /// LAN error
+    LanError,
+    /// DTE/DCE interface error
+    DteDceInterfaceError,
+    /// Response time excessive
+    ResponseTimeExcessive,
+    /// Queue size exceeded
+    QueueSizeExceeded,
+    /// Bandwidth reduced
+    BandwidthReduced,
+    /// Retransmission rate excessive
+    RetransmissionRateExcessive,
+    /// Threshold crossed
+    ThresholdCrossed,
+    /// Performance degraded
+    PerformanceDegraded,
+    /// Congestion
+    Congestion,
+    /// Resource at or nearing capacity
+    ResourceAtOrNearingCapacity,
+    /// Storage capacity problem
+    StorageCapacityProblem,
+    /// Version mismatch
+    VersionMismatch,
+    /// Corrupt data
+    CorruptData,
+    /// CPU cycles limit exceeded
+    CpuCyclesLimitExceeded,
+    /// Software error
+    SoftwareError,
+    /// Software program error
+    SoftwareProgramError,
+    /// Software program abnormally terminated
+    SoftwareProgramAbnormallyTerminated,
+    /// File error
+    FileError,
+    /// Out of memory
+    OutOfMemory,
+    /// Underlying resource unavailable
+    UnderlyingResourceUnavailable,
+    /// Application subsystem failure
+    ApplicationSubsystemFailure,
+    /// Configuration or customization error
+    ConfigurationOrCustomizationError,
+    /// Power problem
+    PowerProblem,
+    /// Timing problem
+    TimingProblem,
+    /// Processor problem
+    ProcessorProblem,
+    /// Data set or modem error
+    DataSetOrModemError,
+    /// Multiplexer problem
+    MultiplexerProblem,
+    /// Receiver failure
+    ReceiverFailure,
+    /// Transmitter failure
+    TransmitterFailure,
+    /// Output device error
+    OutputDeviceError,
+    /// Input device error
+    InputDeviceError,
+    /// Input output device error
+    InputOutputDeviceError,
+    /// Equipment malfunction
+    EquipmentMalfunction,
+    /// Adapter error
+    AdapterError,
+    /// Temperature unacceptable
+    TemperatureUnacceptable,
+    /// Humidity unacceptable
+    HumidityUnacceptable,
+    /// Heating or ventilation or cooling system problem
+    HeatingOrVentilationOrCoolingSystemProblem,
+    /// Fire detected
+    FireDetected,
+    /// Flood detected
+    FloodDetected,
+    /// Toxic leak detected
+    ToxicLeakDetected,
+    /// Leak detected
+    LeakDetected,
+    /// Pressure unacceptable
+    PressureUnacceptable,
+    /// Excessive vibration
+    ExcessiveVibration,
+    /// Material supply exhausted
+    MaterialSupplyExhausted,
+    /// Pump failure
+    PumpFailure,
+    /// Enclosure door open
+    EnclosureDoorOpen,
+    /// Vendor specific probable cause
+    Other(String),
+}
+
+/// Known probable causes with their X.733 name and the alarm type they belong to
+const CAUSES: &[(ProbableCause, &str, AlarmType)] = &[
+    (
+        ProbableCause::LossOfSignal,
+        "lossOfSignal",
+        AlarmType::CommunicationsAlarm,
+    ),
+    (
+        ProbableCause::FramingError,
+        "framingError",
+        AlarmType::CommunicationsAlarm,
+    ),
+    (
+        ProbableCause::LocalNodeTransmissionError,
+        "localNodeTransmissionError",
+        AlarmType::CommunicationsAlarm,
+    ),
+    (
+        ProbableCause::RemoteNodeTransmissionError,
+        "remoteNodeTransmissionError",
+        AlarmType::CommunicationsAlarm,
+    ),
+    (
+        ProbableCause::CallEstablishmentError,
+        "callEstablishmentError",
+        AlarmType::CommunicationsAlarm,
+    ),
+    (
+        ProbableCause::DegradedSignal,
+        "degradedSignal",
+        AlarmType::CommunicationsAlarm,
+    ),
+    (
+        ProbableCause::CommunicationsSubsystemFailure,
+        "communicationsSubsystemFailure",
+        AlarmType::CommunicationsAlarm,
+    ),
+    (
+        ProbableCause::CommunicationsProtocolError,
+        "communicationsProtocolError",
+        AlarmType::CommunicationsAlarm,
+    ),
+    (
+        ProbableCause::LanError,
+        "lanError",
+        AlarmType::CommunicationsAlarm,
+    ),
+    (
+        ProbableCause::DteDceInterfaceError,
+        "dteDceInterfaceError",
+        AlarmType::CommunicationsAlarm,
+    ),
+    (
+        ProbableCause::ResponseTimeExcessive,
+        "responseTimeExcessive",
+        AlarmType::QualityOfServiceAlarm,
+    ),
+    (
+        ProbableCause::QueueSizeExceeded,
+        "queueSizeExceeded",
+        AlarmType::QualityOfServiceAlarm,
+    ),
+    (
+        ProbableCause::BandwidthReduced,
+        "bandwidthReduced",
+        AlarmType::QualityOfServiceAlarm,
+    ),
+    (
+        ProbableCause::RetransmissionRateExcessive,
+        "retransmissionRateExcessive",
+        AlarmType::QualityOfServiceAlarm,
+    ),
+    (
+        ProbableCause::ThresholdCrossed,
+        "thresholdCrossed",
+        AlarmType::QualityOfServiceAlarm,
+    ),
+    (
+        ProbableCause::PerformanceDegraded,
+        "performanceDegraded",
+        AlarmType::QualityOfServiceAlarm,
+    ),
+    (
+        ProbableCause::Congestion,
+        "congestion",
+        AlarmType::QualityOfServiceAlarm,
+    ),
+    (
+        ProbableCause::ResourceAtOrNearingCapacity,
+        "resourceAtOrNearingCapacity",
+        AlarmType::QualityOfServiceAlarm,
+    ),
+    (
+        ProbableCause::StorageCapacityProblem,
+        "storageCapacityProblem",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::VersionMismatch,
+        "versionMismatch",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::CorruptData,
+        "corruptData",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::CpuCyclesLimitExceeded,
+        "cpuCyclesLimitExceeded",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::SoftwareError,
+        "softwareError",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::SoftwareProgramError,
+        "softwareProgramError",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::SoftwareProgramAbnormallyTerminated,
+        "softwareProgramAbnormallyTerminated",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::FileError,
+        "fileError",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::OutOfMemory,
+        "outOfMemory",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::UnderlyingResourceUnavailable,
+        "underlyingResourceUnavailable",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::ApplicationSubsystemFailure,
+        "applicationSubsystemFailure",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::ConfigurationOrCustomizationError,
+        "configurationOrCustomizationError",
+        AlarmType::ProcessingErrorAlarm,
+    ),
+    (
+        ProbableCause::PowerProblem,
+        "powerProblem",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::TimingProblem,
+        "timingProblem",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::ProcessorProblem,
+        "processorProblem",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::DataSetOrModemError,
+        "dataSetOrModemError",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::MultiplexerProblem,
+        "multiplexerProblem",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::ReceiverFailure,
+        "receiverFailure",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::TransmitterFailure,
+        "transmitterFailure",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::OutputDeviceError,
+        "outputDeviceError",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::InputDeviceError,
+        "inputDeviceError",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::InputOutputDeviceError,
+        "inputOutputDeviceError",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::EquipmentMalfunction,
+        "equipmentMalfunction",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::AdapterError,
+        "adapterError",
+        AlarmType::EquipmentAlarm,
+    ),
+    (
+        ProbableCause::TemperatureUnacceptable,
+        "temperatureUnacceptable",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::HumidityUnacceptable,
+        "humidityUnacceptable",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::HeatingOrVentilationOrCoolingSystemProblem,
+        "heatingOrVentilationOrCoolingSystemProblem",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::FireDetected,
+        "fireDetected",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::FloodDetected,
+        "floodDetected",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::ToxicLeakDetected,
+        "toxicLeakDetected",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::LeakDetected,
+        "leakDetected",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::PressureUnacceptable,
+        "pressureUnacceptable",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::ExcessiveVibration,
+        "excessiveVibration",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::MaterialSupplyExhausted,
+        "materialSupplyExhausted",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::PumpFailure,
+        "pumpFailure",
+        AlarmType::EnvironmentalAlarm,
+    ),
+    (
+        ProbableCause::EnclosureDoorOpen,
+        "enclosureDoorOpen",
+        AlarmType::EnvironmentalAlarm,
+    ),
+];
+
+/// Normalise a cause for comparison, e.g. "DTE-DCEInterfaceError" matches "dteDceInterfaceError"
+fn normalise(cause: &str) -> String {
+    cause
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+impl ProbableCause {
+    /// X.733 name of this probable cause
+    pub fn name(&self) -> String {
+        match self {
+            ProbableCause::Other(cause) => cause.clone(),
+            known => CAUSES
+                .iter()
+                .find(|(c, _, _)| c == known)
+                .map(|(_, name, _)| name.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Known cause named by a vendor specific cause, e.g. `Other("LOSS_OF_SIGNAL")` is
+    /// [`ProbableCause::LossOfSignal`]. Known and unmatched causes are returned unchanged.
+    pub fn normalised(&self) -> ProbableCause {
+        match self {
+            ProbableCause::Other(cause) => ProbableCause::from(cause.as_str()),
+            known => known.clone(),
+        }
+    }
+
+    /// Alarm type this probable cause belongs to, None for vendor specific causes
+    pub fn alarm_type(&self) -> Option<AlarmType> {
+        let cause = self.normalised();
+        CAUSES
+            .iter()
+            .find(|(c, _, _)| *c == cause)
+            .map(|(_, _, t)| t.clone())
+    }
+
+    /// Returns true if this probable cause may be raised with the given alarm type.
+    /// Vendor specific causes are valid for all alarm types.
+    pub fn is_valid_for(&self, alarm_type: &AlarmType) -> bool {
+        match self.alarm_type() {
+            Some(t) => &t == alarm_type,
+            None => true,
+        }
+    }
+
+    /// Validate this probable cause against an alarm type
+    pub fn validate(&self, alarm_type: &AlarmType) -> Result<(), TMFError> {
+        match self.is_valid_for(alarm_type) {
+            true => Ok(()),
+            false => Err(TMFError::ProbableCauseError(format!(
+                "Probable cause {} is not valid for {:?}",
+                self, alarm_type
+            ))),
+        }
+    }
+}
+
+impl From<&str> for ProbableCause {
+    fn from(value: &str) -> Self {
+        let key = normalise(value);
+        CAUSES
+            .iter()
+            .find(|(_, name, _)| normalise(name) == key)
+            .map(|(c, _, _)| c.clone())
+            .unwrap_or(ProbableCause::Other(value.to_string()))
+    }
+}
+
+impl From<String> for ProbableCause {
+    fn from(value: String) -> Self {
+        ProbableCause::from(value.as_str())
+    }
+}
+
+impl From<ProbableCause> for String {
+    fn from(value: ProbableCause) -> Self {
+        value.name()
+    }
+}
+
+impl std::fmt::Display for ProbableCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cause_from_str() {
+        assert_eq!(
+            ProbableCause::from("lossOfSignal"),
+            ProbableCause::LossOfSignal
+        );
+        assert_eq!(
+            ProbableCause::from("DTE-DCEInterfaceError"),
+            ProbableCause::DteDceInterfaceError
+        );
+        assert_eq!(
+            ProbableCause::from("vendorFault42"),
+            ProbableCause::Other("vendorFault42".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cause_serde() {
+        let json = serde_json::to_string(&ProbableCause::PowerProblem).unwrap();
+        assert_eq!(json, "\"powerProblem\"");
+
+        let cause: ProbableCause = serde_json::from_str("\"vendorFault42\"").unwrap();
+        assert_eq!(cause.name(), "vendorFault42");
+    }
+
+    #[test]
+    fn test_cause_normalised() {
+        for name in ["LOSS_OF_SIGNAL", "LossOfSignal", "loss-of-signal"] {
+            let cause: ProbableCause = serde_json::from_str(&format!("\"{name}\"")).unwrap();
+            assert_eq!(cause, ProbableCause::LossOfSignal);
+            assert!(cause.validate(&AlarmType::CommunicationsAlarm).is_ok());
+        }
+
+        let other = ProbableCause::Other("LOSS_OF_SIGNAL".to_string());
+        assert_eq!(other.normalised(), ProbableCause::LossOfSignal);
+        assert!(other.validate(&AlarmType::CommunicationsAlarm).is_ok());
+        assert!(other.validate(&AlarmType::EquipmentAlarm).is_err());
+    }
+
+    #[test]
+    fn test_cause_validate() {
+        assert!(ProbableCause::PowerProblem.is_valid_for(&AlarmType::EquipmentAlarm));
+        assert!(ProbableCause::PowerProblem
+            .validate(&AlarmType::CommunicationsAlarm)
+            .is_err());
+        assert!(ProbableCause::from("vendorFault42").is_valid_for(&AlarmType::SecurityService));
+    }
+}
@@ -1,7 +1,21 @@
 use serde::{Deserialize, Serialize};
 ///Service reference, for when Service is used by other entities.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct ServiceRef {}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ServiceRef {
+    ///The identifier of the service.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    ///The URI of the service.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    ///Name of the service.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    ///The actual type of the service.
+    #[serde(rename = "@referredType")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referred_type: Option<String>,
+}
 impl std::fmt::Display for ServiceRef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
@@ -2,9 +2,36 @@ use serde::{Deserialize, Serialize};
 
 /// Reference to a threshold for use in TMForum Open-APIs - When used for in a schema it means that the Entity described by the schema  MUST be extended with the @type
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct ThresholdRef {}
+pub struct ThresholdRef {
+    ///The identifier of the threshold.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    ///The URI of the threshold.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    ///Name of the threshold.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    ///The actual type of the threshold.
+    #[serde(rename = "@referredType")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referred_type: Option<String>,
+}
 impl std::fmt::Display for ThresholdRef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_threshold_ref_empty() {
+        let threshold: ThresholdRef = serde_json::from_str("{}").unwrap();
+
+        assert!(threshold.id.is_none());
+        assert_eq!(serde_json::to_string(&threshold).unwrap(), "{}");
+    }
+}
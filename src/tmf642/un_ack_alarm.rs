@@ -1,5 +1,7 @@
-use super::AlarmRefOrValue;
+use super::alarm_ref_or_value::matches_any;
+use super::{Alarm, AlarmRefOrValue, TASK_DONE};
 use crate::common::entity::Entity;
+use crate::now;
 use crate::DateTime;
 use serde::{Deserialize, Serialize};
 
@@ -39,6 +41,29 @@ pub struct UnAckAlarm {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub un_acked_alarm: Vec<AlarmRefOrValue>,
 }
+impl UnAckAlarm {
+    /// Remove the acknowledgement from all alarms matching any of the alarm patterns,
+    /// returning the alarms unacknowledged. Alarms that are not acknowledged are not affected.
+    pub fn apply(&mut self, alarms: &mut [Alarm]) -> Vec<AlarmRefOrValue> {
+        let time = self.ack_time.get_or_insert_with(now).clone();
+        let unacked: Vec<AlarmRefOrValue> = alarms
+            .iter_mut()
+            .filter(|a| matches_any(&self.alarm_pattern, a))
+            .filter_map(|a| {
+                a.unacknowledge(
+                    self.ack_user_id.clone(),
+                    self.ack_system_id.clone(),
+                    time.clone(),
+                )
+                .then(|| AlarmRefOrValue::reference(a))
+            })
+            .collect();
+        self.un_acked_alarm.extend(unacked.iter().cloned());
+        self.state = Some(TASK_DONE.to_string());
+        unacked
+    }
+}
+
 impl std::fmt::Display for UnAckAlarm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
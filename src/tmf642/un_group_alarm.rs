@@ -1,6 +1,9 @@
-use super::AlarmRefOrValue;
+use super::alarm_ref_or_value::matches_any;
+use super::{Alarm, AlarmRefOrValue, TASK_DONE};
 use crate::common::entity::Entity;
-use crate::DateTime;
+use crate::common::tmf_error::TMFError;
+use crate::now;
+use crate::{DateTime, HasId};
 use serde::{Deserialize, Serialize};
 
 ///UnGroupAlarm defines an ungroup alarm operation task for use in TMForum Open-APIs - When used for in a schema it means that the Entity described by the schema  MUST be extended with the @type
@@ -39,6 +42,52 @@ pub struct UnGroupAlarm {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub un_grouped_alarm: Vec<AlarmRefOrValue>,
 }
+impl UnGroupAlarm {
+    /// Remove all alarms matching the correlated alarm patterns from the group of the alarm
+    /// matching the parent alarm pattern. Returns the alarms ungrouped.
+    /// The parent is no longer marked as a root cause once it has no correlated alarms left.
+    pub fn apply(&mut self, alarms: &mut [Alarm]) -> Result<Vec<AlarmRefOrValue>, TMFError> {
+        let pattern = self.parent_alarm.as_ref().ok_or(TMFError::NoDataError(
+            "UnGroupAlarm has no parent alarm".to_string(),
+        ))?;
+        let parent_idx =
+            alarms
+                .iter()
+                .position(|a| pattern.matches(a))
+                .ok_or(TMFError::InvalidReference(
+                    "Parent alarm not found".to_string(),
+                ))?;
+        let time = self.alarm_changed_time.get_or_insert_with(now).clone();
+        let parent_id = alarms[parent_idx].get_id();
+        let mut ungrouped = vec![];
+        for (idx, child) in alarms.iter_mut().enumerate() {
+            if idx == parent_idx
+                || !matches_any(&self.correlated_alarm, child)
+                || !child.parent_alarm.iter().any(|p| p.id == parent_id)
+            {
+                continue;
+            }
+            child.parent_alarm.retain(|p| p.id != parent_id);
+            if child.parent_alarm.is_empty() && child.correlated_alarm.is_empty() {
+                child.is_root_cause = None;
+            }
+            child.alarm_changed_time = Some(time.clone());
+            ungrouped.push(AlarmRefOrValue::reference(child));
+        }
+        let parent = &mut alarms[parent_idx];
+        parent
+            .correlated_alarm
+            .retain(|c| !ungrouped.iter().any(|u| u.alarm.id.as_ref() == Some(&c.id)));
+        if parent.correlated_alarm.is_empty() {
+            parent.is_root_cause = None;
+        }
+        parent.alarm_changed_time = Some(time);
+        self.un_grouped_alarm.extend(ungrouped.iter().cloned());
+        self.state = Some(TASK_DONE.to_string());
+        Ok(ungrouped)
+    }
+}
+
 impl std::fmt::Display for UnGroupAlarm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "{}", serde_json::to_string(self).unwrap())
@@ -39,6 +39,34 @@ pub enum QuoteStateType {
     Accepted,
 }
 
+/// Quote Reference
+#[derive(Clone, Default, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteRef {
+    /// Unique Id of Quote
+    pub id: String,
+    /// HTTP Reference
+    pub href: String,
+    /// Name or description of Quote
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Referred Type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "@referredType")]
+    pub referred_type: Option<String>,
+}
+
+impl From<&Quote> for QuoteRef {
+    fn from(value: &Quote) -> Self {
+        QuoteRef {
+            id: value.get_id(),
+            href: value.get_href(),
+            name: Some(value.description()),
+            referred_type: Some(Quote::get_class()),
+        }
+    }
+}
+
 /// Product Quote
 #[derive(
     Clone,
@@ -255,6 +283,7 @@ impl EventPayload<QuoteEvent> for Quote {
 mod test {
     use super::Quote;
     use super::QuoteEventType;
+    use super::QuoteRef;
     use super::QuoteStateType;
     use crate::common::event::EventPayload;
     use crate::tmf648::quote::QUOTE_VERS;
@@ -333,6 +362,16 @@ mod test {
         assert_eq!(quote.quote_item.unwrap().len(), 2);
     }
 
+    #[test]
+    fn test_quoteref_from_quote() {
+        let quote = Quote::new();
+
+        let quote_ref = QuoteRef::from(&quote);
+
+        assert_eq!(quote_ref.id, quote.get_id());
+        assert_eq!(quote_ref.href, quote.get_href());
+    }
+
     #[test]
     fn test_quote_add_price() {
         let mut quote = Quote::new();
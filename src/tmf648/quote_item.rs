@@ -93,6 +93,19 @@ impl From<ProductOffering> for ProductRefOrValue {
     }
 }
 
+/// Reference to an item within a Quote
+#[derive(Clone, Default, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteItemRef {
+    /// Id of the Quote Item
+    pub id: String,
+    /// Id of the Quote containing the item
+    pub quote_id: String,
+    /// HTTP Reference of the Quote containing the item
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_href: Option<String>,
+}
+
 /// Quote Item, line item for a product quote
 #[derive(Clone, Default, Debug, Deserialize, HasAttachment, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -149,6 +162,22 @@ impl QuoteItem {
         }
     }
 
+    /// Builder pattern to set the action for this QuoteItem, e.g. add
+    pub fn action(mut self, action: impl Into<String>) -> QuoteItem {
+        self.action = Some(action.into());
+        self
+    }
+
+    /// Get the action for this QuoteItem
+    pub fn get_action(&self) -> Option<String> {
+        self.action.clone()
+    }
+
+    /// Get the pricing for this QuoteItem
+    pub fn get_price(&self) -> Vec<QuotePrice> {
+        self.quote_item_price.clone().unwrap_or_default()
+    }
+
     /// Get the ProductOffering for this QuoteItem
     pub fn get_offer(&self) -> Option<ProductRefOrValue> {
         self.product.clone()
@@ -64,6 +64,21 @@ impl QuotePrice {
         self.recurring_charge_period = Some(period.to_owned());
         self
     }
+
+    /// Get the price type, e.g. recurring
+    pub fn get_price_type(&self) -> Option<String> {
+        self.price_type.clone()
+    }
+
+    /// Get the recurring charge period
+    pub fn get_period(&self) -> Option<String> {
+        self.recurring_charge_period.clone()
+    }
+
+    /// Get the unit of measure
+    pub fn get_unit_of_measure(&self) -> Option<String> {
+        self.unit_of_measure.clone()
+    }
 }
 
 #[cfg(test)]
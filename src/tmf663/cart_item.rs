@@ -2,6 +2,8 @@
 
 use super::shopping_cart::CartPrice;
 use crate::common::note::Note;
+#[cfg(feature = "tmf620")]
+use crate::tmf620::pricing::PriceRequest;
 #[cfg(all(feature = "tmf620", feature = "build-V4"))]
 use crate::tmf620::product_offering::ProductOfferingRef;
 #[cfg(all(feature = "tmf620", feature = "build-V5"))]
@@ -46,6 +48,22 @@ impl CartItem {
     }
 }
 
+#[cfg(feature = "tmf620")]
+impl From<&CartItem> for PriceRequest {
+    fn from(value: &CartItem) -> Self {
+        PriceRequest {
+            item_id: value.id.clone(),
+            offering_id: value
+                .product_offering
+                .as_ref()
+                .map(|po| po.id.clone())
+                .unwrap_or_default(),
+            quantity: value.quantity,
+            ..Default::default()
+        }
+    }
+}
+
 impl From<ProductOfferingRef> for CartItem {
     fn from(value: ProductOfferingRef) -> Self {
         let id = Uuid::new_v4().simple().to_string();
@@ -3,9 +3,13 @@
 use serde::{Deserialize, Serialize};
 
 use crate::common::contact::ContactMedium;
-use crate::common::price::Price;
+use crate::common::price::{Price, PriceAlteration};
 use crate::common::related_party::RelatedParty;
 use crate::common::tmf_error::TMFError;
+#[cfg(feature = "tmf620")]
+use crate::tmf620::pricing::{
+    PriceComponent, PriceRequest, PriceSummary, PricingEngine, PricingResult,
+};
 use crate::{HasId, HasRelatedParty, HasValidity, TimePeriod};
 use tmflib_derive::{HasId, HasRelatedParty, HasValidity};
 
@@ -17,13 +21,58 @@ const CART_DEFAULT_VALID: u8 = 7;
 
 /// Cart pricing
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CartPrice {
-    description: String,
-    name: String,
-    price_type: String,
-    recurring_charge_period: String,
-    unit_of_measure: String,
-    price: Option<Price>,
+    /// Description
+    pub description: String,
+    /// Name
+    pub name: String,
+    /// Price Type, e.g. recurring
+    #[serde(alias = "price_type")]
+    pub price_type: String,
+    /// Recurring charge period, e.g. month
+    #[serde(alias = "recurring_charge_period")]
+    pub recurring_charge_period: String,
+    /// Unit of measure
+    #[serde(alias = "unit_of_measure")]
+    pub unit_of_measure: String,
+    /// Price
+    pub price: Option<Price>,
+    /// Alterations (e.g. discounts) applied to this price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_alteration: Option<Vec<PriceAlteration>>,
+}
+
+#[cfg(feature = "tmf620")]
+impl From<&PriceComponent> for CartPrice {
+    fn from(value: &PriceComponent) -> Self {
+        CartPrice {
+            description: String::default(),
+            name: value.name.clone(),
+            price_type: value.price_type.to_string(),
+            recurring_charge_period: value.recurring_charge_period.clone().unwrap_or_default(),
+            unit_of_measure: value.unit_of_measure.clone().unwrap_or_default(),
+            price: Some(value.price.clone()),
+            price_alteration: match value.price_alteration.is_empty() {
+                true => None,
+                false => Some(value.price_alteration.clone()),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "tmf620")]
+impl From<&PriceSummary> for CartPrice {
+    fn from(value: &PriceSummary) -> Self {
+        CartPrice {
+            description: String::from("Cart Total"),
+            name: value.price_type.to_string(),
+            price_type: value.price_type.to_string(),
+            recurring_charge_period: value.recurring_charge_period.clone().unwrap_or_default(),
+            price: Some(value.price.clone()),
+            ..Default::default()
+        }
+    }
 }
 
 /// Shopping Cart Refernce
@@ -60,7 +109,7 @@ pub struct ShoppingCart {
     pub valid_for: Option<TimePeriod>,
     // Referenced objects
     /// Cart Price (Total)
-    cart_total_price: Option<Vec<CartPrice>>,
+    pub cart_total_price: Option<Vec<CartPrice>>,
     /// Cart Items
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cart_item: Option<Vec<CartItem>>,
@@ -91,6 +140,31 @@ impl ShoppingCart {
             None => self.cart_item = Some(vec![item]),
         }
     }
+
+    /// Price all items in the cart, setting the item prices and the cart total.
+    /// Items without a product offering have nothing to price and are skipped.
+    #[cfg(feature = "tmf620")]
+    pub fn price(&mut self, engine: &PricingEngine) -> Result<PricingResult, TMFError> {
+        let items = self.cart_item.as_deref().unwrap_or_default();
+        let requests: Vec<PriceRequest> = items
+            .iter()
+            .filter(|i| i.product_offering.is_some())
+            .map(PriceRequest::from)
+            .collect();
+        let result = engine.price(&requests)?;
+        let mut prices = result.items.iter();
+        for item in self.cart_item.iter_mut().flatten() {
+            item.item_price = match item.product_offering {
+                // Results are in request order
+                Some(_) => prices
+                    .next()
+                    .map(|p| p.components.iter().map(CartPrice::from).collect()),
+                None => None,
+            };
+        }
+        self.cart_total_price = Some(result.totals.iter().map(CartPrice::from).collect());
+        Ok(result)
+    }
 }
 
 #[cfg(test)]
@@ -102,6 +176,13 @@ mod test {
     #[cfg(all(feature = "tmf632", feature = "build-V5"))]
     use crate::tmf632::organization_v5::Organization;
 
+    use crate::common::money::Money;
+    #[cfg(feature = "build-V4")]
+    use crate::tmf620::product_offering::{ProductOffering, ProductOfferingRef};
+    use crate::tmf620::product_offering_price::ProductOfferingPrice;
+    #[cfg(feature = "build-V5")]
+    use crate::tmf620::product_offering_v5::{ProductOffering, ProductOfferingRef};
+
     const ORG: &str = "AnOrganization";
 
     #[test]
@@ -141,4 +222,74 @@ mod test {
 
         assert_eq!(cart.valid_for.is_some(), true);
     }
+
+    #[test]
+    fn test_cart_price() {
+        let offer = ProductOffering::new("Broadband");
+        let mut engine = PricingEngine::new();
+        engine.add_price(
+            offer.get_id(),
+            ProductOfferingPrice::new("Monthly")
+                .price(Money::from(50))
+                .recurring("month", 1),
+        );
+        let mut item = CartItem::from(ProductOfferingRef::from(offer));
+        item.quantity = 2;
+        let mut cart = ShoppingCart::new();
+        cart.add_item(item);
+
+        cart.price(&engine).unwrap();
+
+        let item_price = cart.cart_item.as_ref().unwrap()[0]
+            .item_price
+            .as_ref()
+            .unwrap();
+        assert_eq!(item_price[0].recurring_charge_period, "month".to_string());
+        let total = cart.cart_total_price.as_ref().unwrap();
+        assert_eq!(total.len(), 1);
+        assert_eq!(
+            total[0].price.as_ref().unwrap().tax_included_amount,
+            Money::from(110)
+        );
+    }
+
+    #[test]
+    fn test_cart_price_skips_item_without_offering() {
+        let offer = ProductOffering::new("Broadband");
+        let mut engine = PricingEngine::new();
+        engine.add_price(
+            offer.get_id(),
+            ProductOfferingPrice::new("Monthly")
+                .price(Money::from(50))
+                .recurring("month", 1),
+        );
+        let mut cart = ShoppingCart::new();
+        cart.add_item(CartItem::default());
+        cart.add_item(CartItem::from(ProductOfferingRef::from(offer)));
+
+        cart.price(&engine).unwrap();
+
+        let items = cart.cart_item.as_ref().unwrap();
+        assert!(items[0].item_price.is_none());
+        assert_eq!(items[1].item_price.as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_cart_price_serialize() {
+        let price = CartPrice {
+            price_type: "recurring".to_string(),
+            price_alteration: Some(vec![PriceAlteration::percentage("Promo", 10.0)]),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&price).unwrap();
+
+        assert!(json.contains("\"priceType\""));
+        assert!(json.contains("\"priceAlteration\""));
+        let old: CartPrice = serde_json::from_str(
+            "{\"description\":\"\",\"name\":\"\",\"price_type\":\"recurring\",\"recurring_charge_period\":\"\",\"unit_of_measure\":\"\",\"price\":null}",
+        )
+        .unwrap();
+        assert_eq!(old.price_type, "recurring".to_string());
+    }
 }
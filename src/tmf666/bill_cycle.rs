@@ -0,0 +1,371 @@
+//! Bill Cycle Module
+//!
+//! Defines when an account is billed and calculates cycle periods and prorated recurring charges.
+//! - Monthly, quarterly and yearly cycles start on the billing day of the month. If the month is
+//!   shorter, the cycle starts on the last day of the month instead.
+//! - Weekly cycles start on the billing day of the week, 1 being Monday.
+//! - Cycles are aligned to the month (or week) of the date the periods are calculated from.
+//! - All boundaries are at midnight UTC, the end of each period is exclusive.
+//! ```
+//! use tmflib::tmf666::bill_cycle::{BillingCycleSpecification, BillingFrequency};
+//!
+//! let cycle = BillingCycleSpecification::new("Monthly on the 15th", BillingFrequency::Monthly, 15);
+//! let periods = cycle.periods("2024-01-20T10:00:00+00:00", 2).unwrap();
+//! assert_eq!(periods[0].start_date_time, "2024-01-15T00:00:00+00:00");
+//! assert_eq!(periods[1].end_date_time, Some("2024-03-15T00:00:00+00:00".to_string()));
+//! ```
+
+use chrono::{Datelike, Days, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::common::money::Money;
+use crate::common::tmf_error::TMFError;
+use crate::{DateTime, TimePeriod};
+
+const PRORATE_DP: u32 = 2;
+
+/// How often an account is billed
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BillingFrequency {
+    /// Every week
+    Weekly,
+    /// Every month
+    #[default]
+    Monthly,
+    /// Every three months
+    Quarterly,
+    /// Every year
+    Yearly,
+}
+
+impl BillingFrequency {
+    fn months(&self) -> Option<i32> {
+        match self {
+            BillingFrequency::Weekly => None,
+            BillingFrequency::Monthly => Some(1),
+            BillingFrequency::Quarterly => Some(3),
+            BillingFrequency::Yearly => Some(12),
+        }
+    }
+}
+
+/// Billing Cycle Specification
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillingCycleSpecification {
+    /// Unique Id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// HTTP URI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    /// Name of the cycle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Billing day, day of the month or day of the week for weekly cycles
+    pub billing_date_shift: u32,
+    /// How often the account is billed
+    pub frequency: BillingFrequency,
+    /// Days between the end of the cycle and the payment due date
+    pub payment_due_date_offset: u32,
+    /// Validity period
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_for: Option<TimePeriod>,
+}
+
+impl BillingCycleSpecification {
+    /// Create a new cycle specification
+    pub fn new(
+        name: impl Into<String>,
+        frequency: BillingFrequency,
+        billing_day: u32,
+    ) -> BillingCycleSpecification {
+        BillingCycleSpecification {
+            name: Some(name.into()),
+            billing_date_shift: billing_day,
+            frequency,
+            ..Default::default()
+        }
+    }
+
+    /// Builder pattern to set the number of days after the end of the cycle that payment is due
+    pub fn payment_due(mut self, days: u32) -> BillingCycleSpecification {
+        self.payment_due_date_offset = days;
+        self
+    }
+
+    /// Check the billing day is valid for the frequency
+    pub fn validate(&self) -> Result<(), TMFError> {
+        let max = match self.frequency {
+            BillingFrequency::Weekly => 7,
+            _ => 31,
+        };
+        match (1..=max).contains(&self.billing_date_shift) {
+            true => Ok(()),
+            false => Err(TMFError::InvalidTimePeriod(format!(
+                "Billing day {} is not valid for a {:?} cycle",
+                self.billing_date_shift, self.frequency
+            ))),
+        }
+    }
+
+    /// Calculate `count` cycle periods, starting with the cycle containing `from`
+    pub fn periods(&self, from: &str, count: usize) -> Result<Vec<TimePeriod>, TMFError> {
+        self.validate()?;
+        let from = parse_date(from)?;
+        let starts: Vec<NaiveDate> = match self.frequency.months() {
+            Some(step) => {
+                let month = from.year() * 12 + from.month0() as i32;
+                let first = match self.boundary(month)? <= from {
+                    true => month,
+                    false => month - step,
+                };
+                (0..=count as i32)
+                    .map(|i| self.boundary(first + i * step))
+                    .collect::<Result<_, _>>()?
+            }
+            None => {
+                let weekday = from.weekday().number_from_monday();
+                let back = (weekday + 7 - self.billing_date_shift) % 7;
+                let first = from - Days::new(back as u64);
+                (0..=count as u64)
+                    .map(|i| first + Days::new(i * 7))
+                    .collect()
+            }
+        };
+        Ok(starts
+            .windows(2)
+            .map(|w| TimePeriod {
+                start_date_time: to_timestamp(w[0]),
+                end_date_time: Some(to_timestamp(w[1])),
+            })
+            .collect())
+    }
+
+    /// Calculate the cycle period containing `date`
+    pub fn period(&self, date: &str) -> Result<TimePeriod, TMFError> {
+        // periods() always returns the requested number of periods
+        Ok(self.periods(date, 1)?.remove(0))
+    }
+
+    /// Calculate the payment due date for a cycle period
+    pub fn payment_due_date(&self, period: &TimePeriod) -> Result<DateTime, TMFError> {
+        let end = period
+            .end_date_time
+            .as_ref()
+            .ok_or(TMFError::NoDataError("Cycle period has no end".to_string()))?;
+        let due = parse_date(end)? + Days::new(self.payment_due_date_offset as u64);
+        Ok(to_timestamp(due))
+    }
+
+    /// First day of the cycle starting in the given month, counted from year 0
+    fn boundary(&self, month: i32) -> Result<NaiveDate, TMFError> {
+        let first =
+            NaiveDate::from_ymd_opt(month.div_euclid(12), month.rem_euclid(12) as u32 + 1, 1)
+                .ok_or(TMFError::InvalidTimePeriod(format!(
+                    "Invalid cycle month {month}"
+                )))?;
+        let next =
+            first
+                .checked_add_months(chrono::Months::new(1))
+                .ok_or(TMFError::InvalidTimePeriod(format!(
+                    "Invalid cycle month {month}"
+                )))?;
+        let days_in_month = (next - first).num_days() as u32;
+        Ok(first + Days::new((self.billing_date_shift.min(days_in_month) - 1) as u64))
+    }
+}
+
+/// Billing structure of an account
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillStructure {
+    /// Billing cycle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cycle_specification: Option<BillingCycleSpecification>,
+}
+
+/// Number of days from the start of `period` up to the end of `period`
+pub fn days(period: &TimePeriod) -> Result<i64, TMFError> {
+    let start = parse_date(&period.start_date_time)?;
+    let end = period
+        .end_date_time
+        .as_ref()
+        .ok_or(TMFError::NoDataError("Period has no end".to_string()))?;
+    Ok((parse_date(end)? - start).num_days())
+}
+
+/// Calculate the period two periods have in common, a period with no end continues indefinitely
+pub fn overlap(a: &TimePeriod, b: &TimePeriod) -> Result<Option<TimePeriod>, TMFError> {
+    let start = parse_date(&a.start_date_time)?.max(parse_date(&b.start_date_time)?);
+    let end = match (&a.end_date_time, &b.end_date_time) {
+        (Some(x), Some(y)) => Some(parse_date(x)?.min(parse_date(y)?)),
+        (Some(x), None) | (None, Some(x)) => Some(parse_date(x)?),
+        (None, None) => None,
+    };
+    if end.is_some_and(|e| e <= start) {
+        return Ok(None);
+    }
+    Ok(Some(TimePeriod {
+        start_date_time: to_timestamp(start),
+        end_date_time: end.map(to_timestamp),
+    }))
+}
+
+/// Calculate the period a recurring price covers, starting at `start`, from its recurring charge
+/// period, e.g. `month` or `year`
+pub fn recurring_period(
+    start: &str,
+    recurring_charge_period: &str,
+) -> Result<TimePeriod, TMFError> {
+    let from = parse_date(start)?;
+    let end = match recurring_charge_period.to_lowercase().as_str() {
+        "day" | "daily" => from.checked_add_days(Days::new(1)),
+        "week" | "weekly" => from.checked_add_days(Days::new(7)),
+        "month" | "monthly" => from.checked_add_months(Months::new(1)),
+        "quarter" | "quarterly" => from.checked_add_months(Months::new(3)),
+        "year" | "yearly" | "annual" => from.checked_add_months(Months::new(12)),
+        _ => {
+            return Err(TMFError::InvalidTimePeriod(format!(
+                "Unknown recurring charge period {recurring_charge_period}"
+            )))
+        }
+    }
+    .ok_or(TMFError::InvalidTimePeriod(format!(
+        "Recurring charge period from {start} is out of range"
+    )))?;
+    Ok(TimePeriod {
+        start_date_time: to_timestamp(from),
+        end_date_time: Some(to_timestamp(end)),
+    })
+}
+
+/// Prorate a recurring charge for `period` by the days `active` falls within the period.
+/// The first day of the active period is charged, the last day (e.g. termination date) is not.
+/// If the active period has no end, it is treated as active until the end of `period`.
+pub fn prorate(
+    charge: &Money,
+    period: &TimePeriod,
+    active: &TimePeriod,
+) -> Result<Money, TMFError> {
+    let total = days(period)?;
+    if total <= 0 {
+        return Err(TMFError::InvalidTimePeriod(
+            "Cannot prorate over an empty period".to_string(),
+        ));
+    }
+    // The overlap ends no later than the period, which days() checked has an end
+    let active_days = match overlap(period, active)? {
+        Some(o) => days(&o)?,
+        None => 0,
+    };
+    Ok(Money {
+        unit: charge.unit.clone(),
+        value: (charge.value * Decimal::from(active_days) / Decimal::from(total))
+            .round_dp(PRORATE_DP),
+    })
+}
+
+fn parse_date(time: &str) -> Result<NaiveDate, TMFError> {
+    chrono::DateTime::parse_from_rfc3339(time)
+        .map(|t| t.with_timezone(&Utc).date_naive())
+        .map_err(|e| TMFError::InvalidTimePeriod(format!("Invalid date {time}: {e}")))
+}
+
+fn to_timestamp(date: NaiveDate) -> DateTime {
+    date.and_hms_opt(0, 0, 0)
+        .expect("Invalid midnight")
+        .and_utc()
+        .to_rfc3339()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn period(start: &str, end: Option<&str>) -> TimePeriod {
+        TimePeriod {
+            start_date_time: start.to_string(),
+            end_date_time: end.map(|e| e.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_periods_month_end() {
+        let cycle = BillingCycleSpecification::new("Month End", BillingFrequency::Monthly, 31);
+
+        let periods = cycle.periods("2024-02-10T00:00:00+00:00", 2).unwrap();
+
+        assert_eq!(periods[0].start_date_time, "2024-01-31T00:00:00+00:00");
+        assert_eq!(periods[1].start_date_time, "2024-02-29T00:00:00+00:00");
+        assert_eq!(
+            periods[1].end_date_time,
+            Some("2024-03-31T00:00:00+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_periods_weekly() {
+        let cycle =
+            BillingCycleSpecification::new("Mondays", BillingFrequency::Weekly, 1).payment_due(7);
+
+        // 2024-01-03 is a Wednesday
+        let period = cycle.period("2024-01-03T12:00:00+00:00").unwrap();
+
+        assert_eq!(period.start_date_time, "2024-01-01T00:00:00+00:00");
+        assert_eq!(
+            cycle.payment_due_date(&period).unwrap(),
+            "2024-01-15T00:00:00+00:00"
+        );
+        assert!(matches!(
+            BillingCycleSpecification::new("Bad", BillingFrequency::Weekly, 8).validate(),
+            Err(TMFError::InvalidTimePeriod(_))
+        ));
+        assert!(matches!(
+            cycle.period("2024-01-03"),
+            Err(TMFError::InvalidTimePeriod(_))
+        ));
+    }
+
+    #[test]
+    fn test_prorate() {
+        let cycle = period(
+            "2024-04-01T00:00:00+00:00",
+            Some("2024-05-01T00:00:00+00:00"),
+        );
+        let active = period("2024-04-21T09:30:00+00:00", None);
+        let terminated = period(
+            "2024-01-01T00:00:00+00:00",
+            Some("2024-04-16T00:00:00+00:00"),
+        );
+
+        assert_eq!(
+            prorate(&Money::from(30), &cycle, &active).unwrap().value,
+            Decimal::from(10)
+        );
+        assert_eq!(
+            prorate(&Money::from(30), &cycle, &terminated)
+                .unwrap()
+                .value,
+            Decimal::from(15)
+        );
+    }
+
+    #[test]
+    fn test_recurring_period() {
+        let year = recurring_period("2024-03-10T12:00:00+00:00", "year").unwrap();
+
+        assert_eq!(year.start_date_time, "2024-03-10T00:00:00+00:00");
+        assert_eq!(
+            year.end_date_time,
+            Some("2025-03-10T00:00:00+00:00".to_string())
+        );
+        assert_eq!(days(&year).unwrap(), 365);
+        assert!(matches!(
+            recurring_period("2024-03-10T12:00:00+00:00", "fortnight"),
+            Err(TMFError::InvalidTimePeriod(_))
+        ));
+    }
+}
@@ -2,13 +2,17 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::common::{contact::Contact, money::Money, related_party::RelatedParty};
+use crate::common::{
+    contact::Contact, money::Money, related_party::RelatedParty, tmf_error::TMFError,
+};
 use crate::{DateTime, HasDescription, HasId, HasLastUpdate, HasName};
 use tmflib_derive::{HasDescription, HasId, HasLastUpdate, HasName};
 
 use super::{
-    financial_account::FinancialAccountRef, AccountBalance, AccountRef, AccountTaxExemption,
-    PaymentMethodRef, PaymentPlan, MOD_PATH,
+    bill_cycle::{BillStructure, BillingCycleSpecification},
+    financial_account::FinancialAccountRef,
+    ledger::{post_entry, HasLedger, Ledger, LedgerEntry},
+    AccountBalance, AccountRef, AccountTaxExemption, PaymentMethodRef, PaymentPlan, MOD_PATH,
 };
 
 const CLASS_PATH: &str = "account";
@@ -57,6 +61,10 @@ pub struct BillingAccount {
     payment_plan: Option<Vec<PaymentPlan>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     default_payment_method: Option<PaymentMethodRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bill_structure: Option<BillStructure>,
+    #[serde(skip)]
+    ledger: Ledger,
 }
 
 impl BillingAccount {
@@ -66,6 +74,82 @@ impl BillingAccount {
         account.name = Some(name.into());
         account
     }
+
+    /// Builder pattern to set the credit limit of this account
+    pub fn credit_limit(mut self, limit: Money) -> BillingAccount {
+        self.credit_limit = Some(limit);
+        self
+    }
+
+    /// Builder pattern to set the billing cycle of this account
+    pub fn cycle(mut self, cycle: BillingCycleSpecification) -> BillingAccount {
+        self.bill_structure
+            .get_or_insert_with(BillStructure::default)
+            .cycle_specification = Some(cycle);
+        self
+    }
+
+    /// Get the billing cycle of this account
+    pub fn get_cycle(&self) -> Option<&BillingCycleSpecification> {
+        self.bill_structure
+            .as_ref()
+            .and_then(|b| b.cycle_specification.as_ref())
+    }
+
+    /// Get the balance of the given type, e.g. [`super::BALANCE_RECEIVABLE`]
+    pub fn get_balance(&self, balance_type: &str) -> Option<&AccountBalance> {
+        self.account_balance
+            .as_ref()?
+            .iter()
+            .find(|b| b.get_balance_type() == balance_type)
+    }
+
+    /// Adjust the balance of the given type by `delta`, creating the balance if required.
+    /// The adjustment is posted to the ledger and is not checked against the credit limit.
+    pub fn adjust_balance(
+        &mut self,
+        balance_type: &str,
+        delta: &Money,
+    ) -> Result<&Money, TMFError> {
+        post_entry(
+            &mut self.ledger,
+            &mut self.account_balance,
+            None,
+            LedgerEntry::adjustment(balance_type, delta.clone()),
+        )?;
+        self.get_balance(balance_type)
+            .and_then(|b| b.get_amount())
+            .ok_or(TMFError::GenericError(format!(
+                "No {balance_type} balance after adjustment"
+            )))
+    }
+}
+
+impl HasLedger for BillingAccount {
+    fn get_account_id(&self) -> String {
+        self.get_id()
+    }
+
+    fn get_ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    fn get_balances(&self) -> &[AccountBalance] {
+        self.account_balance.as_deref().unwrap_or_default()
+    }
+
+    fn get_credit_limit(&self) -> Option<&Money> {
+        self.credit_limit.as_ref()
+    }
+
+    fn post(&mut self, entry: LedgerEntry) -> Result<usize, TMFError> {
+        post_entry(
+            &mut self.ledger,
+            &mut self.account_balance,
+            self.credit_limit.as_ref(),
+            entry,
+        )
+    }
 }
 
 impl From<BillingAccount> for AccountRef {
@@ -80,14 +164,14 @@ impl From<BillingAccount> for AccountRef {
 }
 
 /// Billing Account Reference
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct BillingAccountRef {
     /// Referenced Id
-    id: String,
+    pub id: String,
     /// Referenced HREF
-    href: String,
+    pub href: String,
     /// Referenced Name
-    name: String,
+    pub name: String,
 }
 
 impl From<BillingAccount> for BillingAccountRef {
@@ -113,6 +197,22 @@ mod test {
         assert_eq!(account.name, Some(ACCOUNT.into()));
     }
 
+    #[test]
+    fn test_billing_account_adjust_balance() {
+        let mut account = BillingAccount::new(ACCOUNT).credit_limit(Money::from(10));
+
+        // Adjustments are not limited
+        account
+            .adjust_balance(super::super::BALANCE_RECEIVABLE, &Money::from(50))
+            .unwrap();
+        let balance = account
+            .adjust_balance(super::super::BALANCE_RECEIVABLE, &Money::from(-20))
+            .unwrap();
+
+        assert_eq!(balance, &Money::from(30));
+        assert_eq!(account.get_ledger().entries().len(), 2);
+    }
+
     #[test]
     fn test_ref_from_billing_account() {
         let account = BillingAccount::new(ACCOUNT);
@@ -5,8 +5,11 @@ use serde::{Deserialize, Serialize};
 use crate::{DateTime, HasId, HasLastUpdate, HasName};
 use tmflib_derive::{HasId, HasLastUpdate, HasName};
 
-use super::{AccountBalance, AccountRelationship, AccountTaxExemption, MOD_PATH};
-use crate::common::{contact::Contact, money::Money, related_party::RelatedParty};
+use super::ledger::{post_entry, HasLedger, Ledger, LedgerEntry, RELATIONSHIP_CHILD};
+use super::{AccountBalance, AccountRef, AccountRelationship, AccountTaxExemption, MOD_PATH};
+use crate::common::{
+    contact::Contact, money::Money, related_party::RelatedParty, tmf_error::TMFError,
+};
 
 const CLASS_PATH: &str = "account";
 
@@ -52,9 +55,36 @@ pub struct FinancialAccount {
     account_balance: Option<Vec<AccountBalance>>,
     account_relationship: Option<Vec<AccountRelationship>>,
     tax_exemption: Option<Vec<AccountTaxExemption>>,
+    #[serde(skip)]
+    ledger: Ledger,
 }
 
 impl FinancialAccount {
+    /// Create new Financial Account
+    pub fn new(name: impl Into<String>) -> FinancialAccount {
+        let mut account = FinancialAccount::create();
+        account.name = Some(name.into());
+        account
+    }
+
+    /// Builder pattern to set the credit limit of this account
+    pub fn credit_limit(mut self, limit: Money) -> FinancialAccount {
+        self.credit_limit = Some(limit);
+        self
+    }
+
+    /// Builder pattern to add a child account whose balances roll up into this account
+    pub fn child(mut self, account: &AccountRef) -> FinancialAccount {
+        self.account_relationship
+            .get_or_insert_with(Vec::new)
+            .push(AccountRelationship {
+                relationship_type: RELATIONSHIP_CHILD.to_string(),
+                valid_for: None,
+                account: Some(account.clone()),
+            });
+        self
+    }
+
     /// Get summed balance accross all AccountBalance records
     pub fn get_balance(&self) -> AccountBalance {
         let total = match self.account_balance.as_ref() {
@@ -78,6 +108,48 @@ impl FinancialAccount {
     }
 }
 
+impl HasLedger for FinancialAccount {
+    fn get_account_id(&self) -> String {
+        self.get_id()
+    }
+
+    fn get_ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    fn get_balances(&self) -> &[AccountBalance] {
+        self.account_balance.as_deref().unwrap_or_default()
+    }
+
+    fn get_credit_limit(&self) -> Option<&Money> {
+        self.credit_limit.as_ref()
+    }
+
+    fn post(&mut self, entry: LedgerEntry) -> Result<usize, TMFError> {
+        post_entry(
+            &mut self.ledger,
+            &mut self.account_balance,
+            self.credit_limit.as_ref(),
+            entry,
+        )
+    }
+
+    fn get_relationships(&self) -> &[AccountRelationship] {
+        self.account_relationship.as_deref().unwrap_or_default()
+    }
+}
+
+impl From<FinancialAccount> for AccountRef {
+    fn from(value: FinancialAccount) -> Self {
+        AccountRef {
+            id: value.get_id(),
+            href: value.get_href(),
+            name: value.get_name(),
+            description: value.description.clone(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,602 @@
+//! Account Ledger Module
+//!
+//! An append-only ledger of debit and credit entries for an account, from which balances are
+//! computed. Debits (e.g. bills) increase the amount owed and are positive, credits (e.g. payments)
+//! decrease it and are negative. Entries cannot be changed once posted, a mistake is corrected by
+//! posting a reversing entry.
+//!
+//! - Balances are computed per balance type as at any instant, from entries posted up to it.
+//! - All entries in a ledger must use the same currency.
+//! - Debits that would take the account's total balance over its credit limit are rejected.
+//!   Refunds are not checked, as they return money already paid.
+//! - Accounts implementing [`HasLedger`] keep their account balances in step with their ledger.
+//!   The ledger is not serialized, so an account without ledger entries, e.g. one loaded from
+//!   JSON, reports and brings forward its existing balances as opening entries, dated from the
+//!   start of each balance. Balances are dated when updated by a post; undated balances are
+//!   treated as always in effect.
+//! - [`roll_up`] adds the balances of child accounts, linked via [`AccountRelationship`], to
+//!   the balance of a parent account.
+//! ```
+//! use tmflib::common::money::Money;
+//! use tmflib::tmf666::billing_account::BillingAccount;
+//! use tmflib::tmf666::ledger::{HasLedger, LedgerEntry, LedgerEntryType};
+//! use tmflib::tmf666::BALANCE_RECEIVABLE;
+//!
+//! let mut account = BillingAccount::new("Customer Account").credit_limit(Money::from(100));
+//! account
+//!     .post(LedgerEntry::debit(LedgerEntryType::Bill, Money::from(80)))
+//!     .unwrap();
+//! account
+//!     .post(LedgerEntry::credit(LedgerEntryType::Payment, Money::from(30)))
+//!     .unwrap();
+//! // Would take the balance to 150
+//! assert!(account
+//!     .post(LedgerEntry::debit(LedgerEntryType::Bill, Money::from(100)))
+//!     .is_err());
+//!
+//! let balance = account.get_balance(BALANCE_RECEIVABLE).unwrap();
+//! assert_eq!(balance.get_amount(), Some(&Money::from(50)));
+//! ```
+
+use std::collections::HashSet;
+
+use chrono::FixedOffset;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::{AccountBalance, AccountRelationship, BALANCE_RECEIVABLE};
+use crate::common::money::Money;
+use crate::common::related_entity::EntityRef;
+use crate::common::tmf_error::TMFError;
+use crate::{now, DateTime, TimePeriod};
+
+/// When opening entries take effect for balances without a start date
+const UNDATED_POSTED: &str = "1970-01-01T00:00:00+00:00";
+
+/// Relationship type linking a parent account to a child account whose balances roll up into it
+pub const RELATIONSHIP_CHILD: &str = "child";
+
+/// What a ledger entry records
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LedgerEntryType {
+    /// Bill issued to the account
+    Bill,
+    /// Payment received
+    Payment,
+    /// Refund of a payment
+    Refund,
+    /// Manual adjustment or correction
+    #[default]
+    Adjustment,
+}
+
+/// Single ledger entry
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    /// Position in the ledger, assigned when posted
+    pub sequence: usize,
+    /// What this entry records
+    pub entry_type: LedgerEntryType,
+    /// Balance this entry applies to
+    pub balance_type: String,
+    /// Amount, positive for a debit and negative for a credit
+    pub amount: Money,
+    /// When the entry takes effect
+    pub posted: DateTime,
+    /// Entity this entry records, e.g. a bill or payment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<EntityRef>,
+    /// Description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl LedgerEntry {
+    /// Create a debit entry against the receivable balance, increasing the amount owed
+    pub fn debit(entry_type: LedgerEntryType, amount: Money) -> LedgerEntry {
+        LedgerEntry {
+            entry_type,
+            balance_type: BALANCE_RECEIVABLE.to_string(),
+            amount: Money {
+                unit: amount.unit,
+                value: amount.value.abs(),
+            },
+            posted: now(),
+            ..Default::default()
+        }
+    }
+
+    /// Create a credit entry against the receivable balance, reducing the amount owed
+    pub fn credit(entry_type: LedgerEntryType, amount: Money) -> LedgerEntry {
+        LedgerEntry {
+            amount: Money {
+                unit: amount.unit,
+                value: -amount.value.abs(),
+            },
+            ..LedgerEntry::debit(entry_type, Money::default())
+        }
+    }
+
+    /// Create an adjustment entry against the given balance, positive to increase the amount owed
+    pub fn adjustment(balance_type: impl Into<String>, amount: Money) -> LedgerEntry {
+        LedgerEntry {
+            amount,
+            balance_type: balance_type.into(),
+            ..LedgerEntry::debit(LedgerEntryType::Adjustment, Money::default())
+        }
+    }
+
+    /// Builder pattern to set the balance this entry applies to
+    pub fn balance_type(mut self, balance_type: impl Into<String>) -> LedgerEntry {
+        self.balance_type = balance_type.into();
+        self
+    }
+
+    /// Builder pattern to set when this entry takes effect
+    pub fn posted(mut self, posted: impl Into<DateTime>) -> LedgerEntry {
+        self.posted = posted.into();
+        self
+    }
+
+    /// Builder pattern to set the entity this entry records
+    pub fn reference(mut self, reference: EntityRef) -> LedgerEntry {
+        self.reference = Some(reference);
+        self
+    }
+
+    /// Builder pattern to set the description
+    pub fn description(mut self, description: impl Into<String>) -> LedgerEntry {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Returns true if this entry increases the amount owed
+    pub fn is_debit(&self) -> bool {
+        self.amount.value > Decimal::ZERO
+    }
+}
+
+/// Append-only ledger of account entries
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ledger {
+    entries: Vec<LedgerEntry>,
+}
+
+impl Ledger {
+    /// Create an empty ledger
+    pub fn new() -> Ledger {
+        Ledger::default()
+    }
+
+    /// Entries in the order they were posted
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    /// Currency of this ledger, taken from the first entry
+    pub fn currency(&self) -> Option<&str> {
+        self.entries.first().map(|e| e.amount.unit.as_str())
+    }
+
+    /// Append an entry, returning its sequence number. Debits other than refunds are checked
+    /// against `credit_limit` using the total of all entries, including those taking effect in
+    /// the future.
+    pub fn post(
+        &mut self,
+        mut entry: LedgerEntry,
+        credit_limit: Option<&Money>,
+    ) -> Result<usize, TMFError> {
+        parse_time(&entry.posted)?;
+        if let Some(currency) = self.currency() {
+            if currency != entry.amount.unit {
+                return Err(TMFError::CurrencyError(format!(
+                    "Cannot post {} entry to {currency} ledger",
+                    entry.amount.unit
+                )));
+            }
+        }
+        let limited = entry.is_debit() && entry.entry_type != LedgerEntryType::Refund;
+        if let (true, Some(limit)) = (limited, credit_limit) {
+            if limit.unit != entry.amount.unit {
+                return Err(TMFError::CurrencyError(format!(
+                    "Credit limit in {} for {} entry",
+                    limit.unit, entry.amount.unit
+                )));
+            }
+            let exposure =
+                self.entries.iter().map(|e| e.amount.value).sum::<Decimal>() + entry.amount.value;
+            if exposure > limit.value {
+                return Err(TMFError::CreditLimitError(format!(
+                    "Balance of {exposure} would exceed credit limit of {}",
+                    limit.value
+                )));
+            }
+        }
+        entry.sequence = self.entries.len() + 1;
+        self.entries.push(entry);
+        Ok(self.entries.len())
+    }
+
+    /// Post an entry reversing the entry with the given sequence number
+    pub fn reverse(
+        &mut self,
+        sequence: usize,
+        posted: impl Into<DateTime>,
+    ) -> Result<usize, TMFError> {
+        let original = self.entries.iter().find(|e| e.sequence == sequence).ok_or(
+            TMFError::InvalidReference(format!("Ledger entry {sequence} not found")),
+        )?;
+        let reversal = LedgerEntry {
+            amount: Money {
+                unit: original.amount.unit.clone(),
+                value: -original.amount.value,
+            },
+            description: Some(format!("Reversal of entry {sequence}")),
+            ..original.clone()
+        }
+        .posted(posted);
+        // Corrections are always allowed, so the credit limit is not checked
+        self.post(reversal, None)
+    }
+
+    /// Balance of the given type as at `at`
+    pub fn balance(&self, balance_type: &str, at: &str) -> Result<Money, TMFError> {
+        let at = parse_time(at)?;
+        let mut value = Decimal::ZERO;
+        for entry in self
+            .entries
+            .iter()
+            .filter(|e| e.balance_type == balance_type)
+        {
+            if parse_time(&entry.posted)? <= at {
+                value += entry.amount.value;
+            }
+        }
+        Ok(self.money(value))
+    }
+
+    /// Balances of every type as at `at`, in the order each type was first posted
+    pub fn balances(&self, at: &str) -> Result<Vec<AccountBalance>, TMFError> {
+        let mut types: Vec<&str> = vec![];
+        for entry in &self.entries {
+            if !types.contains(&entry.balance_type.as_str()) {
+                types.push(entry.balance_type.as_str());
+            }
+        }
+        types
+            .into_iter()
+            .map(|t| Ok(AccountBalance::new(t, self.balance(t, at)?)))
+            .collect()
+    }
+
+    fn money(&self, value: Decimal) -> Money {
+        Money {
+            unit: self.currency().unwrap_or_default().to_string(),
+            value,
+        }
+    }
+}
+
+/// Account with a ledger
+pub trait HasLedger {
+    /// Id of the account, used to resolve account relationships
+    fn get_account_id(&self) -> String;
+    /// Get the ledger
+    fn get_ledger(&self) -> &Ledger;
+    /// Get the stored account balances
+    fn get_balances(&self) -> &[AccountBalance];
+    /// Get the credit limit, if any
+    fn get_credit_limit(&self) -> Option<&Money>;
+    /// Post an entry to the ledger and update the account balances to the current time
+    fn post(&mut self, entry: LedgerEntry) -> Result<usize, TMFError>;
+    /// Relationships to other accounts
+    fn get_relationships(&self) -> &[AccountRelationship] {
+        &[]
+    }
+    /// Balance of the given type as at `at`, from the stored balances if the ledger is empty
+    fn balance_at(&self, balance_type: &str, at: &str) -> Result<Money, TMFError> {
+        match self.get_ledger().entries().is_empty() {
+            true => opening(self.get_balances())?.balance(balance_type, at),
+            false => self.get_ledger().balance(balance_type, at),
+        }
+    }
+}
+
+/// Ledger of opening entries for `balances`, each posted at the start of its balance
+fn opening(balances: &[AccountBalance]) -> Result<Ledger, TMFError> {
+    let mut ledger = Ledger::new();
+    for balance in balances {
+        if let Some(amount) = balance.get_amount() {
+            let posted = balance
+                .valid_for
+                .as_ref()
+                .map(|p| p.start_date_time.clone())
+                .unwrap_or(UNDATED_POSTED.to_string());
+            let entry = LedgerEntry::adjustment(balance.get_balance_type(), amount.clone())
+                .posted(posted)
+                .description("Opening balance");
+            ledger.post(entry, None)?;
+        }
+    }
+    Ok(ledger)
+}
+
+/// Post an entry to `ledger`, then update the amounts in `balances` as at now, dating them from
+/// now. An empty ledger is first seeded with the existing `balances` as opening entries. Balance
+/// types without ledger entries are left untouched.
+pub(crate) fn post_entry(
+    ledger: &mut Ledger,
+    balances: &mut Option<Vec<AccountBalance>>,
+    credit_limit: Option<&Money>,
+    entry: LedgerEntry,
+) -> Result<usize, TMFError> {
+    let mut working = match ledger.entries.is_empty() {
+        true => opening(balances.as_deref().unwrap_or_default())?,
+        false => ledger.clone(),
+    };
+    let sequence = working.post(entry, credit_limit)?;
+    let time = now();
+    let current = working.balances(&time)?;
+    let balances = balances.get_or_insert_with(Vec::new);
+    for mut balance in current {
+        balance.valid_for = Some(TimePeriod {
+            start_date_time: time.clone(),
+            end_date_time: None,
+        });
+        match balances
+            .iter_mut()
+            .find(|b| b.get_balance_type() == balance.get_balance_type())
+        {
+            Some(existing) => *existing = balance,
+            None => balances.push(balance),
+        }
+    }
+    *ledger = working;
+    Ok(sequence)
+}
+
+/// Balance of `parent` and all its child accounts as at `at`. Children are found in `accounts`
+/// via [`RELATIONSHIP_CHILD`] relationships valid at `at`, and may have children of their own.
+pub fn roll_up(
+    parent: &dyn HasLedger,
+    accounts: &[&dyn HasLedger],
+    balance_type: &str,
+    at: &str,
+) -> Result<Money, TMFError> {
+    let mut visited = HashSet::new();
+    roll_up_from(parent, accounts, balance_type, at, &mut visited)
+}
+
+fn roll_up_from(
+    account: &dyn HasLedger,
+    accounts: &[&dyn HasLedger],
+    balance_type: &str,
+    at: &str,
+    visited: &mut HashSet<String>,
+) -> Result<Money, TMFError> {
+    if !visited.insert(account.get_account_id()) {
+        return Err(TMFError::DependencyError(format!(
+            "Account {} appears more than once in the hierarchy",
+            account.get_account_id()
+        )));
+    }
+    let mut total = account.balance_at(balance_type, at)?;
+    let instant = parse_time(at)?;
+    for relationship in account.get_relationships() {
+        if relationship.relationship_type != RELATIONSHIP_CHILD
+            || !valid_at(relationship, &instant)?
+        {
+            continue;
+        }
+        let id = match relationship.account.as_ref() {
+            Some(a) => a.id.as_str(),
+            None => continue,
+        };
+        let child = accounts.iter().find(|a| a.get_account_id() == id).ok_or(
+            TMFError::InvalidReference(format!("Child account {id} not found")),
+        )?;
+        let balance = roll_up_from(*child, accounts, balance_type, at, visited)?;
+        if balance.value.is_zero() {
+            continue;
+        }
+        if total.value.is_zero() && total.unit.is_empty() {
+            total.unit = balance.unit.clone();
+        }
+        if balance.unit != total.unit {
+            return Err(TMFError::CurrencyError(format!(
+                "Child account {id} is in {} not {}",
+                balance.unit, total.unit
+            )));
+        }
+        total.value += balance.value;
+    }
+    Ok(total)
+}
+
+fn valid_at(
+    relationship: &AccountRelationship,
+    at: &chrono::DateTime<FixedOffset>,
+) -> Result<bool, TMFError> {
+    let period = match relationship.valid_for.as_ref() {
+        Some(p) => p,
+        None => return Ok(true),
+    };
+    if parse_time(&period.start_date_time)? > *at {
+        return Ok(false);
+    }
+    match period.end_date_time.as_ref() {
+        Some(end) => Ok(parse_time(end)? > *at),
+        None => Ok(true),
+    }
+}
+
+fn parse_time(time: &str) -> Result<chrono::DateTime<FixedOffset>, TMFError> {
+    chrono::DateTime::parse_from_rfc3339(time)
+        .map_err(|e| TMFError::GenericError(format!("Invalid time {time}: {e}")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tmf666::billing_account::BillingAccount;
+    use crate::tmf666::financial_account::FinancialAccount;
+    use crate::tmf666::AccountRef;
+    use crate::HasId;
+
+    const JAN: &str = "2024-01-15T00:00:00+00:00";
+    const FEB: &str = "2024-02-15T00:00:00+00:00";
+
+    #[test]
+    fn test_ledger_balance_at() {
+        let mut ledger = Ledger::new();
+        ledger
+            .post(
+                LedgerEntry::debit(LedgerEntryType::Bill, Money::from(100)).posted(JAN),
+                None,
+            )
+            .unwrap();
+        let payment = ledger
+            .post(
+                LedgerEntry::credit(LedgerEntryType::Payment, Money::from(60)).posted(FEB),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(
+            ledger.balance(BALANCE_RECEIVABLE, JAN).unwrap(),
+            Money::from(100)
+        );
+        assert_eq!(
+            ledger.balance(BALANCE_RECEIVABLE, FEB).unwrap(),
+            Money::from(40)
+        );
+
+        ledger.reverse(payment, FEB).unwrap();
+        assert_eq!(
+            ledger.balance(BALANCE_RECEIVABLE, FEB).unwrap(),
+            Money::from(100)
+        );
+        assert_eq!(ledger.entries().len(), 3);
+    }
+
+    #[test]
+    fn test_ledger_credit_limit() {
+        let mut account = BillingAccount::new("Account").credit_limit(Money::from(100));
+
+        assert!(account
+            .post(LedgerEntry::debit(LedgerEntryType::Bill, Money::from(90)))
+            .is_ok());
+        assert!(matches!(
+            account.post(LedgerEntry::debit(LedgerEntryType::Bill, Money::from(20))),
+            Err(TMFError::CreditLimitError(_))
+        ));
+        assert!(account
+            .post(LedgerEntry::credit(
+                LedgerEntryType::Payment,
+                Money::from(500)
+            ))
+            .is_ok());
+        assert_eq!(account.get_ledger().entries().len(), 2);
+
+        // Refunds return money already paid, so are allowed over the limit
+        assert!(account
+            .post(LedgerEntry::debit(
+                LedgerEntryType::Refund,
+                Money::from(600)
+            ))
+            .is_ok());
+        assert!(account
+            .post(LedgerEntry::debit(LedgerEntryType::Bill, Money::from(1)))
+            .is_err());
+    }
+
+    #[test]
+    fn test_ledger_after_deserialize() {
+        let mut account = BillingAccount::new("Account").credit_limit(Money::from(100));
+        account
+            .post(LedgerEntry::debit(LedgerEntryType::Bill, Money::from(80)))
+            .unwrap();
+
+        let json = serde_json::to_string(&account).unwrap();
+        let mut account: BillingAccount = serde_json::from_str(&json).unwrap();
+        assert!(account.get_ledger().entries().is_empty());
+
+        // The existing balance counts towards the credit limit
+        assert!(matches!(
+            account.post(LedgerEntry::debit(LedgerEntryType::Bill, Money::from(30))),
+            Err(TMFError::CreditLimitError(_))
+        ));
+        account
+            .post(LedgerEntry::credit(
+                LedgerEntryType::Payment,
+                Money::from(10),
+            ))
+            .unwrap();
+        let balance = account.get_balance(BALANCE_RECEIVABLE).unwrap();
+        assert_eq!(balance.get_amount(), Some(&Money::from(70)));
+        assert_eq!(account.get_ledger().entries().len(), 2);
+    }
+
+    #[test]
+    fn test_balance_without_ledger() {
+        let json = format!(
+            "{{ \"id\" : \"ACC1\", \"name\" : \"Account\", \"relatedParty\" : [], \"accountBalance\" : [{{ \"balanceType\" : \"{BALANCE_RECEIVABLE}\", \"amount\" : {{ \"unit\" : \"AUD\", \"value\" : 80 }}, \"validFor\" : {{ \"startDateTime\" : \"{FEB}\" }} }}] }}"
+        );
+        let child: BillingAccount = serde_json::from_str(&json).unwrap();
+        assert!(child.get_ledger().entries().is_empty());
+
+        // The stored balance applies from its own date
+        assert_eq!(
+            child.balance_at(BALANCE_RECEIVABLE, JAN).unwrap().value,
+            Decimal::ZERO
+        );
+        assert_eq!(
+            child.balance_at(BALANCE_RECEIVABLE, &now()).unwrap().value,
+            Decimal::from(80)
+        );
+
+        let parent = FinancialAccount::new("Parent").child(&AccountRef::from(child.clone()));
+        let accounts: [&dyn HasLedger; 1] = [&child];
+        assert_eq!(
+            roll_up(&parent, &accounts, BALANCE_RECEIVABLE, &now())
+                .unwrap()
+                .value,
+            Decimal::from(80)
+        );
+    }
+
+    #[test]
+    fn test_roll_up() {
+        let mut child = BillingAccount::new("Child");
+        child
+            .post(LedgerEntry::debit(LedgerEntryType::Bill, Money::from(30)).posted(JAN))
+            .unwrap();
+        let mut grandchild = BillingAccount::new("Grandchild");
+        grandchild
+            .post(LedgerEntry::debit(LedgerEntryType::Bill, Money::from(20)).posted(JAN))
+            .unwrap();
+        let middle = FinancialAccount::new("Middle").child(&AccountRef::from(grandchild.clone()));
+        let mut parent = FinancialAccount::new("Parent")
+            .child(&AccountRef::from(child.clone()))
+            .child(&AccountRef::from(middle.clone()));
+        parent
+            .post(LedgerEntry::debit(LedgerEntryType::Bill, Money::from(5)).posted(JAN))
+            .unwrap();
+
+        let accounts: [&dyn HasLedger; 3] = [&child, &grandchild, &middle];
+        assert_eq!(
+            roll_up(&parent, &accounts, BALANCE_RECEIVABLE, FEB).unwrap(),
+            Money::from(55)
+        );
+
+        // Unknown children are an error
+        let orphan = FinancialAccount::new("Orphan").child(&AccountRef {
+            id: grandchild.get_id() + "X",
+            ..Default::default()
+        });
+        assert!(roll_up(&orphan, &accounts, BALANCE_RECEIVABLE, FEB).is_err());
+    }
+}
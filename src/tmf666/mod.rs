@@ -21,11 +21,21 @@
 use serde::{Deserialize, Serialize};
 
 use crate::common::money::Money;
+use crate::common::tmf_error::TMFError;
 use crate::TimePeriod;
 
+pub mod bill_cycle;
 pub mod billing_account;
 pub mod financial_account;
+pub mod ledger;
 pub mod party_account;
+#[cfg(all(
+    feature = "tmf620",
+    feature = "tmf635",
+    feature = "tmf637",
+    feature = "tmf678"
+))]
+pub mod settlement;
 pub mod settlement_account;
 
 use crate::HasValidity;
@@ -33,8 +43,11 @@ use tmflib_derive::HasValidity;
 
 const MOD_PATH: &str = "accountManagement/v4";
 
+/// Balance type for the amount owed on an account, reduced by payments and increased by refunds
+pub const BALANCE_RECEIVABLE: &str = "receivableBalance";
+
 /// Account Reference
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountRef {
     /// Referenced Account Description
@@ -72,6 +85,44 @@ pub struct AccountBalance {
     valid_for: Option<TimePeriod>,
 }
 
+impl AccountBalance {
+    /// Create a new balance of the given type
+    pub fn new(balance_type: impl Into<String>, amount: Money) -> AccountBalance {
+        AccountBalance {
+            amount: Some(amount),
+            balance_type: balance_type.into(),
+            valid_for: None,
+        }
+    }
+
+    /// Get the balance type
+    pub fn get_balance_type(&self) -> &str {
+        self.balance_type.as_str()
+    }
+
+    /// Get the balance amount
+    pub fn get_amount(&self) -> Option<&Money> {
+        self.amount.as_ref()
+    }
+
+    /// Adjust the balance by `delta`, which may be negative. A balance without an amount
+    /// takes on the currency of `delta`.
+    pub fn adjust(&mut self, delta: &Money) -> Result<&Money, TMFError> {
+        let amount = self.amount.get_or_insert_with(|| Money {
+            unit: delta.unit.clone(),
+            value: Default::default(),
+        });
+        if amount.unit != delta.unit {
+            return Err(TMFError::CurrencyError(format!(
+                "Cannot adjust {} balance by {}",
+                amount.unit, delta.unit
+            )));
+        }
+        amount.value += delta.value;
+        Ok(amount)
+    }
+}
+
 /// Tax Exemption Status
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -116,6 +167,19 @@ pub struct PaymentMethodRef {
     name: String,
 }
 
+/// Reference a payment method without carrying its details
+#[cfg(feature = "tmf676")]
+impl From<&crate::tmf676::PaymentMethodRefOrValue> for PaymentMethodRef {
+    fn from(value: &crate::tmf676::PaymentMethodRefOrValue) -> Self {
+        use crate::{HasId, HasName};
+        PaymentMethodRef {
+            href: value.get_href(),
+            id: value.get_id(),
+            name: value.get_name(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::{AccountBalance, AccountRef, PaymentMethodRef, PaymentPlan};
@@ -6,8 +6,9 @@ use crate::{DateTime, HasDescription, HasId, HasLastUpdate, HasName, HasRelatedP
 use tmflib_derive::{HasDescription, HasId, HasLastUpdate, HasName, HasRelatedParty};
 
 use super::{
-    financial_account::FinancialAccountRef, AccountBalance, AccountRef, AccountTaxExemption,
-    PaymentMethodRef, PaymentPlan, MOD_PATH,
+    financial_account::FinancialAccountRef,
+    ledger::{post_entry, HasLedger, Ledger, LedgerEntry},
+    AccountBalance, AccountRef, AccountTaxExemption, PaymentMethodRef, PaymentPlan, MOD_PATH,
 };
 use crate::common::tmf_error::TMFError;
 use crate::common::{contact::Contact, money::Money, related_party::RelatedParty};
@@ -60,6 +61,35 @@ pub struct PartyAccount {
     payment_plan: Option<Vec<PaymentPlan>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     default_payment_method: Option<PaymentMethodRef>,
+    #[serde(skip)]
+    ledger: Ledger,
+}
+
+impl HasLedger for PartyAccount {
+    fn get_account_id(&self) -> String {
+        self.get_id()
+    }
+
+    fn get_ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    fn get_balances(&self) -> &[AccountBalance] {
+        self.account_balance.as_deref().unwrap_or_default()
+    }
+
+    fn get_credit_limit(&self) -> Option<&Money> {
+        self.credit_limit.as_ref()
+    }
+
+    fn post(&mut self, entry: LedgerEntry) -> Result<usize, TMFError> {
+        post_entry(
+            &mut self.ledger,
+            &mut self.account_balance,
+            self.credit_limit.as_ref(),
+            entry,
+        )
+    }
 }
 
 impl From<PartyAccount> for AccountRef {
@@ -0,0 +1,583 @@
+//! Settlement Module
+//!
+//! Calculates the revenue share owed to partners, each with a [`SettlementAccount`], for the
+//! product offerings they provide.
+//!
+//! Revenue share rules link a product offering to a partner account and define the share:
+//! - [`RevenueShare::Percentage`]: a percentage of the revenue
+//! - [`RevenueShare::PerUnit`]: a fixed fee for each unit of usage, or each bill line
+//! - [`RevenueShare::Tiered`]: a percentage that changes as revenue passes each tier, with each
+//!   rate applying only to the revenue within its tier
+//!
+//! Revenue is taken, excluding tax, from:
+//! - Rated, re-rated or billed usage within the settlement period, matched to an offering via the
+//!   rated product. Usage without a usage date cannot be placed in a period and is skipped.
+//!   Each usage record counts once towards per unit fees, however many rated products it has for
+//!   the offering.
+//! - Bill lines (see [`BillCharge`]), matched to an offering via the charged product. Bill lines
+//!   carry no date, so only those for the settlement period should be supplied.
+//!
+//! The statement holds a line per rule, and tax is added to the total share at the engine's tax
+//! rate unless the partner account has a tax exemption. All amounts must use the same currency,
+//! are calculated as decimals and are rounded to two decimal places. Rates and fees must not be
+//! negative, and tiers must start from different amounts.
+//! ```
+//! # #[cfg(feature = "build-V4")]
+//! # use tmflib::tmf620::product_offering::{ProductOffering, ProductOfferingRef};
+//! # #[cfg(feature = "build-V5")]
+//! # use tmflib::tmf620::product_offering_v5::{ProductOffering, ProductOfferingRef};
+//! # #[cfg(feature = "build-V4")]
+//! # use tmflib::tmf637::v4::product::Product;
+//! # #[cfg(feature = "build-V5")]
+//! # use tmflib::tmf637::v5::product::Product;
+//! use rust_decimal::Decimal;
+//! use tmflib::common::money::Money;
+//! use tmflib::tmf666::settlement::{RevenueShare, RevenueShareRule, SettlementEngine};
+//! use tmflib::tmf666::settlement_account::SettlementAccount;
+//! use tmflib::tmf678::bill_run::BillCharge;
+//! use tmflib::{HasId, TimePeriod};
+//!
+//! let partner = SettlementAccount::new("Content Partner");
+//! let offer = ProductOffering::new("Streaming Add-on");
+//! let product = Product::new("Streaming").product_offering(ProductOfferingRef::from(offer.clone()));
+//!
+//! let mut engine = SettlementEngine::new();
+//! engine.add_product(&product);
+//! engine.add_rule(RevenueShareRule::new(
+//!     offer.get_id(),
+//!     &partner,
+//!     RevenueShare::Percentage(Decimal::new(30, 2)),
+//! ));
+//!
+//! let mut line = BillCharge::default();
+//! line.product_id = Some(product.get_id());
+//! line.tax_excluded_amount = Money::from(20);
+//! let period = TimePeriod {
+//!     start_date_time: "2024-01-01T00:00:00+00:00".to_string(),
+//!     end_date_time: Some("2024-02-01T00:00:00+00:00".to_string()),
+//! };
+//!
+//! let statement = engine.settle(&partner, period, &[], &[line]).unwrap();
+//! assert_eq!(statement.share_excluded_tax, Money::from(6));
+//! ```
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::settlement_account::SettlementAccount;
+use super::AccountRef;
+use crate::common::money::Money;
+use crate::common::tmf_error::TMFError;
+use crate::tmf635::{Usage, UsageStatusType};
+#[cfg(feature = "build-V4")]
+use crate::tmf637::v4::product::Product;
+#[cfg(feature = "build-V5")]
+use crate::tmf637::v5::product::Product;
+use crate::tmf678::bill_run::{in_period, BillCharge};
+use crate::{HasId, TimePeriod};
+
+/// Default tax rate, matching the default used by the pricing engine
+const DEFAULT_TAX_RATE: Decimal = Decimal::from_parts(10, 0, 0, false, 2);
+const SETTLEMENT_DP: u32 = 2;
+
+/// Revenue share band, the rate applies to revenue from `from` up to the next tier
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareTier {
+    /// Revenue this tier starts from
+    pub from: Decimal,
+    /// Share of revenue within this tier, e.g. 0.25 for 25%
+    pub rate: Decimal,
+}
+
+/// How a partner's share is calculated
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RevenueShare {
+    /// Share of revenue, e.g. 0.3 for 30%
+    Percentage(Decimal),
+    /// Fixed fee per unit
+    PerUnit(Money),
+    /// Share of revenue by tier
+    Tiered(Vec<ShareTier>),
+}
+
+/// Revenue share rule linking a product offering to a partner
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevenueShareRule {
+    /// Product offering the partner provides
+    pub offering_id: String,
+    /// Partner settlement account
+    pub account: AccountRef,
+    /// How the share is calculated
+    pub share: RevenueShare,
+    /// Usage characteristic counted for per unit fees, otherwise each usage record is one unit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+impl RevenueShareRule {
+    /// Create a new rule
+    pub fn new(
+        offering_id: impl Into<String>,
+        account: &SettlementAccount,
+        share: RevenueShare,
+    ) -> RevenueShareRule {
+        RevenueShareRule {
+            offering_id: offering_id.into(),
+            account: AccountRef::from(account.clone()),
+            share,
+            unit: None,
+        }
+    }
+
+    /// Builder pattern to set the usage characteristic counted for per unit fees
+    pub fn unit(mut self, unit: impl Into<String>) -> RevenueShareRule {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Check the rates and fees of this rule
+    fn validate(&self) -> Result<(), TMFError> {
+        let negative = |value: Decimal| {
+            TMFError::TierError(format!(
+                "Share for offering {} has negative value {value}",
+                self.offering_id
+            ))
+        };
+        match &self.share {
+            RevenueShare::Percentage(rate) if rate.is_sign_negative() => Err(negative(*rate)),
+            RevenueShare::PerUnit(fee) if fee.value.is_sign_negative() => Err(negative(fee.value)),
+            RevenueShare::Tiered(tiers) => {
+                let mut starts = vec![];
+                for tier in tiers {
+                    if tier.rate.is_sign_negative() || tier.from.is_sign_negative() {
+                        return Err(negative(tier.rate.min(tier.from)));
+                    }
+                    if starts.contains(&tier.from) {
+                        return Err(TMFError::TierError(format!(
+                            "Share for offering {} has more than one tier from {}",
+                            self.offering_id, tier.from
+                        )));
+                    }
+                    starts.push(tier.from);
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn share_of(&self, revenue: &Money, quantity: Decimal) -> Result<Money, TMFError> {
+        self.validate()?;
+        let value = match &self.share {
+            RevenueShare::Percentage(rate) => revenue.value * rate,
+            RevenueShare::PerUnit(fee) => {
+                if fee.unit != revenue.unit {
+                    return Err(TMFError::CurrencyError(format!(
+                        "Fee for offering {} is in {} not {}",
+                        self.offering_id, fee.unit, revenue.unit
+                    )));
+                }
+                fee.value * quantity
+            }
+            RevenueShare::Tiered(tiers) => {
+                let mut tiers = tiers.clone();
+                tiers.sort_by_key(|t| t.from);
+                let mut share = Decimal::ZERO;
+                for (i, tier) in tiers.iter().enumerate() {
+                    let upper = match tiers.get(i + 1) {
+                        Some(next) => next.from.min(revenue.value),
+                        None => revenue.value,
+                    };
+                    if upper > tier.from {
+                        share += (upper - tier.from) * tier.rate;
+                    }
+                }
+                share
+            }
+        };
+        Ok(Money {
+            unit: revenue.unit.clone(),
+            value: value.round_dp(SETTLEMENT_DP),
+        })
+    }
+}
+
+/// Settlement for a single rule
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementLine {
+    /// Product offering settled
+    pub offering_id: String,
+    /// Units of usage and bill lines settled
+    pub quantity: Decimal,
+    /// Revenue excluding tax
+    pub revenue: Money,
+    /// Partner share excluding tax
+    pub share: Money,
+}
+
+/// Settlement statement for a partner account
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementStatement {
+    /// Partner settlement account
+    pub account: AccountRef,
+    /// Period settled
+    pub period: TimePeriod,
+    /// Settlement for each rule
+    pub lines: Vec<SettlementLine>,
+    /// Total revenue excluding tax
+    pub revenue: Money,
+    /// Total share excluding tax
+    pub share_excluded_tax: Money,
+    /// Tax on the share
+    pub tax_amount: Money,
+    /// Total share including tax
+    pub share_included_tax: Money,
+}
+
+/// Revenue share settlement engine
+#[derive(Clone, Debug)]
+pub struct SettlementEngine {
+    products: HashMap<String, String>,
+    rules: Vec<RevenueShareRule>,
+    tax_rate: Decimal,
+}
+
+impl Default for SettlementEngine {
+    fn default() -> Self {
+        SettlementEngine {
+            products: HashMap::new(),
+            rules: vec![],
+            tax_rate: DEFAULT_TAX_RATE,
+        }
+    }
+}
+
+impl SettlementEngine {
+    /// Create a new settlement engine
+    pub fn new() -> SettlementEngine {
+        SettlementEngine::default()
+    }
+
+    /// Builder pattern to set the tax rate applied to partner shares, e.g. 0.1 for 10%
+    pub fn tax(mut self, rate: Decimal) -> SettlementEngine {
+        self.tax_rate = rate;
+        self
+    }
+
+    /// Add a product, so usage and bill lines for it can be matched to its offering
+    pub fn add_product(&mut self, product: &Product) {
+        if let Some(offering) = product.get_product_offering() {
+            self.products.insert(product.get_id(), offering.id.clone());
+        }
+    }
+
+    /// Add a revenue share rule
+    pub fn add_rule(&mut self, rule: RevenueShareRule) {
+        self.rules.push(rule);
+    }
+
+    /// Calculate the settlement statement for a partner account over a period
+    pub fn settle(
+        &self,
+        account: &SettlementAccount,
+        period: TimePeriod,
+        usage: &[Usage],
+        charges: &[BillCharge],
+    ) -> Result<SettlementStatement, TMFError> {
+        // Revenue by offering, with the usage record it came from. Rated products of a usage
+        // record for the same offering are combined, so the record's units count once.
+        let mut items: Vec<(&String, Money, Option<&Usage>)> = vec![];
+        for record in usage {
+            if !matches!(
+                record.status,
+                Some(UsageStatusType::Rated)
+                    | Some(UsageStatusType::Rerated)
+                    | Some(UsageStatusType::Billed)
+            ) {
+                continue;
+            }
+            match &record.usage_date {
+                Some(date) if in_period(date, &period)? => {}
+                _ => continue,
+            }
+            let first = items.len();
+            for rated in record.rated_product_usage.iter().flatten() {
+                let offering = match rated
+                    .product_ref
+                    .as_ref()
+                    .and_then(|p| self.products.get(&p.id))
+                {
+                    Some(o) => o,
+                    None => continue,
+                };
+                let amount =
+                    rated
+                        .tax_excluded_rating_amount
+                        .as_ref()
+                        .ok_or(TMFError::NoDataError(format!(
+                            "Usage {} has no rated amount",
+                            record.id.clone().unwrap_or_default()
+                        )))?;
+                match items[first..].iter_mut().find(|i| i.0 == offering) {
+                    Some(item) => item.1 = add_money(Some(item.1.clone()), amount)?,
+                    None => items.push((offering, amount.clone(), Some(record))),
+                }
+            }
+        }
+        for charge in charges {
+            if let Some(offering) = charge
+                .product_id
+                .as_ref()
+                .and_then(|p| self.products.get(p))
+            {
+                items.push((offering, charge.tax_excluded_amount.clone(), None));
+            }
+        }
+
+        let mut lines = vec![];
+        let mut total_revenue = None;
+        let mut total_share = None;
+        for rule in self
+            .rules
+            .iter()
+            .filter(|r| r.account.id == account.get_id())
+        {
+            let mut revenue = None;
+            let mut quantity = Decimal::ZERO;
+            for (_, amount, record) in items.iter().filter(|i| *i.0 == rule.offering_id) {
+                revenue = Some(add_money(revenue, amount)?);
+                quantity += match record {
+                    Some(r) => usage_units(r, rule)?,
+                    None => Decimal::ONE,
+                };
+            }
+            let revenue = match revenue {
+                Some(r) => r,
+                None => continue,
+            };
+            let share = rule.share_of(&revenue, quantity)?;
+            total_revenue = Some(add_money(total_revenue, &revenue)?);
+            total_share = Some(add_money(total_share, &share)?);
+            lines.push(SettlementLine {
+                offering_id: rule.offering_id.clone(),
+                quantity,
+                revenue,
+                share,
+            });
+        }
+
+        let share = total_share.unwrap_or_default();
+        let rate = match account.is_tax_exempt() {
+            true => Decimal::ZERO,
+            false => self.tax_rate,
+        };
+        let tax_amount = Money {
+            unit: share.unit.clone(),
+            value: (share.value * rate).round_dp(SETTLEMENT_DP),
+        };
+        Ok(SettlementStatement {
+            account: AccountRef::from(account.clone()),
+            period,
+            lines,
+            revenue: total_revenue.unwrap_or_default(),
+            share_included_tax: share.clone() + tax_amount.clone(),
+            share_excluded_tax: share,
+            tax_amount,
+        })
+    }
+}
+
+/// Units of usage counted by a rule
+fn usage_units(usage: &Usage, rule: &RevenueShareRule) -> Result<Decimal, TMFError> {
+    let unit = match &rule.unit {
+        Some(u) => u,
+        None => return Ok(Decimal::ONE),
+    };
+    usage
+        .get_characteristic(unit)
+        .and_then(|c| c.value.as_decimal())
+        .ok_or(TMFError::NoDataError(format!(
+            "Usage {} has no {unit} quantity",
+            usage.id.clone().unwrap_or_default()
+        )))
+}
+
+/// Add two amounts, checking they are in the same currency
+fn add_money(total: Option<Money>, amount: &Money) -> Result<Money, TMFError> {
+    match total {
+        None => Ok(amount.clone()),
+        Some(t) if t.unit == amount.unit => Ok(Money {
+            unit: t.unit,
+            value: t.value + amount.value,
+        }),
+        Some(t) => Err(TMFError::CurrencyError(format!(
+            "Cannot settle {} with {}",
+            amount.unit, t.unit
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "build-V4")]
+    use crate::tmf620::product_offering::{ProductOffering, ProductOfferingRef};
+    #[cfg(feature = "build-V5")]
+    use crate::tmf620::product_offering_v5::{ProductOffering, ProductOfferingRef};
+    use crate::tmf635::{Any, ProductRef, RatedProductUsage, UsageCharacteristic};
+
+    fn period() -> TimePeriod {
+        TimePeriod {
+            start_date_time: "2024-01-01T00:00:00+00:00".to_string(),
+            end_date_time: Some("2024-02-01T00:00:00+00:00".to_string()),
+        }
+    }
+
+    fn usage(product: &Product, date: &str, amount: i32, minutes: f64) -> Usage {
+        Usage {
+            status: Some(UsageStatusType::Rated),
+            usage_date: Some(date.to_string()),
+            usage_characteristic: Some(vec![UsageCharacteristic {
+                name: "minutes".to_string(),
+                value: Any(serde_json::json!(minutes)),
+                ..Default::default()
+            }]),
+            rated_product_usage: Some(vec![RatedProductUsage {
+                product_ref: Some(ProductRef {
+                    id: product.get_id(),
+                    ..Default::default()
+                }),
+                tax_excluded_rating_amount: Some(Money::from(amount)),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }
+    }
+
+    fn setup(share: RevenueShare) -> (SettlementEngine, SettlementAccount, Product) {
+        let partner = SettlementAccount::new("Partner");
+        let offer = ProductOffering::new("Partner Content");
+        let product =
+            Product::new("Content").product_offering(ProductOfferingRef::from(offer.clone()));
+        let mut engine = SettlementEngine::new();
+        engine.add_product(&product);
+        engine.add_rule(RevenueShareRule::new(offer.get_id(), &partner, share).unit("minutes"));
+        (engine, partner, product)
+    }
+
+    #[test]
+    fn test_settle_per_unit() {
+        let (engine, partner, product) = setup(RevenueShare::PerUnit(Money::from(0.5)));
+        let usage = vec![
+            usage(&product, "2024-01-10T00:00:00+00:00", 10, 12.0),
+            usage(&product, "2024-01-20T00:00:00+00:00", 10, 8.0),
+            // Outside the period
+            usage(&product, "2024-02-10T00:00:00+00:00", 10, 100.0),
+        ];
+
+        let statement = engine.settle(&partner, period(), &usage, &[]).unwrap();
+
+        assert_eq!(statement.lines[0].quantity, Decimal::from(20));
+        assert_eq!(statement.revenue, Money::from(20));
+        assert_eq!(statement.share_excluded_tax, Money::from(10));
+        assert_eq!(statement.tax_amount, Money::from(1));
+        assert_eq!(statement.share_included_tax, Money::from(11));
+
+        let engine = engine.tax(Decimal::new(15, 2));
+        let statement = engine.settle(&partner, period(), &usage, &[]).unwrap();
+        assert_eq!(statement.tax_amount, Money::from(1.5));
+    }
+
+    #[test]
+    fn test_settle_units_per_record() {
+        let (engine, partner, product) = setup(RevenueShare::PerUnit(Money::from(0.5)));
+        let mut record = usage(&product, "2024-01-10T00:00:00+00:00", 10, 12.5);
+        let rated = record.rated_product_usage.as_ref().unwrap()[0].clone();
+        record.rated_product_usage.as_mut().unwrap().push(rated);
+        // Undated usage cannot be placed in the period
+        let mut undated = usage(&product, "2024-01-10T00:00:00+00:00", 10, 12.0);
+        undated.usage_date = None;
+
+        let statement = engine
+            .settle(&partner, period(), &[record, undated], &[])
+            .unwrap();
+
+        assert_eq!(statement.lines[0].quantity, Decimal::new(125, 1));
+        assert_eq!(statement.revenue, Money::from(20));
+        assert_eq!(statement.share_excluded_tax, Money::from(6.25));
+    }
+
+    #[test]
+    fn test_settle_invalid_share() {
+        let (engine, partner, product) = setup(RevenueShare::Tiered(vec![
+            ShareTier {
+                from: Decimal::ZERO,
+                rate: Decimal::new(10, 2),
+            },
+            ShareTier {
+                from: Decimal::ZERO,
+                rate: Decimal::new(20, 2),
+            },
+        ]));
+        let records = vec![usage(&product, "2024-01-10T00:00:00+00:00", 100, 1.0)];
+        assert!(matches!(
+            engine.settle(&partner, period(), &records, &[]),
+            Err(TMFError::TierError(_))
+        ));
+
+        let (engine, partner, product) = setup(RevenueShare::Percentage(Decimal::new(-1, 1)));
+        let records = vec![usage(&product, "2024-01-10T00:00:00+00:00", 100, 1.0)];
+        assert!(engine.settle(&partner, period(), &records, &[]).is_err());
+    }
+
+    #[test]
+    fn test_settle_tiered() {
+        let (engine, partner, product) = setup(RevenueShare::Tiered(vec![
+            ShareTier {
+                from: Decimal::ZERO,
+                rate: Decimal::new(10, 2),
+            },
+            ShareTier {
+                from: Decimal::from(100),
+                rate: Decimal::new(20, 2),
+            },
+        ]));
+        let charge = BillCharge {
+            product_id: Some(product.get_id()),
+            tax_excluded_amount: Money::from(50),
+            ..Default::default()
+        };
+        let usage = vec![usage(&product, "2024-01-10T00:00:00+00:00", 100, 1.0)];
+
+        let statement = engine
+            .settle(&partner, period(), &usage, &[charge])
+            .unwrap();
+
+        // 10% of the first 100, 20% of the remaining 50
+        assert_eq!(statement.revenue, Money::from(150));
+        assert_eq!(statement.share_excluded_tax, Money::from(20));
+    }
+
+    #[test]
+    fn test_settle_currency_mismatch() {
+        let (engine, partner, product) = setup(RevenueShare::Percentage(Decimal::new(5, 1)));
+        let mut other = usage(&product, "2024-01-10T00:00:00+00:00", 10, 1.0);
+        other.rated_product_usage.as_mut().unwrap()[0].tax_excluded_rating_amount = Some(Money {
+            unit: "NZD".to_string(),
+            value: Decimal::from(10),
+        });
+        let usage = vec![usage(&product, "2024-01-10T00:00:00+00:00", 10, 1.0), other];
+
+        assert!(matches!(
+            engine.settle(&partner, period(), &usage, &[]),
+            Err(TMFError::CurrencyError(_))
+        ));
+    }
+}
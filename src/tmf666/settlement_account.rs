@@ -67,6 +67,17 @@ impl SettlementAccount {
             ..SettlementAccount::create()
         }
     }
+
+    /// Returns true if the account has a tax exemption that is currently valid
+    pub fn is_tax_exempt(&self) -> bool {
+        self.tax_exemption
+            .iter()
+            .flatten()
+            .any(|e| match e.valid_for.as_ref() {
+                Some(p) => p.started() && !p.finished(),
+                None => true,
+            })
+    }
 }
 
 impl From<SettlementAccount> for AccountRef {
@@ -0,0 +1,613 @@
+//! Payment Allocation Module
+//!
+//! Distributes a [`Payment`] across outstanding Customer Bills (V4 or V5) and reverses
+//! allocations when a payment is refunded.
+//!
+//! A payment can be allocated:
+//! - [`AllocationStrategy::OldestFirst`]: to sent or partially paid bills, oldest bill date first
+//! - [`AllocationStrategy::Specified`]: to the bills referenced by the payment items, up to the
+//!   amount of each item
+//!
+//! Each bill's remaining amount is reduced and the bill moves to `PartialPaid` or `Settled`.
+//! The payment items are replaced with the amounts actually allocated to each bill, the payment
+//! status is set to [`STATUS_ALLOCATED`], and the full payment is posted to the account's ledger
+//! as a credit to its receivable balance ([`crate::tmf666::BALANCE_RECEIVABLE`]). Any amount that
+//! could not be allocated is returned as unallocated and remains as credit on the account.
+//!
+//! A payment is only allocated once, and only to the account it was made to. Payment items are
+//! only allowed with [`AllocationStrategy::Specified`].
+//!
+//! A [`Refund`] reverses the allocations of an allocated payment, most recent allocation first,
+//! and posts the refunded amount to the account's ledger as a debit. The payment items are reduced
+//! by the amounts reversed, and the refund is recorded on the payment as an item with a negative
+//! amount. A payment cannot be refunded by more than its amount less earlier refunds.
+//!
+//! Bills are only updated once the whole payment or refund has been allocated without error.
+//! ```
+//! # #[cfg(feature = "build-V4")]
+//! # use tmflib::tmf678::customer_bill_v4::{CustomerBill, CustomerBillStateType};
+//! # #[cfg(feature = "build-V5")]
+//! # use tmflib::tmf678::customer_bill_v5::{CustomerBill, CustomerBillStateType};
+//! use tmflib::common::money::Money;
+//! use tmflib::tmf666::billing_account::BillingAccount;
+//! use tmflib::tmf666::{AccountRef, BALANCE_RECEIVABLE};
+//! use tmflib::tmf676::allocation::{allocate, AllocationStrategy};
+//! use tmflib::tmf676::payment::Payment;
+//! use tmflib::tmf676::PaymentMethodRefOrValue;
+//!
+//! let mut account = BillingAccount::new("Customer Account");
+//! let mut bill = CustomerBill::new().amount_due(Money::from(100));
+//! bill.set_state(CustomerBillStateType::Validated).unwrap();
+//! bill.set_state(CustomerBillStateType::Sent).unwrap();
+//! let mut bills = vec![bill];
+//!
+//! let mut payment = Payment::new(
+//!     PaymentMethodRefOrValue::default(),
+//!     AccountRef::from(account.clone()),
+//! )
+//! .amount(Money::from(40));
+//!
+//! let result = allocate(
+//!     &mut payment,
+//!     &mut bills,
+//!     &mut account,
+//!     AllocationStrategy::OldestFirst,
+//! )
+//! .unwrap();
+//! assert_eq!(bills[0].get_remaining_amount(), &Money::from(60));
+//! assert_eq!(bills[0].get_state(), CustomerBillStateType::PartialPaid);
+//! assert_eq!(result.unallocated, Money::from(0));
+//! ```
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::common::money::Money;
+use crate::common::related_entity::EntityRef;
+use crate::common::tmf_error::TMFError;
+use crate::tmf666::billing_account::BillingAccount;
+use crate::tmf666::ledger::{HasLedger, LedgerEntry, LedgerEntryType};
+use crate::tmf676::payment::{Payment, PaymentItem};
+use crate::tmf676::refund::Refund;
+use crate::{HasId, HasName};
+
+#[cfg(feature = "build-V4")]
+use crate::tmf678::customer_bill_v4::{CustomerBill, CustomerBillStateType};
+#[cfg(feature = "build-V5")]
+use crate::tmf678::customer_bill_v5::{CustomerBill, CustomerBillStateType};
+
+/// Status of a payment once it has been allocated
+pub const STATUS_ALLOCATED: &str = "allocated";
+
+/// How a payment is distributed across bills
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum AllocationStrategy {
+    /// Pay outstanding bills in order of bill date, oldest first
+    #[default]
+    OldestFirst,
+    /// Pay the bills referenced by the payment items
+    Specified,
+}
+
+/// Amount of a payment or refund allocated to a single bill
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allocation {
+    /// Id of the bill
+    pub bill_id: String,
+    /// Amount allocated
+    pub amount: Money,
+}
+
+/// Result of allocating a payment or refund
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllocationResult {
+    /// Allocations in the order they were applied
+    pub allocations: Vec<Allocation>,
+    /// Amount not allocated to any bill
+    pub unallocated: Money,
+}
+
+/// Allocate a payment across bills, see the module documentation for details.
+pub fn allocate(
+    payment: &mut Payment,
+    bills: &mut [CustomerBill],
+    account: &mut BillingAccount,
+    strategy: AllocationStrategy,
+) -> Result<AllocationResult, TMFError> {
+    if payment.get_status() == Some(STATUS_ALLOCATED) {
+        return Err(TMFError::StateError(format!(
+            "Payment {} has already been allocated",
+            payment.get_id()
+        )));
+    }
+    if payment.get_account().id != account.get_id() {
+        return Err(TMFError::InvalidReference(format!(
+            "Payment is for account {} not {}",
+            payment.get_account().id,
+            account.get_id()
+        )));
+    }
+    if strategy == AllocationStrategy::OldestFirst && !payment.get_items().is_empty() {
+        return Err(TMFError::InvalidReference(format!(
+            "Payment {} has items, allocate it to the bills specified",
+            payment.get_id()
+        )));
+    }
+    let total = payment
+        .get_amount()
+        .cloned()
+        .ok_or(TMFError::NoDataError("Payment has no amount".to_string()))?;
+    let mut working = bills.to_vec();
+    let order: Vec<(usize, Money)> = match strategy {
+        AllocationStrategy::OldestFirst => {
+            let mut order = vec![];
+            for (i, bill) in working.iter().enumerate() {
+                if !is_outstanding(bill, &total) {
+                    continue;
+                }
+                let date = bill.get_bill_date().map(|d| parse_date(d)).transpose()?;
+                order.push((date.is_none(), date, i));
+            }
+            order.sort();
+            order
+                .into_iter()
+                .map(|(_, _, i)| (i, total.clone()))
+                .collect()
+        }
+        AllocationStrategy::Specified => {
+            let mut requested = Decimal::ZERO;
+            let mut order = vec![];
+            for item in payment.get_items() {
+                let index = find_bill(&working, &item.get_item().id)?;
+                let amount = item.get_amount().ok_or(TMFError::NoDataError(format!(
+                    "Payment item for bill {} has no amount",
+                    item.get_item().id
+                )))?;
+                if amount.unit != total.unit {
+                    return Err(TMFError::CurrencyError(format!(
+                        "Payment item in {} for payment in {}",
+                        amount.unit, total.unit
+                    )));
+                }
+                requested += amount.value;
+                if requested > total.value {
+                    return Err(TMFError::GenericError(
+                        "Payment items exceed the payment amount".to_string(),
+                    ));
+                }
+                order.push((index, amount.clone()));
+            }
+            order
+        }
+    };
+
+    // Each bill is paid up to its limit, out of what is left of the payment
+    let mut remaining = total.clone();
+    let mut allocations = vec![];
+    let mut items = vec![];
+    for (index, limit) in order {
+        if remaining.value.is_zero() {
+            break;
+        }
+        let amount = Money {
+            unit: total.unit.clone(),
+            value: limit.value.min(remaining.value),
+        };
+        let applied = working[index].pay(&amount)?;
+        if applied.value.is_zero() {
+            continue;
+        }
+        remaining.value -= applied.value;
+        items.push(PaymentItem::from_ref(bill_ref(&working[index])).amount(applied.clone()));
+        allocations.push(Allocation {
+            bill_id: working[index].get_id(),
+            amount: applied,
+        });
+    }
+
+    account.post(
+        LedgerEntry::credit(LedgerEntryType::Payment, total).reference(payment.as_entity()),
+    )?;
+    payment.set_items(items);
+    payment.set_status(STATUS_ALLOCATED);
+    bills.clone_from_slice(&working);
+    Ok(AllocationResult {
+        allocations,
+        unallocated: remaining,
+    })
+}
+
+/// Reverse the allocations of `payment` for a refund, see the module documentation for details.
+pub fn refund(
+    refund: &Refund,
+    payment: &mut Payment,
+    bills: &mut [CustomerBill],
+    account: &mut BillingAccount,
+) -> Result<AllocationResult, TMFError> {
+    if let Some(refunded) = refund.get_payment() {
+        if refunded.id != payment.get_id() {
+            return Err(TMFError::InvalidReference(format!(
+                "Refund is for payment {} not {}",
+                refunded.id,
+                payment.get_id()
+            )));
+        }
+    }
+    if payment.get_status() != Some(STATUS_ALLOCATED) {
+        return Err(TMFError::StateError(format!(
+            "Payment {} has not been allocated",
+            payment.get_id()
+        )));
+    }
+    let total = refund
+        .get_amount()
+        .cloned()
+        .ok_or(TMFError::NoDataError("Refund has no amount".to_string()))?;
+    let paid = payment
+        .get_amount()
+        .ok_or(TMFError::NoDataError("Payment has no amount".to_string()))?;
+    if paid.unit != total.unit {
+        return Err(TMFError::CurrencyError(format!(
+            "Refund in {} for payment in {}",
+            total.unit, paid.unit
+        )));
+    }
+    // Earlier refunds are recorded as items with negative amounts
+    let refunded: Decimal = payment
+        .get_items()
+        .iter()
+        .filter_map(|i| i.get_amount())
+        .filter(|a| a.value < Decimal::ZERO)
+        .map(|a| -a.value)
+        .sum();
+    let refundable = paid.value - refunded;
+    if total.value > refundable {
+        return Err(TMFError::GenericError(format!(
+            "Refund of {} exceeds the {refundable} of payment {} not yet refunded",
+            total.value,
+            payment.get_id()
+        )));
+    }
+
+    let mut working = bills.to_vec();
+    let mut items = payment.get_items().to_vec();
+    let mut remaining = total.clone();
+    let mut allocations = vec![];
+    for item in items.iter_mut().rev() {
+        if remaining.value.is_zero() {
+            break;
+        }
+        let paid = match item.get_amount() {
+            Some(paid) if paid.value > Decimal::ZERO => paid.value,
+            _ => continue,
+        };
+        let index = find_bill(&working, &item.get_item().id)?;
+        let amount = Money {
+            unit: remaining.unit.clone(),
+            value: remaining.value.min(paid),
+        };
+        let reversed = working[index].reverse_payment(&amount)?;
+        remaining.value -= reversed.value;
+        *item = PaymentItem::from_ref(item.get_item().clone()).amount(Money {
+            unit: remaining.unit.clone(),
+            value: paid - reversed.value,
+        });
+        allocations.push(Allocation {
+            bill_id: working[index].get_id(),
+            amount: reversed,
+        });
+    }
+    items.retain(|i| !matches!(i.get_amount(), Some(a) if a.value.is_zero()));
+    items.push(PaymentItem::from_ref(refund.as_entity()).amount(Money {
+        unit: total.unit.clone(),
+        value: -total.value,
+    }));
+
+    account
+        .post(LedgerEntry::debit(LedgerEntryType::Refund, total).reference(refund.as_entity()))?;
+    payment.set_items(items);
+    bills.clone_from_slice(&working);
+    Ok(AllocationResult {
+        allocations,
+        unallocated: remaining,
+    })
+}
+
+fn is_outstanding(bill: &CustomerBill, payment: &Money) -> bool {
+    matches!(
+        bill.get_state(),
+        CustomerBillStateType::Sent | CustomerBillStateType::PartialPaid
+    ) && bill.get_remaining_amount().unit == payment.unit
+        && bill.get_remaining_amount().value > Decimal::ZERO
+}
+
+fn find_bill(bills: &[CustomerBill], id: &str) -> Result<usize, TMFError> {
+    bills
+        .iter()
+        .position(|b| b.get_id() == id)
+        .ok_or(TMFError::InvalidReference(format!("Bill {id} not found")))
+}
+
+fn parse_date(date: &str) -> Result<chrono::DateTime<chrono::FixedOffset>, TMFError> {
+    chrono::DateTime::parse_from_rfc3339(date)
+        .map_err(|e| TMFError::GenericError(format!("Invalid bill date {date}: {e}")))
+}
+
+fn bill_ref(bill: &CustomerBill) -> EntityRef {
+    EntityRef {
+        href: bill.get_href(),
+        id: bill.get_id(),
+        name: bill.get_bill_no().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tmf666::{AccountRef, BALANCE_RECEIVABLE};
+    use crate::tmf676::PaymentMethodRefOrValue;
+
+    fn sent_bill(amount: i32, date: &str) -> CustomerBill {
+        let mut bill = CustomerBill::new()
+            .amount_due(Money::from(amount))
+            .bill_date(date);
+        bill.set_state(CustomerBillStateType::Validated).unwrap();
+        bill.set_state(CustomerBillStateType::Sent).unwrap();
+        bill
+    }
+
+    fn new_payment(account: &BillingAccount, amount: i32) -> Payment {
+        Payment::new(
+            PaymentMethodRefOrValue::default(),
+            AccountRef::from(account.clone()),
+        )
+        .amount(Money::from(amount))
+    }
+
+    #[test]
+    fn test_allocate_oldest_first() {
+        let mut account = BillingAccount::new("Account");
+        let mut bills = vec![
+            sent_bill(50, "2024-02-01T00:00:00+00:00"),
+            sent_bill(30, "2024-01-01T00:00:00+00:00"),
+        ];
+        let mut payment = new_payment(&account, 100);
+
+        let result = allocate(
+            &mut payment,
+            &mut bills,
+            &mut account,
+            AllocationStrategy::OldestFirst,
+        )
+        .unwrap();
+
+        assert_eq!(result.allocations[0].bill_id, bills[1].get_id());
+        assert_eq!(bills[0].get_state(), CustomerBillStateType::Settled);
+        assert_eq!(bills[1].get_state(), CustomerBillStateType::Settled);
+        assert_eq!(result.unallocated, Money::from(20));
+        assert_eq!(payment.get_items().len(), 2);
+        assert_eq!(
+            account
+                .get_balance(BALANCE_RECEIVABLE)
+                .unwrap()
+                .get_amount(),
+            Some(&Money::from(-100))
+        );
+    }
+
+    #[test]
+    fn test_allocate_specified() {
+        let mut account = BillingAccount::new("Account");
+        let mut bills = vec![
+            sent_bill(50, "2024-01-01T00:00:00+00:00"),
+            sent_bill(30, "2024-02-01T00:00:00+00:00"),
+        ];
+        let target = bill_ref(&bills[1]);
+        let mut payment = new_payment(&account, 40)
+            .item(PaymentItem::from_ref(target.clone()).amount(Money::from(20)));
+
+        let result = allocate(
+            &mut payment,
+            &mut bills,
+            &mut account,
+            AllocationStrategy::Specified,
+        )
+        .unwrap();
+
+        assert_eq!(bills[0].get_state(), CustomerBillStateType::Sent);
+        assert_eq!(bills[1].get_remaining_amount(), &Money::from(10));
+        assert_eq!(result.unallocated, Money::from(20));
+
+        // Unknown bills leave all bills untouched
+        let mut payment = new_payment(&account, 10).item(
+            PaymentItem::from_ref(EntityRef {
+                id: "unknown".to_string(),
+                ..Default::default()
+            })
+            .amount(Money::from(10)),
+        );
+        assert!(allocate(
+            &mut payment,
+            &mut bills,
+            &mut account,
+            AllocationStrategy::Specified
+        )
+        .is_err());
+        assert_eq!(bills[1].get_remaining_amount(), &Money::from(10));
+    }
+
+    #[test]
+    fn test_refund() {
+        let mut account = BillingAccount::new("Account");
+        let mut bills = vec![
+            sent_bill(50, "2024-01-01T00:00:00+00:00"),
+            sent_bill(30, "2024-02-01T00:00:00+00:00"),
+        ];
+        let mut payment = new_payment(&account, 80);
+        allocate(
+            &mut payment,
+            &mut bills,
+            &mut account,
+            AllocationStrategy::OldestFirst,
+        )
+        .unwrap();
+
+        let request = Refund::new(PaymentMethodRefOrValue::default(), AccountRef::default())
+            .amount(Money::from(40))
+            .payment(&payment);
+        let result = refund(&request, &mut payment, &mut bills, &mut account).unwrap();
+
+        assert_eq!(bills[1].get_state(), CustomerBillStateType::Sent);
+        assert_eq!(bills[0].get_state(), CustomerBillStateType::PartialPaid);
+        assert_eq!(bills[0].get_remaining_amount(), &Money::from(10));
+        assert_eq!(result.allocations.len(), 2);
+        assert_eq!(
+            account
+                .get_balance(BALANCE_RECEIVABLE)
+                .unwrap()
+                .get_amount(),
+            Some(&Money::from(-40))
+        );
+    }
+
+    #[test]
+    fn test_refund_twice() {
+        let mut account = BillingAccount::new("Account");
+        let mut bills = vec![sent_bill(50, "2024-01-01T00:00:00+00:00")];
+        let mut payment = new_payment(&account, 80);
+        allocate(
+            &mut payment,
+            &mut bills,
+            &mut account,
+            AllocationStrategy::OldestFirst,
+        )
+        .unwrap();
+        // Another payment settles the rest of a second bill
+        bills.push(sent_bill(40, "2024-02-01T00:00:00+00:00"));
+        allocate(
+            &mut new_payment(&account, 40),
+            &mut bills,
+            &mut account,
+            AllocationStrategy::OldestFirst,
+        )
+        .unwrap();
+
+        let request = Refund::new(PaymentMethodRefOrValue::default(), AccountRef::default())
+            .amount(Money::from(60))
+            .payment(&payment);
+        let result = refund(&request, &mut payment, &mut bills, &mut account).unwrap();
+        assert_eq!(result.unallocated, Money::from(10));
+        assert_eq!(bills[0].get_state(), CustomerBillStateType::Sent);
+
+        // Only 20 of the payment is left to refund, and nothing paid by it remains on a bill
+        assert!(refund(&request, &mut payment, &mut bills, &mut account).is_err());
+        let request = Refund::new(PaymentMethodRefOrValue::default(), AccountRef::default())
+            .amount(Money::from(20))
+            .payment(&payment);
+        let result = refund(&request, &mut payment, &mut bills, &mut account).unwrap();
+        assert!(result.allocations.is_empty());
+        assert_eq!(bills[1].get_state(), CustomerBillStateType::Settled);
+        assert_eq!(
+            account
+                .get_balance(BALANCE_RECEIVABLE)
+                .unwrap()
+                .get_amount(),
+            Some(&Money::from(-40))
+        );
+    }
+
+    #[test]
+    fn test_allocate_oldest_first_offsets() {
+        let mut account = BillingAccount::new("Account");
+        // The second bill is earlier despite sorting later as text
+        let mut bills = vec![
+            sent_bill(10, "2024-01-01T09:00:00+00:00"),
+            sent_bill(10, "2024-01-01T10:00:00+05:00"),
+        ];
+        let mut payment = new_payment(&account, 10);
+
+        let result = allocate(
+            &mut payment,
+            &mut bills,
+            &mut account,
+            AllocationStrategy::OldestFirst,
+        )
+        .unwrap();
+
+        assert_eq!(result.allocations[0].bill_id, bills[1].get_id());
+    }
+
+    #[test]
+    fn test_allocate_twice() {
+        let mut account = BillingAccount::new("Account");
+        let mut bills = vec![
+            sent_bill(50, "2024-01-01T00:00:00+00:00"),
+            sent_bill(50, "2024-02-01T00:00:00+00:00"),
+        ];
+        let mut payment = new_payment(&account, 50);
+
+        allocate(
+            &mut payment,
+            &mut bills,
+            &mut account,
+            AllocationStrategy::OldestFirst,
+        )
+        .unwrap();
+        let result = allocate(
+            &mut payment,
+            &mut bills,
+            &mut account,
+            AllocationStrategy::OldestFirst,
+        );
+
+        assert!(matches!(result, Err(TMFError::StateError(_))));
+        assert_eq!(bills[1].get_state(), CustomerBillStateType::Sent);
+        assert_eq!(
+            account
+                .get_balance(BALANCE_RECEIVABLE)
+                .unwrap()
+                .get_amount(),
+            Some(&Money::from(-50))
+        );
+    }
+
+    #[test]
+    fn test_allocate_checks() {
+        let mut account = BillingAccount::new("Account");
+        let mut bills = vec![sent_bill(50, "2024-01-01T00:00:00+00:00")];
+
+        // Payment made to another account
+        let mut payment = new_payment(&BillingAccount::new("Other"), 50);
+        assert!(matches!(
+            allocate(
+                &mut payment,
+                &mut bills,
+                &mut account,
+                AllocationStrategy::OldestFirst
+            ),
+            Err(TMFError::InvalidReference(_))
+        ));
+
+        // Items are only used when allocating to the bills specified
+        let mut payment = new_payment(&account, 50)
+            .item(PaymentItem::from_ref(bill_ref(&bills[0])).amount(Money::from(10)));
+        assert!(allocate(
+            &mut payment,
+            &mut bills,
+            &mut account,
+            AllocationStrategy::OldestFirst
+        )
+        .is_err());
+
+        // A payment that was never allocated cannot be refunded
+        let mut payment = new_payment(&account, 50);
+        let request = Refund::new(PaymentMethodRefOrValue::default(), AccountRef::default())
+            .amount(Money::from(10))
+            .payment(&payment);
+        assert!(matches!(
+            refund(&request, &mut payment, &mut bills, &mut account),
+            Err(TMFError::StateError(_))
+        ));
+        assert_eq!(bills[0].get_state(), CustomerBillStateType::Sent);
+    }
+}
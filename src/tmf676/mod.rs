@@ -22,10 +22,15 @@ const MOD_PATH: &str = "paymentManagement/v5";
 // For payment method
 const CLASS_PATH: &str = "method";
 
+#[cfg(feature = "tmf678")]
+pub mod allocation;
 pub mod payment;
+pub mod payment_method;
 pub mod refund;
 
+use crate::common::tmf_error::TMFError;
 use crate::{HasDescription, HasId, HasName, Uri};
+use payment_method::{PaymentMethodDetails, TokenVault};
 use serde::{Deserialize, Serialize};
 use tmflib_derive::{HasDescription, HasId, HasName};
 
@@ -36,4 +41,27 @@ pub struct PaymentMethodRefOrValue {
     href: Option<Uri>,
     name: Option<String>,
     description: Option<String>,
+    /// Payment method details, masked when displayed or serialized
+    #[serde(
+        flatten,
+        deserialize_with = "payment_method::details",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub details: Option<PaymentMethodDetails>,
+}
+
+impl PaymentMethodRefOrValue {
+    /// Builder pattern to set the payment method details
+    pub fn details(mut self, details: PaymentMethodDetails) -> PaymentMethodRefOrValue {
+        self.details = Some(details);
+        self
+    }
+
+    /// Tokenise the payment method details, see [`PaymentMethodDetails::tokenise`]
+    pub fn tokenise(&mut self, vault: &mut impl TokenVault) -> Result<String, TMFError> {
+        self.details
+            .as_mut()
+            .ok_or(TMFError::NoDataError("Payment method details".to_string()))?
+            .tokenise(vault)
+    }
 }
@@ -6,7 +6,7 @@ use crate::common::related_entity::EntityRef;
 use crate::common::related_party::RelatedParty;
 use crate::tmf666::AccountRef;
 use crate::tmf676::PaymentMethodRefOrValue;
-use crate::{vec_insert, DateTime, HasDescription, HasId, HasName, Uri};
+use crate::{now, vec_insert, DateTime, HasDescription, HasId, HasName, Uri};
 use serde::{Deserialize, Serialize};
 
 use tmflib_derive::{HasDescription, HasId, HasName};
@@ -34,21 +34,38 @@ impl PaymentItem {
         }
     }
 
+    /// Create new Payment Item from an entity reference, e.g. a bill
+    pub fn from_ref(item: EntityRef) -> PaymentItem {
+        PaymentItem {
+            item,
+            ..Default::default()
+        }
+    }
+
     /// Set the amount for this transaction
-    pub fn amount(mut self, amount: f32) -> PaymentItem {
-        self.amount = Some(Money::from(amount));
+    pub fn amount(mut self, amount: Money) -> PaymentItem {
+        self.amount = Some(amount);
         self
     }
 
     /// Set the tax amount for this payment
-    pub fn tax(mut self, tax: f32) -> PaymentItem {
-        let tax = Money::from(tax);
+    pub fn tax(mut self, tax: Money) -> PaymentItem {
         self.tax_amount = Some(tax.clone());
         if let Some(amount) = self.amount.clone() {
             self.total_amount = Some(amount + tax);
         };
         self
     }
+
+    /// Get the entity this item pays for
+    pub fn get_item(&self) -> &EntityRef {
+        &self.item
+    }
+
+    /// Get the amount paid, the total amount if set otherwise the amount
+    pub fn get_amount(&self) -> Option<&Money> {
+        self.total_amount.as_ref().or(self.amount.as_ref())
+    }
 }
 
 /// A Payment
@@ -114,20 +131,50 @@ impl Payment {
     }
 
     /// Set the amount for this transaction
-    pub fn amount(mut self, amount: f32) -> Payment {
-        self.amount = Some(Money::from(amount));
+    pub fn amount(mut self, amount: Money) -> Payment {
+        self.amount = Some(amount);
         self
     }
 
     /// Set the tax amount for this payment
-    pub fn tax(mut self, tax: f32) -> Payment {
-        let tax = Money::from(tax);
+    pub fn tax(mut self, tax: Money) -> Payment {
         self.tax_amount = Some(tax.clone());
         if let Some(amount) = self.amount.clone() {
             self.total_amount = Some(amount + tax);
         };
         self
     }
+
+    /// Get the amount paid, the total amount if set otherwise the amount
+    pub fn get_amount(&self) -> Option<&Money> {
+        self.total_amount.as_ref().or(self.amount.as_ref())
+    }
+
+    /// Get the account this payment was made to
+    pub fn get_account(&self) -> &AccountRef {
+        &self.account
+    }
+
+    /// Get the payment items
+    pub fn get_items(&self) -> &[PaymentItem] {
+        self.payment_item.as_deref().unwrap_or_default()
+    }
+
+    /// Get the payment status
+    pub fn get_status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// Set the payment status, recording when it changed
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = Some(status.into());
+        self.status_date = Some(now());
+    }
+
+    /// Replace the payment items, e.g. with the result of allocating this payment
+    pub fn set_items(&mut self, items: Vec<PaymentItem>) {
+        self.payment_item = Some(items);
+    }
 }
 
 #[cfg(test)]
@@ -163,7 +210,9 @@ mod test {
         let account = AccountRef::default();
         let payer = Individual::new("John Quinton Smith");
         let product1 = Product::new("Mobile Phone");
-        let item1 = PaymentItem::new(product1).amount(100.0).tax(10.0);
+        let item1 = PaymentItem::new(product1)
+            .amount(Money::from(100))
+            .tax(Money::from(10));
         let payment = Payment::new(method, account).payer(&payer).item(item1);
 
         assert!(payment.payment_item.is_some());
@@ -174,7 +223,9 @@ mod test {
     fn test_payment_amount() {
         let method = PaymentMethodRefOrValue::default().name("Credit Card");
         let account = AccountRef::default();
-        let payment = Payment::new(method, account).amount(100.0).tax(10.0);
+        let payment = Payment::new(method, account)
+            .amount(Money::from(100.0))
+            .tax(Money::from(10.0));
 
         assert!(payment.amount.is_some());
         assert!(payment.tax_amount.is_some());
@@ -0,0 +1,721 @@
+//! Payment Method Details Module
+//!
+//! Typed payment method details that keep card numbers, IBANs and voucher codes out of logs.
+//! - Sensitive values are held in [`Sensitive`], which only shows the last few characters via
+//!   `Debug` and `Display`, see [`mask`]. The clear value is available via [`Sensitive::expose`]
+//!   until the value is tokenised.
+//! - Sensitive values are masked when serialized, unless serialized inside
+//!   [`serialize_with`] using [`SerializeMode::Clear`], e.g. to send them to a payment gateway.
+//! - Card numbers are checked with the Luhn algorithm, IBANs with the ISO 7064 mod-97 check, both
+//!   when created and when clear values are deserialized.
+//! - Details are tokenised via a [`TokenVault`], which swaps the clear value for a token that
+//!   can later be exchanged for the clear value by the vault. [`MemoryVault`] keeps tokens in
+//!   memory, e.g. for testing.
+//! - Payment method types without typed details, e.g. `Cash` or `TokenizedCard`, are kept as
+//!   [`PaymentMethodDetails::Other`] with their raw JSON.
+//! ```
+//! use tmflib::tmf676::payment_method::{BankCard, MemoryVault, PaymentMethodDetails};
+//!
+//! let card = BankCard::new("4111 1111 1111 1111", "2030-12").unwrap();
+//! let mut details = PaymentMethodDetails::BankCard(card);
+//! assert!(!format!("{details:?}").contains("4111111111111111"));
+//!
+//! let mut vault = MemoryVault::new();
+//! let token = details.tokenise(&mut vault).unwrap();
+//! assert_eq!(details.detokenise(&vault).unwrap(), "4111111111111111");
+//! assert!(token.starts_with("tok_"));
+//! ```
+
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::common::money::Money;
+use crate::common::tmf_error::TMFError;
+use crate::DateTime;
+
+/// Character used to hide sensitive values
+pub const MASK_CHAR: char = '*';
+const VISIBLE_CHARS: usize = 4;
+const TOKEN_PREFIX: &str = "tok_";
+/// Types of a payment method reference, which has no details
+const REFERENCE_TYPES: [&str; 3] = [
+    "PaymentMethod",
+    "PaymentMethodRef",
+    "PaymentMethodRefOrValue",
+];
+/// Types with typed details, including the names used before the TMF676 names were adopted
+const DETAIL_TYPES: [&str; 7] = [
+    "BankCard",
+    "BankAccountTransfer",
+    "BankAccount",
+    "BankAccountDebit",
+    "DirectDebit",
+    "Voucher",
+    "DigitalWallet",
+];
+
+thread_local! {
+    static SERIALIZE_MODE: Cell<SerializeMode> = const { Cell::new(SerializeMode::Masked) };
+}
+
+/// How sensitive values are serialized
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SerializeMode {
+    /// Masked value only
+    #[default]
+    Masked,
+    /// Clear value if held, otherwise the masked value
+    Clear,
+}
+
+/// Run `f`, e.g. a call to `serde_json::to_string`, serializing sensitive values using `mode`
+pub fn serialize_with<T>(mode: SerializeMode, f: impl FnOnce() -> T) -> T {
+    struct Restore(SerializeMode);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SERIALIZE_MODE.with(|m| m.set(self.0));
+        }
+    }
+    let _restore = Restore(SERIALIZE_MODE.with(|m| m.replace(mode)));
+    f()
+}
+
+/// Sensitive value, masked for display and serialization.
+/// A masked value (e.g. one read back from a serialized payment method) has no clear value.
+#[derive(Clone, Default, PartialEq)]
+pub struct Sensitive {
+    clear: Option<String>,
+    masked: String,
+}
+
+impl Sensitive {
+    /// Create a new sensitive value
+    pub fn new(value: impl Into<String>) -> Sensitive {
+        let value = value.into();
+        Sensitive {
+            masked: mask(&value),
+            clear: Some(value),
+        }
+    }
+
+    /// Get the clear value, if it has not been masked or tokenised
+    pub fn expose(&self) -> Option<&str> {
+        self.clear.as_deref()
+    }
+
+    /// Get the masked value
+    pub fn masked(&self) -> &str {
+        &self.masked
+    }
+
+    /// Returns true if the clear value is no longer held
+    pub fn is_masked(&self) -> bool {
+        self.clear.is_none()
+    }
+
+    /// Drop the clear value, keeping only the masked value
+    pub fn forget(&mut self) {
+        self.clear = None;
+    }
+}
+
+impl std::fmt::Debug for Sensitive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{}\"", self.masked)
+    }
+}
+
+impl std::fmt::Display for Sensitive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.masked)
+    }
+}
+
+impl Serialize for Sensitive {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (SERIALIZE_MODE.with(|m| m.get()), self.clear.as_ref()) {
+            (SerializeMode::Clear, Some(clear)) => serializer.serialize_str(clear),
+            _ => serializer.serialize_str(&self.masked),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Sensitive {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        // Only a value in the exact format produced by mask() is treated as masked
+        Ok(match value.contains(MASK_CHAR) && mask(&value) == value {
+            true => Sensitive {
+                clear: None,
+                masked: value,
+            },
+            false => Sensitive::new(value),
+        })
+    }
+}
+
+/// Mask all but the last four characters of a value. Short values show fewer characters, so that
+/// at least three quarters of a value is always masked.
+pub fn mask(value: &str) -> String {
+    let count = value.chars().count();
+    let visible = VISIBLE_CHARS.min(count / 4);
+    value
+        .chars()
+        .enumerate()
+        .map(|(i, c)| match i + visible < count {
+            true => MASK_CHAR,
+            false => c,
+        })
+        .collect()
+}
+
+/// Check a card number using the Luhn algorithm. Spaces and dashes are ignored.
+pub fn luhn_valid(number: &str) -> bool {
+    let digits: Vec<u32> = match number
+        .chars()
+        .filter(|c| *c != ' ' && *c != '-')
+        .map(|c| c.to_digit(10))
+        .collect::<Option<_>>()
+    {
+        Some(d) => d,
+        None => return false,
+    };
+    if !(12..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| match i % 2 {
+            1 if *d > 4 => d * 2 - 9,
+            1 => d * 2,
+            _ => *d,
+        })
+        .sum();
+    // Valid numbers have a checksum ending in zero
+    let check = sum % 10;
+    check == 0
+}
+
+/// Check an IBAN using the ISO 7064 mod-97 check. Spaces are ignored.
+pub fn iban_valid(iban: &str) -> bool {
+    let iban: String = iban
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if !(15..=34).contains(&iban.len())
+        || !iban.chars().take(2).all(|c| c.is_ascii_alphabetic())
+        || !iban.chars().skip(2).take(2).all(|c| c.is_ascii_digit())
+    {
+        return false;
+    }
+    let mut remainder = 0u32;
+    for c in iban.chars().skip(4).chain(iban.chars().take(4)) {
+        let value = match c.to_digit(36) {
+            Some(v) => v,
+            None => return false,
+        };
+        remainder = match value {
+            0..=9 => (remainder * 10 + value) % 97,
+            _ => (remainder * 100 + value) % 97,
+        };
+    }
+    remainder == 1
+}
+
+/// Bank card details
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BankCard {
+    /// Card number
+    #[serde(deserialize_with = "card_number")]
+    pub card_number: Sensitive,
+    /// Expiry, e.g. 2030-12
+    pub expiration_date: String,
+    /// Card brand, e.g. Visa
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brand: Option<String>,
+    /// Name on the card
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_on_card: Option<String>,
+    /// Token for the card number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl BankCard {
+    /// Create new card details, checking the card number
+    pub fn new(
+        card_number: impl Into<String>,
+        expiration_date: impl Into<String>,
+    ) -> Result<BankCard, TMFError> {
+        Ok(BankCard {
+            card_number: Sensitive::new(normalise_card(&card_number.into())?),
+            expiration_date: expiration_date.into(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Bank account details, for payment by transfer
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BankAccount {
+    /// International Bank Account Number
+    #[serde(deserialize_with = "iban")]
+    pub iban: Sensitive,
+    /// Bank Identifier Code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bic: Option<String>,
+    /// Name of the account holder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_holder: Option<String>,
+    /// Token for the IBAN
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl BankAccount {
+    /// Create new bank account details, checking the IBAN
+    pub fn new(iban: impl Into<String>) -> Result<BankAccount, TMFError> {
+        Ok(BankAccount {
+            iban: Sensitive::new(normalise_iban(&iban.into())?),
+            ..Default::default()
+        })
+    }
+}
+
+/// Direct debit details, a bank account with a mandate to collect payments
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DirectDebit {
+    /// International Bank Account Number
+    #[serde(deserialize_with = "iban")]
+    pub iban: Sensitive,
+    /// Reference of the mandate signed by the account holder
+    pub mandate_id: String,
+    /// Date the mandate was signed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mandate_date: Option<DateTime>,
+    /// Name of the account holder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_holder: Option<String>,
+    /// Token for the IBAN
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl DirectDebit {
+    /// Create new direct debit details, checking the IBAN
+    pub fn new(
+        iban: impl Into<String>,
+        mandate_id: impl Into<String>,
+    ) -> Result<DirectDebit, TMFError> {
+        Ok(DirectDebit {
+            iban: Sensitive::new(normalise_iban(&iban.into())?),
+            mandate_id: mandate_id.into(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Voucher details
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Voucher {
+    /// Voucher code
+    pub code: Sensitive,
+    /// Value of the voucher
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Money>,
+    /// Expiry of the voucher
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_date: Option<DateTime>,
+    /// Token for the voucher code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl Voucher {
+    /// Create new voucher details
+    pub fn new(code: impl Into<String>) -> Voucher {
+        Voucher {
+            code: Sensitive::new(code),
+            ..Default::default()
+        }
+    }
+}
+
+/// Digital wallet details
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DigitalWallet {
+    /// Wallet service, e.g. PayPal
+    pub service: String,
+    /// Id of the wallet within the service
+    pub wallet_id: String,
+}
+
+impl DigitalWallet {
+    /// Create new digital wallet details
+    pub fn new(service: impl Into<String>, wallet_id: impl Into<String>) -> DigitalWallet {
+        DigitalWallet {
+            service: service.into(),
+            wallet_id: wallet_id.into(),
+        }
+    }
+}
+
+/// Payment method details, by type
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "@type")]
+pub enum PaymentMethodDetails {
+    /// Credit or debit card
+    BankCard(BankCard),
+    /// Bank transfer
+    #[serde(alias = "BankAccount")]
+    BankAccountTransfer(BankAccount),
+    /// Direct debit from a bank account
+    #[serde(alias = "DirectDebit")]
+    BankAccountDebit(DirectDebit),
+    /// Voucher
+    Voucher(Voucher),
+    /// Digital wallet
+    DigitalWallet(DigitalWallet),
+    /// Any other payment method type, e.g. Cash, as raw JSON including its `@type`
+    #[serde(untagged, skip_deserializing)]
+    Other(serde_json::Value),
+}
+
+impl PaymentMethodDetails {
+    /// Swap the sensitive value for a token from `vault`, returning the token.
+    /// Details that are already tokenised return the existing token.
+    pub fn tokenise(&mut self, vault: &mut impl TokenVault) -> Result<String, TMFError> {
+        let (value, token) = match self.sensitive_mut() {
+            Some(s) => s,
+            None => {
+                return Err(TMFError::PaymentMethodError(
+                    "Payment method has nothing to tokenise".to_string(),
+                ))
+            }
+        };
+        if let Some(token) = token.as_ref() {
+            return Ok(token.clone());
+        }
+        let clear = value.expose().ok_or(TMFError::PaymentMethodError(
+            "Payment method is masked and cannot be tokenised".to_string(),
+        ))?;
+        let new_token = vault.tokenise(clear)?;
+        value.forget();
+        *token = Some(new_token.clone());
+        Ok(new_token)
+    }
+
+    /// Get the clear sensitive value, from `vault` if the details have been tokenised
+    pub fn detokenise(&self, vault: &impl TokenVault) -> Result<String, TMFError> {
+        let (value, token) = match self {
+            PaymentMethodDetails::BankCard(c) => (&c.card_number, &c.token),
+            PaymentMethodDetails::BankAccountTransfer(a) => (&a.iban, &a.token),
+            PaymentMethodDetails::BankAccountDebit(d) => (&d.iban, &d.token),
+            PaymentMethodDetails::Voucher(v) => (&v.code, &v.token),
+            PaymentMethodDetails::DigitalWallet(w) => return Ok(w.wallet_id.clone()),
+            PaymentMethodDetails::Other(_) => {
+                return Err(TMFError::PaymentMethodError(
+                    "Payment method has no sensitive value".to_string(),
+                ))
+            }
+        };
+        match (value.expose(), token) {
+            (Some(clear), _) => Ok(clear.to_string()),
+            (None, Some(token)) => vault.detokenise(token),
+            (None, None) => Err(TMFError::PaymentMethodError(
+                "Payment method is masked and has no token".to_string(),
+            )),
+        }
+    }
+
+    fn sensitive_mut(&mut self) -> Option<(&mut Sensitive, &mut Option<String>)> {
+        match self {
+            PaymentMethodDetails::BankCard(c) => Some((&mut c.card_number, &mut c.token)),
+            PaymentMethodDetails::BankAccountTransfer(a) => Some((&mut a.iban, &mut a.token)),
+            PaymentMethodDetails::BankAccountDebit(d) => Some((&mut d.iban, &mut d.token)),
+            PaymentMethodDetails::Voucher(v) => Some((&mut v.code, &mut v.token)),
+            PaymentMethodDetails::DigitalWallet(_) | PaymentMethodDetails::Other(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PaymentMethodDetails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", serde_json::to_string(self).unwrap())
+    }
+}
+
+/// Store exchanging sensitive values for tokens
+pub trait TokenVault {
+    /// Store a sensitive value, returning a token for it
+    fn tokenise(&mut self, value: &str) -> Result<String, TMFError>;
+    /// Get the sensitive value for a token
+    fn detokenise(&self, token: &str) -> Result<String, TMFError>;
+}
+
+/// Token vault held in memory. The same value always gets the same token.
+#[derive(Clone, Default)]
+pub struct MemoryVault {
+    tokens: HashMap<String, String>,
+    values: HashMap<String, String>,
+}
+
+impl std::fmt::Debug for MemoryVault {
+    // Never show the stored clear values
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryVault")
+            .field("tokens", &self.values.len())
+            .finish()
+    }
+}
+
+impl MemoryVault {
+    /// Create an empty vault
+    pub fn new() -> MemoryVault {
+        MemoryVault::default()
+    }
+}
+
+impl TokenVault for MemoryVault {
+    fn tokenise(&mut self, value: &str) -> Result<String, TMFError> {
+        if let Some(token) = self.tokens.get(value) {
+            return Ok(token.clone());
+        }
+        let token = format!("{TOKEN_PREFIX}{}", uuid::Uuid::new_v4().simple());
+        self.tokens.insert(value.to_string(), token.clone());
+        self.values.insert(token.clone(), value.to_string());
+        Ok(token)
+    }
+
+    fn detokenise(&self, token: &str) -> Result<String, TMFError> {
+        self.values
+            .get(token)
+            .cloned()
+            .ok_or(TMFError::PaymentMethodError(format!(
+                "Unknown token {token}"
+            )))
+    }
+}
+
+/// Deserialize payment method details, flattened into a payment method. Payloads without an
+/// `@type`, or typed as a payment method reference, have no details. Types without typed
+/// details are kept as [`PaymentMethodDetails::Other`].
+pub(crate) fn details<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<PaymentMethodDetails>, D::Error> {
+    let fields = serde_json::Map::<String, serde_json::Value>::deserialize(deserializer)?;
+    match fields.get("@type").and_then(|t| t.as_str()) {
+        None => Ok(None),
+        Some(t) if REFERENCE_TYPES.contains(&t) => Ok(None),
+        Some(t) if DETAIL_TYPES.contains(&t) => {
+            serde_json::from_value(serde_json::Value::Object(fields))
+                .map(Some)
+                .map_err(D::Error::custom)
+        }
+        Some(_) => Ok(Some(PaymentMethodDetails::Other(
+            serde_json::Value::Object(fields),
+        ))),
+    }
+}
+
+fn card_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Sensitive, D::Error> {
+    let value = Sensitive::deserialize(deserializer)?;
+    match value.expose() {
+        Some(clear) => normalise_card(clear)
+            .map(Sensitive::new)
+            .map_err(D::Error::custom),
+        None => Ok(value),
+    }
+}
+
+fn iban<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Sensitive, D::Error> {
+    let value = Sensitive::deserialize(deserializer)?;
+    match value.expose() {
+        Some(clear) => normalise_iban(clear)
+            .map(Sensitive::new)
+            .map_err(D::Error::custom),
+        None => Ok(value),
+    }
+}
+
+fn normalise_card(number: &str) -> Result<String, TMFError> {
+    if !luhn_valid(number) {
+        return Err(TMFError::PaymentMethodError(
+            "Card number failed the Luhn check".to_string(),
+        ));
+    }
+    Ok(number.chars().filter(|c| c.is_ascii_digit()).collect())
+}
+
+fn normalise_iban(iban: &str) -> Result<String, TMFError> {
+    if !iban_valid(iban) {
+        return Err(TMFError::PaymentMethodError(
+            "IBAN failed the mod-97 check".to_string(),
+        ));
+    }
+    Ok(iban
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tmf676::PaymentMethodRefOrValue;
+
+    const CARD: &str = "4111111111111111";
+    const IBAN: &str = "GB82 WEST 1234 5698 7654 32";
+
+    #[test]
+    fn test_validation() {
+        assert!(luhn_valid("4111-1111-1111-1111"));
+        assert!(!luhn_valid("4111111111111112"));
+        assert!(!luhn_valid("4111x11111111111"));
+        assert!(iban_valid(IBAN));
+        assert!(iban_valid("DE89370400440532013000"));
+        assert!(!iban_valid("GB82WEST12345698765433"));
+        assert!(BankAccount::new("GB00WEST12345698765432").is_err());
+    }
+
+    #[test]
+    fn test_masking() {
+        let card = PaymentMethodDetails::BankCard(BankCard::new(CARD, "2030-12").unwrap());
+        let json = serde_json::to_string(&card).unwrap();
+
+        assert!(!format!("{card:?}").contains(CARD));
+        assert!(!card.to_string().contains(CARD));
+        assert!(json.contains("\"cardNumber\":\"************1111\""));
+        assert!(json.contains("\"@type\":\"BankCard\""));
+
+        // Masked values read back stay masked
+        let method = PaymentMethodRefOrValue::default().details(card);
+        let json = serde_json::to_string(&method).unwrap();
+        let read: PaymentMethodRefOrValue = serde_json::from_str(&json).unwrap();
+        match read.details {
+            Some(PaymentMethodDetails::BankCard(c)) => assert!(c.card_number.is_masked()),
+            _ => panic!("Expected a bank card"),
+        }
+
+        let reference: PaymentMethodRefOrValue =
+            serde_json::from_str("{ \"name\" : \"Cash\" }").unwrap();
+        assert!(reference.details.is_none());
+        let reference: PaymentMethodRefOrValue =
+            serde_json::from_str("{ \"@type\" : \"PaymentMethodRef\" }").unwrap();
+        assert!(reference.details.is_none());
+    }
+
+    #[test]
+    fn test_other_types() {
+        for kind in ["Cash", "BankAccountDebit", "TokenizedCard"] {
+            let json = format!("{{ \"@type\" : \"{kind}\", \"name\" : \"Pay\" }}");
+            let method: PaymentMethodRefOrValue = serde_json::from_str(&json).unwrap();
+            assert!(method.details.is_some(), "{kind}");
+        }
+
+        let json = "{ \"@type\" : \"TokenizedCard\", \"token\" : \"abc\" }";
+        let method: PaymentMethodRefOrValue = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            method.details,
+            Some(PaymentMethodDetails::Other(_))
+        ));
+        let written: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&method).unwrap()).unwrap();
+        assert_eq!(written["@type"], "TokenizedCard");
+        assert_eq!(written["token"], "abc");
+
+        // Names used before the TMF676 names were adopted are still read
+        let account = format!("{{ \"@type\" : \"BankAccount\", \"iban\" : \"{IBAN}\" }}");
+        let details: PaymentMethodDetails = serde_json::from_str(&account).unwrap();
+        assert!(serde_json::to_string(&details)
+            .unwrap()
+            .contains("\"@type\":\"BankAccountTransfer\""));
+    }
+
+    #[test]
+    fn test_vault_debug() {
+        let mut vault = MemoryVault::new();
+        vault.tokenise(CARD).unwrap();
+
+        assert!(!format!("{vault:?}").contains(CARD));
+    }
+
+    #[test]
+    fn test_masked_format() {
+        let masked: Sensitive = serde_json::from_str("\"************1111\"").unwrap();
+        assert!(masked.is_masked());
+        // A '*' anywhere else is part of a clear value
+        let clear: Sensitive = serde_json::from_str("\"AB*CD\"").unwrap();
+        assert_eq!(clear.expose(), Some("AB*CD"));
+    }
+
+    #[test]
+    fn test_mask_short() {
+        assert_eq!(mask("ABCD"), "***D");
+        assert_eq!(mask("A1B2C3"), "*****3");
+        assert_eq!(mask("ABC"), "***");
+        assert_eq!(mask(CARD), "************1111");
+    }
+
+    #[test]
+    fn test_deserialize_validates() {
+        let card = "{ \"@type\" : \"BankCard\", \"cardNumber\" : \"4111111111111112\", \"expirationDate\" : \"2030-12\" }";
+        assert!(serde_json::from_str::<PaymentMethodDetails>(card).is_err());
+        let card = card.replace("4111111111111112", CARD);
+        assert!(serde_json::from_str::<PaymentMethodDetails>(&card).is_ok());
+
+        let account =
+            "{ \"@type\" : \"BankAccountTransfer\", \"iban\" : \"GB82WEST12345698765433\" }";
+        assert!(serde_json::from_str::<PaymentMethodDetails>(account).is_err());
+        let account = account.replace("GB82WEST12345698765433", IBAN);
+        match serde_json::from_str::<PaymentMethodDetails>(&account).unwrap() {
+            PaymentMethodDetails::BankAccountTransfer(a) => {
+                assert_eq!(a.iban.expose(), Some("GB82WEST12345698765432"))
+            }
+            _ => panic!("Expected a bank account"),
+        }
+    }
+
+    #[test]
+    fn test_serialize_clear() {
+        let card = PaymentMethodDetails::BankCard(BankCard::new(CARD, "2030-12").unwrap());
+
+        let json = serialize_with(SerializeMode::Clear, || serde_json::to_string(&card)).unwrap();
+        assert!(json.contains(CARD));
+        // The mode only applies within serialize_with
+        assert!(!serde_json::to_string(&card).unwrap().contains(CARD));
+
+        let read: PaymentMethodDetails = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, card);
+    }
+
+    #[test]
+    fn test_tokenise() {
+        let mut vault = MemoryVault::new();
+        let mut account =
+            PaymentMethodDetails::BankAccountDebit(DirectDebit::new(IBAN, "MANDATE-1").unwrap());
+
+        let token = account.tokenise(&mut vault).unwrap();
+        let json = serde_json::to_string(&account).unwrap();
+
+        assert!(json.contains(&token));
+        assert!(!json.contains("GB82WEST12345698765432"));
+        assert_eq!(
+            account.detokenise(&vault).unwrap(),
+            "GB82WEST12345698765432"
+        );
+        assert_eq!(account.tokenise(&mut vault).unwrap(), token);
+    }
+}
@@ -1,8 +1,10 @@
 //! TMF676 Refund Module
 
 use crate::common::money::Money;
+use crate::common::related_entity::EntityRef;
 use crate::common::related_party::RelatedParty;
 use crate::tmf666::AccountRef;
+use crate::tmf676::payment::Payment;
 use crate::tmf676::PaymentMethodRefOrValue;
 use crate::{DateTime, HasDescription, HasId, HasName, Uri};
 use serde::{Deserialize, Serialize};
@@ -52,6 +54,8 @@ pub struct Refund {
     requestor: Option<RelatedParty>,
     payment_method: PaymentMethodRefOrValue,
     account: AccountRef,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payment: Option<EntityRef>,
 }
 
 impl Refund {
@@ -71,20 +75,35 @@ impl Refund {
     }
 
     /// Set the amount for this refund
-    pub fn amount(mut self, amount: f32) -> Refund {
-        self.amount = Some(Money::from(amount));
+    pub fn amount(mut self, amount: Money) -> Refund {
+        self.amount = Some(amount);
         self
     }
 
     /// Set the tax amount of this refund
-    pub fn tax(mut self, tax: f32) -> Refund {
-        let tax = Money::from(tax);
+    pub fn tax(mut self, tax: Money) -> Refund {
         self.tax_amount = Some(tax.clone());
         if let Some(amount) = self.amount.clone() {
             self.total_amount = Some(amount + tax);
         };
         self
     }
+
+    /// Set the payment being refunded
+    pub fn payment(mut self, payment: &Payment) -> Refund {
+        self.payment = Some(payment.as_entity());
+        self
+    }
+
+    /// Get the payment being refunded
+    pub fn get_payment(&self) -> Option<&EntityRef> {
+        self.payment.as_ref()
+    }
+
+    /// Get the amount refunded, the total amount if set otherwise the amount
+    pub fn get_amount(&self) -> Option<&Money> {
+        self.total_amount.as_ref().or(self.amount.as_ref())
+    }
 }
 
 #[cfg(test)]
@@ -123,8 +142,8 @@ mod test {
         let party = Individual::new("John Quinton Smith");
         let refund = Refund::new(method, account)
             .requestor(&party)
-            .amount(100.0)
-            .tax(10.0);
+            .amount(Money::from(100.0))
+            .tax(Money::from(10.0));
 
         assert!(refund.amount.is_some());
         assert!(refund.tax_amount.is_some());
@@ -0,0 +1,727 @@
+//! Bill Run Module
+//!
+//! Calculates the charges for a [`BillingAccount`] over a bill period, ready to be turned into a
+//! Customer Bill (V4 or V5) via `CustomerBill::from()`.
+//!
+//! Charges are collected from:
+//! - Recurring prices of active products billed to the account
+//! - One-time prices of products billed to the account that started within the bill period
+//! - Rated usage of products billed to the account that has not yet been billed, within the bill
+//!   period. Usage without a usage date cannot be placed in a period and is skipped.
+//! - One-time charges added to the bill run
+//!
+//! Recurring charges are prorated by the days the product was active, using the product start
+//! and termination dates. The run type selects the period charges are prorated against:
+//! - [`CustomerBillRunType::OnCycle`]: the bill period, normally the account's billing cycle
+//! - [`CustomerBillRunType::OffCycle`]: the account's billing cycle containing the start of the
+//!   bill period, so an off-cycle bill covering part of a cycle charges part of the recurring price
+//!
+//! A recurring price with a recurring charge period (e.g. `year`) is scaled to the period charges
+//! are prorated against, so a yearly price on a monthly bill charges a month of the year.
+//!
+//! All charges must use the same currency. Tax is summed by tax category and rate into tax items.
+//! Once calculated, consumed rated usage is marked as billed, and usage where all rated
+//! usage has been billed moves to [`UsageStatusType::Billed`].
+//!
+//! Bills created from a calculation are issued as `CustomerBillStateType::Validated`, or
+//! `CustomerBillStateType::OnHold` if the bill run was held for review.
+//! ```
+//! # #[cfg(feature = "build-V4")]
+//! # use tmflib::tmf637::v4::product::{Product, ProductPrice};
+//! # #[cfg(feature = "build-V4")]
+//! # use tmflib::tmf678::customer_bill_v4::{CustomerBill, CustomerBillStateType};
+//! # #[cfg(feature = "build-V5")]
+//! # use tmflib::tmf637::v5::product::{Product, ProductPrice};
+//! # #[cfg(feature = "build-V5")]
+//! # use tmflib::tmf678::customer_bill_v5::{CustomerBill, CustomerBillStateType};
+//! use tmflib::common::price::Price;
+//! use tmflib::common::product::ProductStatusType;
+//! use tmflib::tmf666::billing_account::{BillingAccount, BillingAccountRef};
+//! use tmflib::tmf678::bill_run::BillRun;
+//! use tmflib::TimePeriod;
+//!
+//! let account = BillingAccount::new("Customer Account");
+//! let product = Product::new("Broadband")
+//!     .status(ProductStatusType::Active)
+//!     .billing_account(BillingAccountRef::from(account.clone()))
+//!     .price(ProductPrice::new("Monthly", "recurring", Price::new_ex(50.0)));
+//! let period = TimePeriod {
+//!     start_date_time: "2024-01-01T00:00:00+00:00".to_string(),
+//!     end_date_time: Some("2024-02-01T00:00:00+00:00".to_string()),
+//! };
+//!
+//! let calculation = BillRun::new(&account, period)
+//!     .run(&[product], &mut [])
+//!     .unwrap();
+//! let bill = CustomerBill::from(calculation);
+//! assert_eq!(bill.get_state(), CustomerBillStateType::Validated);
+//! ```
+
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::common::money::Money;
+use crate::common::price::Price;
+use crate::common::product::ProductStatusType;
+use crate::common::tax_item::TaxItem;
+use crate::common::tmf_error::TMFError;
+use crate::tmf620::product_offering_price::PriceType;
+use crate::tmf635::{Usage, UsageStatusType};
+#[cfg(feature = "build-V4")]
+use crate::tmf637::v4::product::Product;
+#[cfg(feature = "build-V5")]
+use crate::tmf637::v5::product::Product;
+use crate::tmf666::bill_cycle::{
+    days, overlap, prorate, recurring_period, BillingCycleSpecification,
+};
+use crate::tmf666::billing_account::{BillingAccount, BillingAccountRef};
+#[cfg(feature = "build-V4")]
+use crate::tmf678::customer_bill_v4::CustomerBillRunType;
+#[cfg(feature = "build-V5")]
+use crate::tmf678::customer_bill_v5::CustomerBillRunType;
+use crate::{now, DateTime, HasId, HasName, TimePeriod};
+
+/// Default tax category, matching the pricing engine
+pub const DEFAULT_TAX_CATEGORY: &str = "GST";
+/// Default number of days between the bill date and payment due date
+const DEFAULT_PAYMENT_DAYS: u64 = 14;
+const BILL_DP: u32 = 2;
+
+/// Single charge appearing on a bill
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillCharge {
+    /// Name of the charge
+    pub name: String,
+    /// Type of charge, e.g. recurring, usage or oneTime
+    pub charge_type: String,
+    /// Product being charged for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+    /// Amount excluding tax
+    pub tax_excluded_amount: Money,
+    /// Amount of tax
+    pub tax_amount: Money,
+    /// Tax category, e.g. GST
+    pub tax_category: String,
+    /// Tax rate, e.g. 0.10 for 10%
+    pub tax_rate: f32,
+}
+
+impl BillCharge {
+    /// Create a one-time charge from a price, taxed under [`DEFAULT_TAX_CATEGORY`]
+    pub fn one_time(name: impl Into<String>, price: Price) -> BillCharge {
+        BillCharge::from_price(name, PriceType::OneTime, None, &price, DEFAULT_TAX_CATEGORY)
+    }
+
+    /// Builder pattern to set the tax category
+    pub fn tax_category(mut self, category: impl Into<String>) -> BillCharge {
+        self.tax_category = category.into();
+        self
+    }
+
+    fn from_price(
+        name: impl Into<String>,
+        charge_type: PriceType,
+        product_id: Option<String>,
+        price: &Price,
+        tax_category: &str,
+    ) -> BillCharge {
+        BillCharge {
+            name: name.into(),
+            charge_type: charge_type.to_string(),
+            product_id,
+            tax_excluded_amount: price.duty_free_amount.clone(),
+            tax_amount: price.tax_amount(),
+            tax_category: tax_category.to_string(),
+            tax_rate: price.tax_rate,
+        }
+    }
+
+    /// Amount including tax
+    pub fn tax_included_amount(&self) -> Money {
+        self.tax_excluded_amount.clone() + self.tax_amount.clone()
+    }
+}
+
+/// Result of a bill run for a single billing account
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillCalculation {
+    /// Account being billed
+    pub billing_account: BillingAccountRef,
+    /// Period being billed
+    pub billing_period: TimePeriod,
+    /// Whether the bill is part of the regular bill cycle
+    pub run_type: CustomerBillRunType,
+    /// Date of the bill
+    pub bill_date: DateTime,
+    /// Date payment is due
+    pub payment_due_date: DateTime,
+    /// Individual charges
+    pub charges: Vec<BillCharge>,
+    /// Total excluding tax
+    pub tax_excluded_amount: Money,
+    /// Total including tax
+    pub tax_included_amount: Money,
+    /// Amount the customer has to pay
+    pub amount_due: Money,
+    /// Tax totals by category and rate
+    pub tax_item: Vec<TaxItem>,
+    /// Whether the bill is held for review rather than issued
+    pub on_hold: bool,
+}
+
+/// Bill Run for a single billing account and bill period
+#[derive(Clone, Debug)]
+pub struct BillRun {
+    account: BillingAccountRef,
+    period: TimePeriod,
+    charges: Vec<BillCharge>,
+    payment_days: u64,
+    cycle: Option<BillingCycleSpecification>,
+    run_type: CustomerBillRunType,
+    hold: bool,
+    tax_category: String,
+}
+
+impl BillRun {
+    /// Create a new bill run for an account and bill period
+    pub fn new(account: &BillingAccount, period: TimePeriod) -> BillRun {
+        BillRun {
+            account: BillingAccountRef::from(account.clone()),
+            period,
+            charges: vec![],
+            payment_days: DEFAULT_PAYMENT_DAYS,
+            cycle: account.get_cycle().cloned(),
+            run_type: CustomerBillRunType::OnCycle,
+            hold: false,
+            tax_category: DEFAULT_TAX_CATEGORY.to_string(),
+        }
+    }
+
+    /// Create a new on cycle bill run for the account's billing cycle containing `date`
+    pub fn on_cycle(account: &BillingAccount, date: &str) -> Result<BillRun, TMFError> {
+        let cycle = account.get_cycle().ok_or(TMFError::NoDataError(format!(
+            "Account {} has no billing cycle",
+            account.get_name()
+        )))?;
+        Ok(BillRun::new(account, cycle.period(date)?))
+    }
+
+    /// Builder pattern to set the run type, off cycle bills prorate recurring charges
+    /// against the account's billing cycle
+    pub fn run_type(mut self, run_type: CustomerBillRunType) -> BillRun {
+        self.run_type = run_type;
+        self
+    }
+
+    /// Builder pattern to hold the bill for review instead of issuing it
+    pub fn hold(mut self, hold: bool) -> BillRun {
+        self.hold = hold;
+        self
+    }
+
+    /// Builder pattern to set the tax category of product and usage charges,
+    /// the tax rate is taken from each price or rating.
+    pub fn tax_category(mut self, category: impl Into<String>) -> BillRun {
+        self.tax_category = category.into();
+        self
+    }
+
+    /// Builder pattern to add a one-time charge to this bill run
+    pub fn charge(mut self, charge: BillCharge) -> BillRun {
+        self.charges.push(charge);
+        self
+    }
+
+    /// Builder pattern to set the number of days after the bill date that payment is due
+    pub fn payment_days(mut self, days: u64) -> BillRun {
+        self.payment_days = days;
+        self
+    }
+
+    /// Calculate the bill for the products billed to this account, consuming any unbilled rated
+    /// usage of those products. Usage is only marked as billed if the calculation succeeds.
+    pub fn run(
+        &self,
+        products: &[Product],
+        usage: &mut [Usage],
+    ) -> Result<BillCalculation, TMFError> {
+        let products: Vec<&Product> = products
+            .iter()
+            .filter(|p| {
+                p.get_billing_account()
+                    .is_some_and(|a| a.id == self.account.id)
+            })
+            .collect();
+        let mut charges = vec![];
+        for product in products.iter() {
+            charges.extend(self.product_charges(product)?);
+        }
+        let consumed = self.usage_charges(&products, usage, &mut charges)?;
+        charges.extend(self.charges.iter().cloned());
+
+        let calculation = self.calculate(charges)?;
+
+        for (u, r) in consumed {
+            if let Some(rated) = usage[u].rated_product_usage.as_mut() {
+                rated[r].is_billed = Some(true);
+                if rated.iter().all(|r| r.is_billed == Some(true)) {
+                    usage[u].status = Some(UsageStatusType::Billed);
+                }
+            }
+        }
+        Ok(calculation)
+    }
+
+    /// Period recurring charges are prorated against
+    fn charge_period(&self) -> Result<TimePeriod, TMFError> {
+        match (&self.run_type, &self.cycle) {
+            (CustomerBillRunType::OffCycle, Some(cycle)) => {
+                cycle.period(&self.period.start_date_time)
+            }
+            _ => Ok(self.period.clone()),
+        }
+    }
+
+    fn product_charges(&self, product: &Product) -> Result<Vec<BillCharge>, TMFError> {
+        let started = match product.get_start_date() {
+            Some(d) => in_period(d, &self.period)?,
+            None => false,
+        };
+        let active = TimePeriod {
+            start_date_time: product
+                .get_start_date()
+                .cloned()
+                .unwrap_or(self.period.start_date_time.clone()),
+            end_date_time: product.get_termination_date().cloned(),
+        };
+        let charged = match product.get_status() {
+            ProductStatusType::Active | ProductStatusType::PendingTerminate => {
+                overlap(&active, &self.period)?
+            }
+            ProductStatusType::Terminated if active.end_date_time.is_some() => {
+                overlap(&active, &self.period)?
+            }
+            _ => None,
+        };
+        let mut charges = vec![];
+        for price in product.get_product_price() {
+            let amount = match price.get_price() {
+                Some(a) => a,
+                None => continue,
+            };
+            let name = format!("{} - {}", product.get_name(), price.get_name());
+            match PriceType::try_from(price.get_price_type().as_str()) {
+                Ok(PriceType::Recurring) => {
+                    let charged = match &charged {
+                        Some(c) => c,
+                        None => continue,
+                    };
+                    let period = self.charge_period()?;
+                    let scale = recurring_scale(&period, &price.get_recurring_charge_period())?;
+                    let prorated = Price {
+                        duty_free_amount: prorate(
+                            &scaled(&amount.duty_free_amount, scale),
+                            &period,
+                            charged,
+                        )?,
+                        tax_included_amount: prorate(
+                            &scaled(&amount.tax_included_amount, scale),
+                            &period,
+                            charged,
+                        )?,
+                        ..amount.clone()
+                    };
+                    charges.push(BillCharge::from_price(
+                        name,
+                        PriceType::Recurring,
+                        Some(product.get_id()),
+                        &prorated,
+                        &self.tax_category,
+                    ));
+                }
+                Ok(PriceType::OneTime) if started => {
+                    charges.push(BillCharge::from_price(
+                        name,
+                        PriceType::OneTime,
+                        Some(product.get_id()),
+                        amount,
+                        &self.tax_category,
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(charges)
+    }
+
+    /// Add charges for unbilled rated usage, returning the position of each consumed record
+    fn usage_charges(
+        &self,
+        products: &[&Product],
+        usage: &[Usage],
+        charges: &mut Vec<BillCharge>,
+    ) -> Result<Vec<(usize, usize)>, TMFError> {
+        let mut consumed = vec![];
+        for (u, record) in usage.iter().enumerate() {
+            if !matches!(
+                record.status,
+                Some(UsageStatusType::Rated) | Some(UsageStatusType::Rerated)
+            ) {
+                continue;
+            }
+            match &record.usage_date {
+                Some(date) if in_period(date, &self.period)? => {}
+                _ => continue,
+            }
+            for (r, rated) in record.rated_product_usage.iter().flatten().enumerate() {
+                let product = match rated
+                    .product_ref
+                    .as_ref()
+                    .and_then(|p| products.iter().find(|product| product.get_id() == p.id))
+                {
+                    Some(p) => p,
+                    None => continue,
+                };
+                if rated.is_billed == Some(true) {
+                    continue;
+                }
+                let excluded =
+                    rated
+                        .tax_excluded_rating_amount
+                        .clone()
+                        .ok_or(TMFError::NoDataError(format!(
+                            "Usage {} has no rated amount",
+                            record.id.clone().unwrap_or_default()
+                        )))?;
+                let included = rated
+                    .tax_included_rating_amount
+                    .clone()
+                    .unwrap_or(excluded.clone());
+                check_currency(&excluded, &included)?;
+                charges.push(BillCharge {
+                    name: format!(
+                        "{} - {}",
+                        product.get_name(),
+                        rated.offer_tariff_type.clone().unwrap_or_default()
+                    ),
+                    charge_type: PriceType::Usage.to_string(),
+                    product_id: Some(product.get_id()),
+                    tax_amount: included - excluded.clone(),
+                    tax_excluded_amount: excluded,
+                    tax_category: self.tax_category.clone(),
+                    tax_rate: rated.tax_rate.unwrap_or_default() as f32,
+                });
+                consumed.push((u, r));
+            }
+        }
+        Ok(consumed)
+    }
+
+    fn calculate(&self, charges: Vec<BillCharge>) -> Result<BillCalculation, TMFError> {
+        let zero = match charges.first() {
+            Some(c) => Money {
+                unit: c.tax_excluded_amount.unit.clone(),
+                value: Decimal::ZERO,
+            },
+            None => Money::from(0),
+        };
+        let mut tax_excluded = zero.clone();
+        let mut tax_items: Vec<TaxItem> = vec![];
+        for charge in charges.iter() {
+            check_currency(&zero, &charge.tax_excluded_amount)?;
+            check_currency(&zero, &charge.tax_amount)?;
+            tax_excluded += charge.tax_excluded_amount.clone();
+            let rate = Decimal::from_f32(charge.tax_rate).unwrap_or_default();
+            let item = tax_items.iter_mut().find(|t| {
+                t.tax_category == charge.tax_category
+                    && Decimal::from_f32(t.tax_rate).unwrap_or_default() == rate
+            });
+            match item {
+                Some(t) => t.tax_amount += charge.tax_amount.clone(),
+                None => tax_items.push(TaxItem::new(
+                    charge.tax_category.clone(),
+                    charge.tax_rate,
+                    charge.tax_amount.clone(),
+                )),
+            }
+        }
+        let mut tax = zero.clone();
+        for item in tax_items.iter_mut() {
+            item.tax_amount.value = item.tax_amount.value.round_dp(BILL_DP);
+            tax += item.tax_amount.clone();
+        }
+        tax_excluded.value = tax_excluded.value.round_dp(BILL_DP);
+        let tax_included = tax_excluded.clone() + tax;
+
+        let bill_date = now();
+        let payment_due = match (&self.run_type, &self.cycle) {
+            (CustomerBillRunType::OnCycle, Some(cycle)) => cycle.payment_due_date(&self.period)?,
+            _ => chrono::DateTime::parse_from_rfc3339(&bill_date)
+                .ok()
+                .and_then(|d| d.checked_add_days(chrono::Days::new(self.payment_days)))
+                .ok_or(TMFError::InvalidTimePeriod(format!(
+                    "Cannot add {} payment days to {bill_date}",
+                    self.payment_days
+                )))?
+                .to_rfc3339(),
+        };
+        Ok(BillCalculation {
+            billing_account: self.account.clone(),
+            billing_period: self.period.clone(),
+            run_type: self.run_type.clone(),
+            bill_date,
+            payment_due_date: payment_due,
+            charges,
+            tax_excluded_amount: tax_excluded,
+            amount_due: tax_included.clone(),
+            tax_included_amount: tax_included,
+            tax_item: tax_items,
+            on_hold: self.hold,
+        })
+    }
+}
+
+fn check_currency(lhs: &Money, rhs: &Money) -> Result<(), TMFError> {
+    match lhs.unit == rhs.unit {
+        true => Ok(()),
+        false => Err(TMFError::CurrencyError(format!(
+            "Cannot bill {} with {}",
+            rhs.unit, lhs.unit
+        ))),
+    }
+}
+
+/// Portion of a recurring price charged over `period`, e.g. 1/12 of a yearly price for a month.
+/// A price with no recurring charge period is charged in full each period.
+fn recurring_scale(
+    period: &TimePeriod,
+    recurring_charge_period: &str,
+) -> Result<Decimal, TMFError> {
+    if recurring_charge_period.is_empty() {
+        return Ok(Decimal::ONE);
+    }
+    let price_period = recurring_period(&period.start_date_time, recurring_charge_period)?;
+    Ok(Decimal::from(days(period)?) / Decimal::from(days(&price_period)?))
+}
+
+fn scaled(amount: &Money, scale: Decimal) -> Money {
+    Money {
+        unit: amount.unit.clone(),
+        value: amount.value * scale,
+    }
+}
+
+/// Returns true if the time is within the period, the end of the period is exclusive
+pub(crate) fn in_period(time: &str, period: &TimePeriod) -> Result<bool, TMFError> {
+    let parse = |t: &str| {
+        chrono::DateTime::parse_from_rfc3339(t)
+            .map_err(|e| TMFError::InvalidTimePeriod(format!("Invalid date {t}: {e}")))
+    };
+    let time = parse(time)?;
+    if time < parse(&period.start_date_time)? {
+        return Ok(false);
+    }
+    match &period.end_date_time {
+        Some(end) => Ok(time < parse(end)?),
+        None => Ok(true),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tmf635::{ProductRef, RatedProductUsage};
+    #[cfg(feature = "build-V4")]
+    use crate::tmf637::v4::product::ProductPrice;
+    #[cfg(feature = "build-V5")]
+    use crate::tmf637::v5::product::ProductPrice;
+    use crate::tmf666::bill_cycle::BillingFrequency;
+    #[cfg(feature = "build-V4")]
+    use crate::tmf678::customer_bill_v4::{CustomerBill, CustomerBillStateType};
+    #[cfg(feature = "build-V5")]
+    use crate::tmf678::customer_bill_v5::{CustomerBill, CustomerBillStateType};
+
+    fn period() -> TimePeriod {
+        TimePeriod {
+            start_date_time: "2024-01-01T00:00:00+00:00".to_string(),
+            end_date_time: Some("2024-02-01T00:00:00+00:00".to_string()),
+        }
+    }
+
+    fn usage(product: &Product, date: &str, amount: i32) -> Usage {
+        Usage {
+            status: Some(UsageStatusType::Rated),
+            usage_date: Some(date.to_string()),
+            rated_product_usage: Some(vec![RatedProductUsage {
+                is_billed: Some(false),
+                product_ref: Some(ProductRef {
+                    id: product.get_id(),
+                    ..Default::default()
+                }),
+                tax_excluded_rating_amount: Some(Money::from(amount)),
+                tax_included_rating_amount: Some(Money::from(amount) * 1.1),
+                tax_rate: Some(0.1),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bill_run_totals() {
+        let account = BillingAccount::new("Account");
+        let product = Product::new("Mobile")
+            .status(ProductStatusType::Active)
+            .start_date("2023-06-01T00:00:00+00:00")
+            .billing_account(BillingAccountRef::from(account.clone()))
+            .price(ProductPrice::new("Plan", "recurring", Price::new_ex(30.0)))
+            .price(ProductPrice::new(
+                "Connection",
+                "oneTime",
+                Price::new_ex(99.0),
+            ));
+        let mut usage = vec![
+            usage(&product, "2024-01-10T10:00:00+00:00", 10),
+            usage(&product, "2024-02-10T10:00:00+00:00", 20),
+        ];
+
+        let calc = BillRun::new(&account, period())
+            .charge(BillCharge::one_time("Late Fee", Price::new_ex(5.0)))
+            .run(&[product], &mut usage)
+            .unwrap();
+
+        // Plan, January usage and late fee. Connection was charged on an earlier bill.
+        assert_eq!(calc.charges.len(), 3);
+        assert_eq!(calc.tax_excluded_amount.value, Decimal::from(45));
+        assert_eq!(calc.tax_included_amount.value, Decimal::new(4950, 2));
+        assert_eq!(calc.tax_item.len(), 1);
+        assert_eq!(usage[0].status, Some(UsageStatusType::Billed));
+        assert_eq!(usage[1].status, Some(UsageStatusType::Rated));
+    }
+
+    #[test]
+    fn test_bill_run_prorate() {
+        let account = BillingAccount::new("Account").cycle(BillingCycleSpecification::new(
+            "Monthly",
+            BillingFrequency::Monthly,
+            1,
+        ));
+        let product = Product::new("Mobile")
+            .status(ProductStatusType::Active)
+            .start_date("2024-04-21T09:00:00+00:00")
+            .billing_account(BillingAccountRef::from(account.clone()))
+            .price(ProductPrice::new("Plan", "recurring", Price::new_ex(30.0)));
+        let off_cycle = TimePeriod {
+            start_date_time: "2024-04-01T00:00:00+00:00".to_string(),
+            end_date_time: Some("2024-04-26T00:00:00+00:00".to_string()),
+        };
+        let products = [product];
+
+        let on = BillRun::on_cycle(&account, "2024-04-10T00:00:00+00:00")
+            .unwrap()
+            .run(&products, &mut [])
+            .unwrap();
+        let off = BillRun::new(&account, off_cycle)
+            .run_type(CustomerBillRunType::OffCycle)
+            .run(&products, &mut [])
+            .unwrap();
+
+        // Active 10 of 30 days in the cycle, 5 of 30 days in the off cycle bill
+        assert_eq!(on.tax_excluded_amount.value, Decimal::from(10));
+        assert_eq!(off.tax_excluded_amount.value, Decimal::from(5));
+    }
+
+    #[test]
+    fn test_bill_run_currency() {
+        let account = BillingAccount::new("Account");
+        let mut fee = Price::new_ex(5.0);
+        fee.set_ex_price(5.0, Some("NZD"));
+        let product = Product::new("Mobile")
+            .status(ProductStatusType::Active)
+            .billing_account(BillingAccountRef::from(account.clone()))
+            .price(ProductPrice::new("Plan", "recurring", Price::new_ex(30.0)));
+        let mut usage = vec![usage(&product, "2024-01-10T10:00:00+00:00", 10)];
+
+        let result = BillRun::new(&account, period())
+            .charge(BillCharge::one_time("Fee", fee))
+            .run(&[product], &mut usage);
+
+        assert!(matches!(result, Err(TMFError::CurrencyError(_))));
+        // Usage is not consumed by a failed bill run
+        assert_eq!(usage[0].status, Some(UsageStatusType::Rated));
+    }
+
+    #[test]
+    fn test_bill_run_recurring_period() {
+        let account = BillingAccount::new("Account");
+        let period = TimePeriod {
+            start_date_time: "2023-01-01T00:00:00+00:00".to_string(),
+            end_date_time: Some("2023-02-01T00:00:00+00:00".to_string()),
+        };
+        let product = Product::new("Domain")
+            .status(ProductStatusType::Active)
+            .billing_account(BillingAccountRef::from(account.clone()))
+            .price(
+                ProductPrice::new("Registration", "recurring", Price::new_ex(365.0))
+                    .recurring_charge_period("year"),
+            );
+
+        let calc = BillRun::new(&account, period.clone())
+            .run(std::slice::from_ref(&product), &mut [])
+            .unwrap();
+
+        // 31 days of a 365 day year
+        assert_eq!(calc.tax_excluded_amount.value, Decimal::from(31));
+
+        let bad = product.price(
+            ProductPrice::new("Support", "recurring", Price::new_ex(10.0))
+                .recurring_charge_period("fortnight"),
+        );
+        assert!(matches!(
+            BillRun::new(&account, period).run(&[bad], &mut []),
+            Err(TMFError::InvalidTimePeriod(_))
+        ));
+    }
+
+    #[test]
+    fn test_bill_run_usage() {
+        let account = BillingAccount::new("Account");
+        let product = Product::new("Mobile")
+            .status(ProductStatusType::Active)
+            .billing_account(BillingAccountRef::from(account.clone()));
+        let mut undated = usage(&product, "2024-01-10T10:00:00+00:00", 20);
+        undated.usage_date = None;
+        let mut usage = vec![usage(&product, "2024-01-10T10:00:00+00:00", 10), undated];
+
+        let calc = BillRun::new(&account, period())
+            .tax_category("VAT")
+            .run(&[product], &mut usage)
+            .unwrap();
+
+        assert_eq!(calc.charges.len(), 1);
+        assert_eq!(calc.tax_excluded_amount.value, Decimal::from(10));
+        assert_eq!(calc.tax_item[0].tax_category, "VAT");
+        assert_eq!(usage[1].status, Some(UsageStatusType::Rated));
+    }
+
+    #[test]
+    fn test_bill_run_state() {
+        let account = BillingAccount::new("Account");
+
+        let issued =
+            CustomerBill::from(BillRun::new(&account, period()).run(&[], &mut []).unwrap());
+        let held = CustomerBill::from(
+            BillRun::new(&account, period())
+                .hold(true)
+                .run(&[], &mut [])
+                .unwrap(),
+        );
+
+        assert_eq!(issued.get_state(), CustomerBillStateType::Validated);
+        assert_eq!(held.get_state(), CustomerBillStateType::OnHold);
+    }
+}
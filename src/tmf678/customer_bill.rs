@@ -0,0 +1,240 @@
+//! Customer Bill state machine and calculations shared by V4 and V5
+//!
+
+use crate::common::money::Money;
+use crate::common::tmf_error::TMFError;
+use crate::{DateTime, HasLastUpdate, TimePeriod};
+
+#[cfg(all(
+    feature = "tmf620",
+    feature = "tmf635",
+    feature = "tmf637",
+    feature = "tmf666"
+))]
+use super::bill_run::BillCalculation;
+#[cfg(feature = "build-V4")]
+use super::customer_bill_v4::{CustomerBill, CustomerBillStateType};
+#[cfg(feature = "build-V5")]
+use super::customer_bill_v5::{CustomerBill, CustomerBillStateType};
+#[cfg(all(
+    feature = "tmf620",
+    feature = "tmf635",
+    feature = "tmf637",
+    feature = "tmf666"
+))]
+use crate::HasId;
+
+#[cfg(all(
+    feature = "tmf620",
+    feature = "tmf635",
+    feature = "tmf637",
+    feature = "tmf666"
+))]
+const BILL_CATEGORY: &str = "normal";
+
+impl CustomerBillStateType {
+    /// Returns true if a bill can move from this state to the next state.
+    /// Paid bills can move back to a partially paid or sent state when a payment is refunded.
+    pub fn can_transition(&self, next: &CustomerBillStateType) -> bool {
+        use CustomerBillStateType::*;
+        matches!(
+            (self, next),
+            (New, OnHold)
+                | (New, Validated)
+                | (OnHold, New)
+                | (OnHold, Validated)
+                | (Validated, OnHold)
+                | (Validated, Sent)
+                | (Sent, PartialPaid)
+                | (Sent, Settled)
+                | (PartialPaid, PartialPaid)
+                | (PartialPaid, Settled)
+                | (PartialPaid, Sent)
+                | (Settled, PartialPaid)
+                | (Settled, Sent)
+        )
+    }
+}
+
+impl CustomerBill {
+    /// Builder pattern to set the amount due, which is also the amount remaining to be paid
+    pub fn amount_due(mut self, amount: Money) -> CustomerBill {
+        self.remaining_amount = amount.clone();
+        self.amount_due = Some(amount);
+        self
+    }
+
+    /// Builder pattern to set the date of the bill
+    pub fn bill_date(mut self, date: impl Into<DateTime>) -> CustomerBill {
+        self.bill_date = Some(date.into());
+        self
+    }
+
+    /// Move this bill to a new state, checking the transition is allowed
+    pub fn set_state(&mut self, state: CustomerBillStateType) -> Result<(), TMFError> {
+        let current = self.state.clone().unwrap_or_default();
+        if !current.can_transition(&state) {
+            return Err(TMFError::StateError(format!(
+                "Cannot move bill from {current:?} to {state:?}"
+            )));
+        }
+        self.state = Some(state);
+        self.set_last_update(Self::get_timestamp());
+        Ok(())
+    }
+
+    /// Get the current state of this bill
+    pub fn get_state(&self) -> CustomerBillStateType {
+        self.state.clone().unwrap_or_default()
+    }
+
+    /// Get the amount due
+    pub fn get_amount_due(&self) -> Option<&Money> {
+        self.amount_due.as_ref()
+    }
+
+    /// Get the total excluding tax
+    pub fn get_tax_excluded_amount(&self) -> &Money {
+        &self.tax_excluded_amount
+    }
+
+    /// Get the total including tax
+    pub fn get_tax_included_amount(&self) -> &Money {
+        &self.tax_included_amount
+    }
+
+    /// Get the period this bill covers
+    pub fn get_billing_period(&self) -> &TimePeriod {
+        &self.billing_period
+    }
+
+    /// Get the bill number
+    pub fn get_bill_no(&self) -> &str {
+        &self.bill_no
+    }
+
+    /// Get the date of the bill
+    pub fn get_bill_date(&self) -> Option<&DateTime> {
+        self.bill_date.as_ref()
+    }
+
+    /// Get the date payment is due
+    pub fn get_payment_due_date(&self) -> &DateTime {
+        &self.payment_due_date
+    }
+
+    /// Get the amount still to be paid
+    pub fn get_remaining_amount(&self) -> &Money {
+        &self.remaining_amount
+    }
+
+    /// Apply up to `amount` of a payment to this bill, returning the amount applied.
+    /// The bill must have been sent and is settled once nothing remains to be paid.
+    pub fn pay(&mut self, amount: &Money) -> Result<Money, TMFError> {
+        let state = self.get_state();
+        if !matches!(
+            state,
+            CustomerBillStateType::Sent | CustomerBillStateType::PartialPaid
+        ) {
+            return Err(TMFError::StateError(format!(
+                "Cannot pay a bill in state {state:?}"
+            )));
+        }
+        self.check_currency(amount)?;
+        let applied = Money {
+            unit: amount.unit.clone(),
+            value: amount
+                .value
+                .min(self.remaining_amount.value)
+                .max(Default::default()),
+        };
+        if applied.value.is_zero() {
+            return Ok(applied);
+        }
+        self.remaining_amount.value -= applied.value;
+        match self.remaining_amount.value.is_zero() {
+            true => self.set_state(CustomerBillStateType::Settled)?,
+            false => self.set_state(CustomerBillStateType::PartialPaid)?,
+        }
+        Ok(applied)
+    }
+
+    /// Reverse up to `amount` of the payments applied to this bill, e.g. for a refund,
+    /// returning the amount reversed.
+    pub fn reverse_payment(&mut self, amount: &Money) -> Result<Money, TMFError> {
+        let state = self.get_state();
+        if !matches!(
+            state,
+            CustomerBillStateType::Settled | CustomerBillStateType::PartialPaid
+        ) {
+            return Err(TMFError::StateError(format!(
+                "Cannot reverse a payment on a bill in state {state:?}"
+            )));
+        }
+        self.check_currency(amount)?;
+        let due = self
+            .amount_due
+            .as_ref()
+            .map(|d| d.value)
+            .unwrap_or(self.remaining_amount.value);
+        let paid = due - self.remaining_amount.value;
+        let reversed = Money {
+            unit: amount.unit.clone(),
+            value: amount.value.min(paid).max(Default::default()),
+        };
+        if reversed.value.is_zero() {
+            return Ok(reversed);
+        }
+        self.remaining_amount.value += reversed.value;
+        match self.remaining_amount.value >= due {
+            true => self.set_state(CustomerBillStateType::Sent)?,
+            false => self.set_state(CustomerBillStateType::PartialPaid)?,
+        }
+        Ok(reversed)
+    }
+
+    fn check_currency(&self, amount: &Money) -> Result<(), TMFError> {
+        match self.remaining_amount.unit == amount.unit {
+            true => Ok(()),
+            false => Err(TMFError::CurrencyError(format!(
+                "Bill is in {} not {}",
+                self.remaining_amount.unit, amount.unit
+            ))),
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "tmf620",
+    feature = "tmf635",
+    feature = "tmf637",
+    feature = "tmf666"
+))]
+impl From<BillCalculation> for CustomerBill {
+    fn from(value: BillCalculation) -> Self {
+        let mut bill = CustomerBill::new();
+        // A new bill can always be issued or held
+        bill.state = Some(match value.on_hold {
+            true => CustomerBillStateType::OnHold,
+            false => CustomerBillStateType::Validated,
+        });
+        bill.bill_no = bill.get_id();
+        bill.category = BILL_CATEGORY.to_string();
+        bill.bill_date = Some(value.bill_date);
+        bill.next_bill_date = value
+            .billing_period
+            .end_date_time
+            .clone()
+            .unwrap_or_default();
+        bill.billing_period = value.billing_period;
+        bill.run_type = value.run_type;
+        bill.payment_due_date = value.payment_due_date;
+        bill.amount_due = Some(value.amount_due.clone());
+        bill.remaining_amount = value.amount_due;
+        bill.tax_excluded_amount = value.tax_excluded_amount;
+        bill.tax_included_amount = value.tax_included_amount;
+        bill.billing_account = Some(vec![value.billing_account]);
+        bill.tax_item = Some(value.tax_item);
+        bill
+    }
+}
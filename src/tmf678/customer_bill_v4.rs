@@ -47,12 +47,12 @@ pub enum CustomerBillStateType {
 #[serde(rename_all = "camelCase")]
 pub struct CustomerBill {
     #[serde(skip_serializing_if = "Option::is_none")]
-    amount_due: Option<Money>,
+    pub(crate) amount_due: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    bill_date: Option<DateTime>,
-    bill_no: String,
-    billing_period: TimePeriod,
-    category: String,
+    pub(crate) bill_date: Option<DateTime>,
+    pub(crate) bill_no: String,
+    pub(crate) billing_period: TimePeriod,
+    pub(crate) category: String,
     /// Uri
     #[serde(skip_serializing_if = "Option::is_none")]
     pub href: Option<Uri>,
@@ -62,15 +62,15 @@ pub struct CustomerBill {
     /// Last update of bill
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_update: Option<DateTime>,
-    next_bill_date: DateTime,
-    payment_due_date: DateTime,
-    remaining_amount: Money,
-    run_type: CustomerBillRunType,
+    pub(crate) next_bill_date: DateTime,
+    pub(crate) payment_due_date: DateTime,
+    pub(crate) remaining_amount: Money,
+    pub(crate) run_type: CustomerBillRunType,
     /// Customer Bill Status
     #[serde(skip_serializing_if = "Option::is_none")]
     pub state: Option<CustomerBillStateType>,
-    tax_excluded_amount: Money,
-    tax_included_amount: Money,
+    pub(crate) tax_excluded_amount: Money,
+    pub(crate) tax_included_amount: Money,
 
     // Referenced Fields
     /// Related Parties
@@ -179,6 +179,37 @@ mod test {
         assert_eq!(bill.state, Some(CustomerBillStateType::default()));
     }
 
+    #[test]
+    fn test_customer_bill_set_state() {
+        let mut bill = CustomerBill::new();
+
+        assert!(bill.set_state(CustomerBillStateType::Sent).is_err());
+        assert!(bill.set_state(CustomerBillStateType::Validated).is_ok());
+        assert!(bill.set_state(CustomerBillStateType::Sent).is_ok());
+        assert_eq!(bill.get_state(), CustomerBillStateType::Sent);
+    }
+
+    #[test]
+    fn test_customer_bill_pay() {
+        let mut bill = CustomerBill::new().amount_due(Money::from(100));
+
+        assert!(bill.pay(&Money::from(10)).is_err());
+        bill.set_state(CustomerBillStateType::Validated).unwrap();
+        bill.set_state(CustomerBillStateType::Sent).unwrap();
+
+        assert_eq!(bill.pay(&Money::from(40)).unwrap(), Money::from(40));
+        assert_eq!(bill.get_state(), CustomerBillStateType::PartialPaid);
+        assert_eq!(bill.pay(&Money::from(80)).unwrap(), Money::from(60));
+        assert_eq!(bill.get_state(), CustomerBillStateType::Settled);
+
+        assert_eq!(
+            bill.reverse_payment(&Money::from(150)).unwrap(),
+            Money::from(100)
+        );
+        assert_eq!(bill.get_state(), CustomerBillStateType::Sent);
+        assert_eq!(bill.get_remaining_amount(), &Money::from(100));
+    }
+
     #[test]
     fn test_runtype_deserialize() {
         let runtype: CustomerBillRunType = serde_json::from_str(RUNTYPE_JSON).unwrap();
@@ -7,13 +7,15 @@ use serde::{Deserialize, Serialize};
 use crate::common::attachment::AttachmentRefOrValue;
 use crate::common::money::Money;
 use crate::common::related_party::RelatedParty;
+use crate::common::tax_item::TaxItem;
+use crate::tmf666::billing_account::BillingAccountRef;
 use crate::{DateTime, HasAttachment, HasId, HasLastUpdate, TimePeriod, Uri};
 use tmflib_derive::{HasId, HasLastUpdate};
 
 const CLASS_PATH: &str = "customer_bill";
 
 /// Customer Bill Run Type
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub enum CustomerBillRunType {
     /// Inside regular bill cycle
     #[default]
@@ -23,7 +25,7 @@ pub enum CustomerBillRunType {
 }
 
 /// Customer Bill Status
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub enum CustomerBillStateType {
     /// New Bill
     #[default]
@@ -43,30 +45,36 @@ pub enum CustomerBillStateType {
 /// Customer Bill
 #[derive(Clone, Debug, Default, Deserialize, HasId, HasLastUpdate, Serialize)]
 pub struct CustomerBill {
-    amount_due: Option<Money>,
-    bill_date: Option<DateTime>,
-    bill_no: String,
-    billing_period: TimePeriod,
-    category: String,
+    pub(crate) amount_due: Option<Money>,
+    pub(crate) bill_date: Option<DateTime>,
+    pub(crate) bill_no: String,
+    pub(crate) billing_period: TimePeriod,
+    pub(crate) category: String,
     /// Uri
     pub href: Option<Uri>,
     /// Unique Id
     pub id: Option<String>,
     /// Last update of bill
     pub last_update: Option<DateTime>,
-    next_bill_date: DateTime,
-    payment_due_date: DateTime,
-    remaining_amount: Money,
-    run_type: CustomerBillRunType,
-    state: Option<CustomerBillStateType>,
-    tax_excluded_amount: Money,
-    tax_included_amount: Money,
+    pub(crate) next_bill_date: DateTime,
+    pub(crate) payment_due_date: DateTime,
+    pub(crate) remaining_amount: Money,
+    pub(crate) run_type: CustomerBillRunType,
+    pub(crate) state: Option<CustomerBillStateType>,
+    pub(crate) tax_excluded_amount: Money,
+    pub(crate) tax_included_amount: Money,
 
     // Referenced Fields
     /// Related Parties
     pub related_party: Option<Vec<RelatedParty>>,
     /// Invoice / Bill documents
     pub bill_document: Option<Vec<AttachmentRefOrValue>>,
+    /// Billing Account References
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billing_account: Option<Vec<BillingAccountRef>>,
+    /// Tax Items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_item: Option<Vec<TaxItem>>,
 }
 
 impl CustomerBill {
@@ -0,0 +1,420 @@
+//! Invoice Module
+//!
+//! Renders a Customer Bill and its charges into HTML and plain text invoices, and attaches them to
+//! the bill as inline documents. Rendering only uses the bill and charges, so the same input always
+//! gives the same output.
+//!
+//! Templates use `{{field}}` placeholders. A `{{#lines}}...{{/lines}}` block is repeated for each
+//! charge and a `{{#taxes}}...{{/taxes}}` block for each tax item. Values are HTML escaped in the
+//! HTML invoice. Unknown fields or unclosed blocks are an error.
+//! - Bill fields: `billNo`, `billDate`, `periodStart`, `periodEnd`, `paymentDueDate`, `account`,
+//!   `currency`, `taxExcludedAmount`, `taxAmount`, `taxIncludedAmount`, `amountDue`
+//! - Line fields: `name`, `chargeType`, `taxExcludedAmount`, `taxAmount`, `taxIncludedAmount`
+//! - Tax fields: `category`, `rate`, `amount`
+//! ```
+//! # #[cfg(feature = "build-V4")]
+//! # use tmflib::tmf678::customer_bill_v4::CustomerBill;
+//! # #[cfg(feature = "build-V5")]
+//! # use tmflib::tmf678::customer_bill_v5::CustomerBill;
+//! use tmflib::tmf678::invoice::InvoiceRenderer;
+//!
+//! let mut bill = CustomerBill::new();
+//! let renderer = InvoiceRenderer::new().text_template("Invoice {{billNo}}: {{amountDue}}");
+//! renderer.attach(&mut bill, &[]).unwrap();
+//! assert_eq!(bill.bill_document.unwrap().len(), 2);
+//! ```
+
+use std::collections::HashMap;
+
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+
+use super::bill_run::BillCharge;
+use crate::common::attachment::AttachmentRefOrValue;
+use crate::common::money::Money;
+use crate::common::tmf_error::TMFError;
+use crate::tmf667::document::Document;
+#[cfg(feature = "build-V4")]
+use crate::tmf678::customer_bill_v4::CustomerBill;
+#[cfg(feature = "build-V5")]
+use crate::tmf678::customer_bill_v5::CustomerBill;
+use crate::HasAttachment;
+
+/// MIME type of the HTML invoice
+pub const MIME_HTML: &str = "text/html";
+/// MIME type of the plain text invoice
+pub const MIME_TEXT: &str = "text/plain";
+/// Document type of invoice documents
+pub const DOC_TYPE_INVOICE: &str = "invoice";
+
+const DEFAULT_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Invoice {{billNo}}</title>
+<style>
+body { font-family: sans-serif; }
+table { border-collapse: collapse; }
+td, th { padding: 4px 8px; text-align: left; }
+.amount { text-align: right; }
+</style>
+</head>
+<body>
+<h1>Invoice {{billNo}}</h1>
+<p>Account: {{account}}<br>
+Bill date: {{billDate}}<br>
+Period: {{periodStart}} to {{periodEnd}}<br>
+Payment due: {{paymentDueDate}}</p>
+<table>
+<tr><th>Charge</th><th>Type</th><th class="amount">Amount</th><th class="amount">Tax</th><th class="amount">Total</th></tr>
+{{#lines}}<tr><td>{{name}}</td><td>{{chargeType}}</td><td class="amount">{{taxExcludedAmount}}</td><td class="amount">{{taxAmount}}</td><td class="amount">{{taxIncludedAmount}}</td></tr>
+{{/lines}}</table>
+<table>
+{{#taxes}}<tr><td>{{category}} ({{rate}})</td><td class="amount">{{amount}}</td></tr>
+{{/taxes}}<tr><td>Total excluding tax</td><td class="amount">{{taxExcludedAmount}}</td></tr>
+<tr><td>Tax</td><td class="amount">{{taxAmount}}</td></tr>
+<tr><td>Total including tax</td><td class="amount">{{taxIncludedAmount}}</td></tr>
+<tr><th>Amount due ({{currency}})</th><th class="amount">{{amountDue}}</th></tr>
+</table>
+</body>
+</html>
+"#;
+
+const DEFAULT_TEXT: &str = "INVOICE {{billNo}}
+Account: {{account}}
+Bill date: {{billDate}}
+Period: {{periodStart}} to {{periodEnd}}
+Payment due: {{paymentDueDate}}
+
+{{#lines}}{{name}} ({{chargeType}}): {{taxExcludedAmount}} + {{taxAmount}} tax = {{taxIncludedAmount}}
+{{/lines}}
+{{#taxes}}{{category}} ({{rate}}): {{amount}}
+{{/taxes}}Total excluding tax: {{taxExcludedAmount}}
+Tax: {{taxAmount}}
+Total including tax: {{taxIncludedAmount}}
+Amount due ({{currency}}): {{amountDue}}
+";
+
+type Fields = HashMap<&'static str, String>;
+
+/// Invoice Renderer
+#[derive(Clone, Debug)]
+pub struct InvoiceRenderer {
+    html: String,
+    text: String,
+}
+
+impl Default for InvoiceRenderer {
+    fn default() -> Self {
+        InvoiceRenderer {
+            html: DEFAULT_HTML.to_string(),
+            text: DEFAULT_TEXT.to_string(),
+        }
+    }
+}
+
+impl InvoiceRenderer {
+    /// Create a renderer using the default templates
+    pub fn new() -> InvoiceRenderer {
+        InvoiceRenderer::default()
+    }
+
+    /// Builder pattern to override the HTML template
+    pub fn html_template(mut self, template: impl Into<String>) -> InvoiceRenderer {
+        self.html = template.into();
+        self
+    }
+
+    /// Builder pattern to override the plain text template
+    pub fn text_template(mut self, template: impl Into<String>) -> InvoiceRenderer {
+        self.text = template.into();
+        self
+    }
+
+    /// Render the HTML invoice
+    pub fn render_html(
+        &self,
+        bill: &CustomerBill,
+        lines: &[BillCharge],
+    ) -> Result<String, TMFError> {
+        render(&self.html, &bill_fields(bill), &blocks(bill, lines), true)
+    }
+
+    /// Render the plain text invoice
+    pub fn render_text(
+        &self,
+        bill: &CustomerBill,
+        lines: &[BillCharge],
+    ) -> Result<String, TMFError> {
+        render(&self.text, &bill_fields(bill), &blocks(bill, lines), false)
+    }
+
+    /// Render both invoices as inline attachments
+    pub fn attachments(
+        &self,
+        bill: &CustomerBill,
+        lines: &[BillCharge],
+    ) -> Result<Vec<AttachmentRefOrValue>, TMFError> {
+        let name = format!("Invoice {}", bill.get_bill_no());
+        let mut html = AttachmentRefOrValue::inline(
+            format!("{name}.html"),
+            MIME_HTML,
+            self.render_html(bill, lines)?,
+        );
+        html.description = Some(format!("{name} (HTML)"));
+        let mut text = AttachmentRefOrValue::inline(
+            format!("{name}.txt"),
+            MIME_TEXT,
+            self.render_text(bill, lines)?,
+        );
+        text.description = Some(format!("{name} (Text)"));
+        Ok(vec![html, text])
+    }
+
+    /// Render both invoices as TMF667 documents
+    pub fn documents(
+        &self,
+        bill: &CustomerBill,
+        lines: &[BillCharge],
+    ) -> Result<Vec<Document>, TMFError> {
+        Ok(self
+            .attachments(bill, lines)?
+            .into_iter()
+            .map(|a| Document::from(a).doc_type(DOC_TYPE_INVOICE))
+            .collect())
+    }
+
+    /// Render both invoices and attach them to the bill, replacing any previously attached invoice
+    pub fn attach(&self, bill: &mut CustomerBill, lines: &[BillCharge]) -> Result<(), TMFError> {
+        for attachment in self.attachments(bill, lines)? {
+            let name = attachment.name.clone().unwrap_or_default();
+            if let Some(position) = bill.position(name) {
+                bill.remove(position);
+            }
+            bill.add(&attachment);
+        }
+        Ok(())
+    }
+}
+
+fn amount(money: &Money) -> String {
+    format!("{:.2}", money.value.round_dp(2))
+}
+
+fn bill_fields(bill: &CustomerBill) -> Fields {
+    let period = bill.get_billing_period();
+    let excluded = bill.get_tax_excluded_amount();
+    let included = bill.get_tax_included_amount();
+    let tax = included.clone() - excluded.clone();
+    let account = bill
+        .billing_account
+        .iter()
+        .flatten()
+        .map(|a| a.name.clone())
+        .collect::<Vec<_>>()
+        .join(", ");
+    HashMap::from([
+        ("billNo", bill.get_bill_no().to_string()),
+        (
+            "billDate",
+            bill.get_bill_date().cloned().unwrap_or_default(),
+        ),
+        ("periodStart", period.start_date_time.clone()),
+        (
+            "periodEnd",
+            period.end_date_time.clone().unwrap_or_default(),
+        ),
+        ("paymentDueDate", bill.get_payment_due_date().clone()),
+        ("account", account),
+        ("currency", included.unit.clone()),
+        ("taxExcludedAmount", amount(excluded)),
+        ("taxAmount", amount(&tax)),
+        ("taxIncludedAmount", amount(included)),
+        (
+            "amountDue",
+            amount(bill.get_amount_due().unwrap_or(included)),
+        ),
+    ])
+}
+
+fn blocks(bill: &CustomerBill, lines: &[BillCharge]) -> HashMap<&'static str, Vec<Fields>> {
+    let lines = lines
+        .iter()
+        .map(|l| {
+            HashMap::from([
+                ("name", l.name.clone()),
+                ("chargeType", l.charge_type.clone()),
+                ("taxExcludedAmount", amount(&l.tax_excluded_amount)),
+                ("taxAmount", amount(&l.tax_amount)),
+                ("taxIncludedAmount", amount(&l.tax_included_amount())),
+            ])
+        })
+        .collect();
+    let taxes = bill
+        .tax_item
+        .iter()
+        .flatten()
+        .map(|t| {
+            let rate = Decimal::from_f32(t.tax_rate * 100.0).unwrap_or_default();
+            HashMap::from([
+                ("category", t.tax_category.clone()),
+                ("rate", format!("{}%", rate.round_dp(2).normalize())),
+                ("amount", amount(&t.tax_amount)),
+            ])
+        })
+        .collect();
+    HashMap::from([("lines", lines), ("taxes", taxes)])
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Substitute fields and expand blocks in a template
+fn render(
+    template: &str,
+    fields: &Fields,
+    blocks: &HashMap<&'static str, Vec<Fields>>,
+    html: bool,
+) -> Result<String, TMFError> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or(TMFError::GenericError(
+            "Unclosed placeholder in invoice template".to_string(),
+        ))?;
+        let key = after[..end].trim();
+        rest = &after[end + 2..];
+        if let Some(block) = key.strip_prefix('#') {
+            let close = format!("{{{{/{block}}}}}");
+            let inner_end = rest.find(&close).ok_or(TMFError::GenericError(format!(
+                "Unclosed block {block} in invoice template"
+            )))?;
+            let inner = &rest[..inner_end];
+            let items = blocks.get(block).ok_or(TMFError::GenericError(format!(
+                "Unknown block {block} in invoice template"
+            )))?;
+            for item in items {
+                out.push_str(&render(inner, item, &HashMap::new(), html)?);
+            }
+            rest = &rest[inner_end + close.len()..];
+        } else {
+            let value = fields.get(key).ok_or(TMFError::GenericError(format!(
+                "Unknown field {key} in invoice template"
+            )))?;
+            match html {
+                true => out.push_str(&escape(value)),
+                false => out.push_str(value),
+            }
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::tax_item::TaxItem;
+
+    const BILL_JSON: &str = "{
+        \"billNo\" : \"B-100\",
+        \"billDate\" : \"2024-02-01T00:00:00+00:00\",
+        \"billingPeriod\" : {
+            \"startDateTime\" : \"2024-01-01T00:00:00+00:00\",
+            \"endDateTime\" : \"2024-02-01T00:00:00+00:00\"
+        },
+        \"category\" : \"normal\",
+        \"nextBillDate\" : \"2024-03-01T00:00:00+00:00\",
+        \"paymentDueDate\" : \"2024-02-15T00:00:00+00:00\",
+        \"remainingAmount\" : { \"unit\" : \"AUD\", \"value\" : 33 },
+        \"amountDue\" : { \"unit\" : \"AUD\", \"value\" : 33 },
+        \"runType\" : \"OnCycle\",
+        \"taxExcludedAmount\" : { \"unit\" : \"AUD\", \"value\" : 30 },
+        \"taxIncludedAmount\" : { \"unit\" : \"AUD\", \"value\" : 33 },
+        \"billingAccount\" : [{ \"id\" : \"A1\", \"href\" : \"\", \"name\" : \"Smith & Co\" }]
+    }";
+
+    fn bill() -> (CustomerBill, Vec<BillCharge>) {
+        let mut bill: CustomerBill = serde_json::from_str(BILL_JSON).unwrap();
+        bill.tax_item = Some(vec![TaxItem::new("GST", 0.1, Money::from(3))]);
+        let lines = vec![BillCharge {
+            name: "Broadband - <Monthly>".to_string(),
+            charge_type: "recurring".to_string(),
+            tax_excluded_amount: Money::from(30),
+            tax_amount: Money::from(3),
+            tax_category: "GST".to_string(),
+            tax_rate: 0.1,
+            ..Default::default()
+        }];
+        (bill, lines)
+    }
+
+    #[test]
+    fn test_render_text_golden() {
+        let (bill, lines) = bill();
+
+        let text = InvoiceRenderer::new().render_text(&bill, &lines).unwrap();
+
+        assert_eq!(
+            text,
+            "INVOICE B-100
+Account: Smith & Co
+Bill date: 2024-02-01T00:00:00+00:00
+Period: 2024-01-01T00:00:00+00:00 to 2024-02-01T00:00:00+00:00
+Payment due: 2024-02-15T00:00:00+00:00
+
+Broadband - <Monthly> (recurring): 30.00 + 3.00 tax = 33.00
+
+GST (10%): 3.00
+Total excluding tax: 30.00
+Tax: 3.00
+Total including tax: 33.00
+Amount due (AUD): 33.00
+"
+        );
+    }
+
+    #[test]
+    fn test_render_html_escaped() {
+        let (bill, lines) = bill();
+
+        let html = InvoiceRenderer::new().render_html(&bill, &lines).unwrap();
+
+        assert!(html.contains("<td>Broadband - &lt;Monthly&gt;</td>"));
+        assert!(html.contains("Account: Smith &amp; Co"));
+    }
+
+    #[test]
+    fn test_attach_deterministic() {
+        let (mut bill, lines) = bill();
+        let renderer = InvoiceRenderer::new();
+
+        renderer.attach(&mut bill, &lines).unwrap();
+        let first = bill.bill_document.clone().unwrap();
+        renderer.attach(&mut bill, &lines).unwrap();
+        let second = bill.bill_document.clone().unwrap();
+
+        assert_eq!(second.len(), 2);
+        assert_eq!(first[0].content_hash, second[0].content_hash);
+        assert_eq!(first[1].mime_type, Some(MIME_TEXT.to_string()));
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let (bill, lines) = bill();
+
+        let unknown = InvoiceRenderer::new().text_template("{{unknown}}");
+        let unclosed = InvoiceRenderer::new().text_template("{{#lines}}{{name}}");
+
+        assert!(unknown.render_text(&bill, &lines).is_err());
+        assert!(unclosed.render_text(&bill, &lines).is_err());
+    }
+}
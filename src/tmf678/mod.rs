@@ -21,6 +21,26 @@
 
 const MOD_PATH: &str = "tmf678/v5";
 
+#[cfg(all(
+    feature = "tmf678",
+    feature = "tmf620",
+    feature = "tmf635",
+    feature = "tmf637",
+    feature = "tmf666"
+))]
+pub mod bill_run;
+#[cfg(all(
+    feature = "tmf678",
+    feature = "tmf620",
+    feature = "tmf635",
+    feature = "tmf637",
+    feature = "tmf666",
+    feature = "tmf667"
+))]
+pub mod invoice;
+
+#[cfg(all(feature = "tmf678", any(feature = "build-V4", feature = "build-V5")))]
+mod customer_bill;
 #[cfg(all(feature = "tmf678", feature = "build-V4"))]
 pub mod customer_bill_v4;
 
@@ -1,6 +1,7 @@
 //! Work Order Mobule V4
 
 use super::{work_order_item::WorkOrderItem, MOD_PATH};
+use crate::common::dependency::{DependencyGraph, ExecutionPlan};
 use crate::common::{note::Note, related_party::RelatedParty, tmf_error::TMFError};
 use crate::tmf646::appointment::AppointmentRef;
 use crate::{vec_insert, HasId, HasNote, HasRelatedParty, Uri};
@@ -100,6 +101,11 @@ impl WorkOrder {
         // Safely add item
         vec_insert(&mut self.work_order_item, item);
     }
+
+    /// Generate a dependency based execution plan for the items of this order
+    pub fn execution_plan(&self) -> Result<ExecutionPlan, TMFError> {
+        DependencyGraph::new(self.work_order_item.as_deref().unwrap_or_default())?.execution_plan()
+    }
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@
 //!
 //! This module defines an item as part of an overall [`super::work_order::WorkOrder`]
 
+use crate::common::dependency::{is_dependency, HasDependency, OrderItemRelationship};
 use crate::gen_code;
 use serde::{Deserialize, Serialize};
 
@@ -29,6 +30,9 @@ pub struct WorkOrderItem {
     /// Reference of Value for Work
     #[serde(skip_serializing_if = "Option::is_none")]
     pub work: Option<WorkRefOrValue>,
+    /// Relationships to other items within the same work order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_order_item_relationship: Option<Vec<OrderItemRelationship>>,
 }
 
 impl WorkOrderItem {
@@ -57,6 +61,21 @@ impl WorkOrderItem {
     }
 }
 
+impl HasDependency for WorkOrderItem {
+    fn get_item_id(&self) -> Option<String> {
+        self.id.clone()
+    }
+
+    fn get_dependencies(&self) -> Vec<String> {
+        self.work_order_item_relationship
+            .iter()
+            .flatten()
+            .filter(|r| is_dependency(&r.relationship_type))
+            .map(|r| r.id.clone())
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -2,7 +2,8 @@
 
 use super::work_order_item::WorkOrderItem;
 use super::MOD_PATH;
-use crate::common::{note::Note, related_party::RelatedParty};
+use crate::common::dependency::{DependencyGraph, ExecutionPlan};
+use crate::common::{note::Note, related_party::RelatedParty, tmf_error::TMFError};
 use crate::tmf646::appointment::AppointmentRef;
 use crate::{HasId, Uri};
 use serde::{Deserialize, Serialize};
@@ -108,4 +109,9 @@ impl WorkOrder {
             }
         }
     }
+
+    /// Generate a dependency based execution plan for the items of this order
+    pub fn execution_plan(&self) -> Result<ExecutionPlan, TMFError> {
+        DependencyGraph::new(self.work_order_item.as_deref().unwrap_or_default())?.execution_plan()
+    }
 }
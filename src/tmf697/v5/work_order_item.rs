@@ -3,6 +3,7 @@
 //! Covers the container object for inclusion in a [`WorkOrder`]
 
 use super::work::WorkRefOrValue;
+use crate::common::dependency::{is_dependency, HasDependency, OrderItemRelationship};
 use crate::gen_code;
 use serde::{Deserialize, Serialize};
 
@@ -27,6 +28,10 @@ pub struct WorkOrderItem {
     /// Reference of Value for Work
     #[serde(skip_serializing_if = "Option::is_none")]
     pub work: Option<WorkRefOrValue>,
+    /// Relationships to other items within the same work order
+    #[serde(rename = "workOrderItemRelationship")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_order_item_relationship: Option<Vec<OrderItemRelationship>>,
 }
 
 impl WorkOrderItem {
@@ -60,3 +65,18 @@ impl WorkOrderItem {
         }
     }
 }
+
+impl HasDependency for WorkOrderItem {
+    fn get_item_id(&self) -> Option<String> {
+        self.id.clone()
+    }
+
+    fn get_dependencies(&self) -> Vec<String> {
+        self.work_order_item_relationship
+            .iter()
+            .flatten()
+            .filter(|r| is_dependency(&r.relationship_type))
+            .map(|r| r.id.clone())
+            .collect()
+    }
+}
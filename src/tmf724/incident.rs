@@ -1,15 +1,19 @@
 //! Incident Module
 //!
 
+use super::priority::{PriorityMatrix, PriorityOverride};
 use super::MOD_PATH;
 use crate::common::external_identifier::ExternalIdentifier;
-use crate::{DateTime, HasId, HasName, Uri};
+use crate::common::tmf_error::TMFError;
+#[cfg(feature = "tmf642")]
+use crate::tmf642::{correlation::AlarmSummary, Alarm, PerceivedSeverity};
+use crate::{now, DateTime, HasId, HasName, Uri};
 use serde::{Deserialize, Serialize};
 use tmflib_derive::{HasId, HasName};
 const CLASS_PATH: &str = "incident";
 
 /// Incident Priority
-#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum PriorityType {
     /// Critical
     Critical,
@@ -23,7 +27,7 @@ pub enum PriorityType {
 }
 
 /// Incident Urgency
-#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum UrgencyType {
     /// Critical
     Critical,
@@ -37,7 +41,7 @@ pub enum UrgencyType {
 }
 
 /// Incident Acknowledge State
-#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum IncidentAckStateType {
     /// Acknowledged
     Acknowledged,
@@ -47,7 +51,7 @@ pub enum IncidentAckStateType {
 }
 
 /// Incident Status
-#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum IncidentStateType {
     /// Raised
     #[default]
@@ -58,8 +62,22 @@ pub enum IncidentStateType {
     Cleared,
 }
 
+impl IncidentStateType {
+    /// Returns true if an incident may move from this state to the next state.
+    /// Cleared is a terminal state.
+    pub fn can_transition(&self, next: &IncidentStateType) -> bool {
+        matches!(
+            (self, next),
+            (IncidentStateType::Raised, IncidentStateType::Updated)
+                | (IncidentStateType::Raised, IncidentStateType::Cleared)
+                | (IncidentStateType::Updated, IncidentStateType::Updated)
+                | (IncidentStateType::Updated, IncidentStateType::Cleared)
+        )
+    }
+}
+
 /// Incident Impact
-#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum ImpactType {
     /// Extensive (Highest)
     Extensive,
@@ -108,9 +126,12 @@ pub struct RootCause {
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EntityRef {
-    href: Uri,
-    id: String,
-    name: String,
+    /// Referenced Uri
+    pub href: Uri,
+    /// Referenced Id
+    pub id: String,
+    /// Referenced Name
+    pub name: String,
 }
 
 /// ITIL Incident
@@ -138,9 +159,8 @@ pub struct Incident {
     /// Id for incident
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
-    /// What is the impact of this incident?
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub impact: Option<ImpactType>,
+    impact: Option<ImpactType>,
     /// Detailed description of incident
     #[serde(skip_serializing_if = "Option::is_none")]
     pub incident_detail: Option<String>,
@@ -153,18 +173,16 @@ pub struct Incident {
     /// Incident first occurance
     #[serde(skip_serializing_if = "Option::is_none")]
     pub occur_time: Option<DateTime>,
-    /// Priority
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub priority: Option<PriorityType>,
+    priority: Option<PriorityType>,
     /// Status
     #[serde(skip_serializing_if = "Option::is_none")]
     pub state: Option<IncidentStateType>,
     /// Last update time
     #[serde(skip_serializing_if = "Option::is_none")]
     pub update_time: Option<DateTime>,
-    /// Urgency
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub urgency: Option<UrgencyType>,
+    urgency: Option<UrgencyType>,
 
     // Referenced types
     /// Extensions
@@ -188,6 +206,9 @@ pub struct Incident {
     /// External Identifiers
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_identifier: Option<Vec<ExternalIdentifier>>,
+    /// Priority forced onto this incident, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority_override: Option<PriorityOverride>,
 }
 
 impl Incident {
@@ -203,6 +224,265 @@ impl Incident {
             ..Default::default()
         }
     }
+
+    /// Get the impact of this incident
+    pub fn get_impact(&self) -> Option<ImpactType> {
+        self.impact.clone()
+    }
+
+    /// Get the urgency of this incident
+    pub fn get_urgency(&self) -> Option<UrgencyType> {
+        self.urgency.clone()
+    }
+
+    /// Get the priority of this incident
+    pub fn get_priority(&self) -> Option<PriorityType> {
+        self.priority.clone()
+    }
+
+    fn is_cleared(&self) -> bool {
+        self.state == Some(IncidentStateType::Cleared)
+    }
+
+    fn check_open(&self) -> Result<(), TMFError> {
+        match self.is_cleared() {
+            true => Err(TMFError::StateError(format!(
+                "Incident {} is cleared",
+                self.get_id()
+            ))),
+            false => Ok(()),
+        }
+    }
+
+    /// Recalculate the priority from impact and urgency, unless the priority has been overridden.
+    /// Returns the resulting priority.
+    pub fn recalculate(&mut self, matrix: &PriorityMatrix) -> PriorityType {
+        let priority = match &self.priority_override {
+            Some(o) => o.priority.clone(),
+            None => matrix.priority(
+                &self.impact.clone().unwrap_or_default(),
+                &self.urgency.clone().unwrap_or_default(),
+            ),
+        };
+        self.priority = Some(priority.clone());
+        priority
+    }
+
+    /// Change the impact, recalculating the priority
+    pub fn set_impact(
+        &mut self,
+        impact: ImpactType,
+        matrix: &PriorityMatrix,
+    ) -> Result<PriorityType, TMFError> {
+        self.check_open()?;
+        self.impact = Some(impact);
+        self.update_time = Some(now());
+        Ok(self.recalculate(matrix))
+    }
+
+    /// Change the urgency, recalculating the priority
+    pub fn set_urgency(
+        &mut self,
+        urgency: UrgencyType,
+        matrix: &PriorityMatrix,
+    ) -> Result<PriorityType, TMFError> {
+        self.check_open()?;
+        self.urgency = Some(urgency);
+        self.update_time = Some(now());
+        Ok(self.recalculate(matrix))
+    }
+
+    /// Force a priority, recording who forced it. The priority is no longer recalculated until
+    /// the override is removed with [`Incident::remove_override`].
+    pub fn override_priority(
+        &mut self,
+        priority: PriorityType,
+        user: impl Into<String>,
+        reason: Option<String>,
+    ) -> Result<(), TMFError> {
+        self.check_open()?;
+        let time = now();
+        self.priority = Some(priority.clone());
+        self.priority_override = Some(PriorityOverride {
+            priority,
+            user: user.into(),
+            reason,
+            time: time.clone(),
+        });
+        self.update_time = Some(time);
+        Ok(())
+    }
+
+    /// Remove a forced priority, returning the override and recalculating the priority
+    pub fn remove_override(&mut self, matrix: &PriorityMatrix) -> Option<PriorityOverride> {
+        let previous = self.priority_override.take();
+        self.recalculate(matrix);
+        previous
+    }
+
+    /// Move the incident to a new state, validating the transition
+    pub fn set_state(&mut self, state: IncidentStateType) -> Result<(), TMFError> {
+        let current = self.state.clone().unwrap_or_default();
+        if !current.can_transition(&state) {
+            return Err(TMFError::StateError(format!(
+                "Incident {} cannot move from {:?} to {:?}",
+                self.get_id(),
+                current,
+                state
+            )));
+        }
+        let time = now();
+        if state == IncidentStateType::Cleared {
+            self.clear_time = Some(time.clone());
+        }
+        self.state = Some(state);
+        self.update_time = Some(time);
+        Ok(())
+    }
+
+    /// Acknowledge the incident. Fails if already acknowledged or cleared.
+    pub fn acknowledge(&mut self) -> Result<(), TMFError> {
+        self.check_open()?;
+        if self.ack_state == Some(IncidentAckStateType::Acknowledged) {
+            return Err(TMFError::StateError(format!(
+                "Incident {} is already acknowledged",
+                self.get_id()
+            )));
+        }
+        self.ack_state = Some(IncidentAckStateType::Acknowledged);
+        self.ack_time = Some(now());
+        Ok(())
+    }
+
+    /// Remove the acknowledgement. Fails if not acknowledged or cleared.
+    pub fn unacknowledge(&mut self) -> Result<(), TMFError> {
+        self.check_open()?;
+        if self.ack_state != Some(IncidentAckStateType::Acknowledged) {
+            return Err(TMFError::StateError(format!(
+                "Incident {} is not acknowledged",
+                self.get_id()
+            )));
+        }
+        self.ack_state = Some(IncidentAckStateType::Unacknowledged);
+        self.ack_time = None;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tmf642")]
+impl From<&PerceivedSeverity> for PriorityType {
+    fn from(value: &PerceivedSeverity) -> Self {
+        match value {
+            PerceivedSeverity::Critical => PriorityType::Critical,
+            PerceivedSeverity::Major => PriorityType::High,
+            PerceivedSeverity::Minor => PriorityType::Medium,
+            _ => PriorityType::Low,
+        }
+    }
+}
+
+#[cfg(feature = "tmf642")]
+impl From<&PerceivedSeverity> for UrgencyType {
+    fn from(value: &PerceivedSeverity) -> Self {
+        match value {
+            PerceivedSeverity::Critical => UrgencyType::Critical,
+            PerceivedSeverity::Major => UrgencyType::High,
+            PerceivedSeverity::Minor => UrgencyType::Medium,
+            _ => UrgencyType::Low,
+        }
+    }
+}
+
+#[cfg(feature = "tmf642")]
+fn alarm_entity(alarm: &Alarm) -> ResourceEntity {
+    ResourceEntity {
+        href: Some(alarm.get_href()),
+        id: Some(alarm.get_id()),
+    }
+}
+
+#[cfg(feature = "tmf642")]
+impl Incident {
+    /// Raise an incident from a group of correlated alarms.
+    /// Urgency follows the highest alarm severity and priority is taken from the default [`PriorityMatrix`].
+    /// All alarms are referenced as events, the root cause alarm (or the first alarm if none is flagged) as the root event.
+    /// Alarmed objects become source objects and affected services become affected entities.
+    pub fn from_alarms(alarms: &[Alarm]) -> Result<Incident, TMFError> {
+        let summary = AlarmSummary::new(alarms)?;
+        let service_affecting = alarms
+            .iter()
+            .any(|a| a.service_affecting == Some(true) || !a.affected_service.is_empty());
+
+        let mut source_object: Vec<ResourceEntity> = vec![];
+        for alarm in alarms {
+            if let Some(o) = &alarm.alarmed_object {
+                if !source_object.iter().any(|s| s.id.as_ref() == Some(&o.id)) {
+                    source_object.push(ResourceEntity {
+                        href: o.href.clone(),
+                        id: Some(o.id.clone()),
+                    });
+                }
+            }
+        }
+        let affected_entity: Vec<EntityRef> = summary
+            .services
+            .iter()
+            .map(|service| EntityRef {
+                href: service.href.clone().unwrap_or_default(),
+                id: service.id.clone().unwrap_or_default(),
+                name: service.name.clone().unwrap_or_default(),
+            })
+            .collect();
+
+        let mut incident = Incident::new(summary.name.clone());
+        incident.generate_id();
+        incident.category = Some(String::from("alarm"));
+        incident.incident_detail = summary.details.clone();
+        incident.incident_resolution_suggestion = summary.root.proposed_repaired_actions.clone();
+        incident.occur_time = alarms
+            .iter()
+            .filter_map(|a| a.alarm_raised_time.clone())
+            .min();
+        incident.urgency = Some(UrgencyType::from(&summary.severity));
+        incident.impact = match service_affecting {
+            true => Some(ImpactType::Significant),
+            false => Some(ImpactType::Minor),
+        };
+        incident.recalculate(&PriorityMatrix::default());
+        incident.event_id = Some(alarms.iter().map(alarm_entity).collect());
+        incident.root_event_id = Some(vec![alarm_entity(summary.root)]);
+        incident.source_object = Some(source_object);
+        if !affected_entity.is_empty() {
+            incident.affected_entity = Some(affected_entity);
+        }
+        Ok(incident)
+    }
+
+    /// Ids of the alarms this incident was raised from
+    pub fn alarm_ids(&self) -> Vec<String> {
+        self.event_id
+            .iter()
+            .flatten()
+            .filter_map(|e| e.id.clone())
+            .collect()
+    }
+
+    /// Find the alarms this incident was raised from within a list of alarms
+    pub fn find_alarms<'a>(&self, alarms: &'a [Alarm]) -> Vec<&'a Alarm> {
+        let ids = self.alarm_ids();
+        alarms
+            .iter()
+            .filter(|a| ids.contains(&a.get_id()))
+            .collect()
+    }
+}
+
+#[cfg(feature = "tmf642")]
+impl From<&Alarm> for Incident {
+    fn from(value: &Alarm) -> Self {
+        // A single alarm always provides a root
+        Incident::from_alarms(std::slice::from_ref(value)).unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -266,6 +546,88 @@ mod test {
             Some(IncidentAckStateType::Unacknowledged)
         );
     }
+    #[cfg(feature = "tmf642")]
+    #[test]
+    fn test_incident_from_alarms() {
+        use crate::tmf642::{Alarm, AlarmType, AlarmedObjectRef, PerceivedSeverity, ServiceRef};
+
+        let mut root = Alarm::new(
+            AlarmedObjectRef::new("router-01"),
+            AlarmType::EquipmentAlarm,
+            PerceivedSeverity::Major,
+        );
+        root.is_root_cause = Some(true);
+        root.affected_service.push(ServiceRef {
+            id: Some("svc-01".to_string()),
+            ..Default::default()
+        });
+        let child = Alarm::new(
+            AlarmedObjectRef::new("router-01"),
+            AlarmType::CommunicationsAlarm,
+            PerceivedSeverity::Minor,
+        );
+        let alarms = vec![child, root.clone()];
+
+        let incident = Incident::from_alarms(&alarms).unwrap();
+
+        assert_eq!(incident.priority, Some(PriorityType::High));
+        assert_eq!(incident.urgency, Some(UrgencyType::High));
+        assert_eq!(incident.impact, Some(ImpactType::Significant));
+        assert_eq!(
+            incident.root_event_id.as_ref().unwrap()[0].id,
+            root.id.clone()
+        );
+        assert_eq!(incident.source_object.as_ref().map(|s| s.len()), Some(1));
+        assert_eq!(incident.find_alarms(&alarms).len(), 2);
+    }
+
+    #[test]
+    fn test_incident_recalculate() {
+        let matrix = PriorityMatrix::new();
+        let mut incident = Incident::new("AnIncident");
+
+        let priority = incident.set_impact(ImpactType::Extensive, &matrix).unwrap();
+        assert_eq!(priority, PriorityType::High);
+
+        let priority = incident
+            .set_urgency(UrgencyType::Critical, &matrix)
+            .unwrap();
+        assert_eq!(priority, PriorityType::Critical);
+    }
+
+    #[test]
+    fn test_incident_override() {
+        let matrix = PriorityMatrix::new();
+        let mut incident = Incident::new("AnIncident");
+
+        incident
+            .override_priority(PriorityType::Critical, "duty.manager", None)
+            .unwrap();
+        incident.set_urgency(UrgencyType::Low, &matrix).unwrap();
+        assert_eq!(incident.priority, Some(PriorityType::Critical));
+
+        let previous = incident.remove_override(&matrix).unwrap();
+        assert_eq!(previous.user.as_str(), "duty.manager");
+        assert_eq!(incident.priority, Some(PriorityType::Low));
+    }
+
+    #[test]
+    fn test_incident_state() {
+        let mut incident = Incident::new("AnIncident");
+
+        incident.set_state(IncidentStateType::Updated).unwrap();
+        incident.acknowledge().unwrap();
+        assert!(incident.acknowledge().is_err());
+        incident.set_state(IncidentStateType::Cleared).unwrap();
+
+        assert!(incident.clear_time.is_some());
+        assert!(incident.set_state(IncidentStateType::Raised).is_err());
+        assert!(incident.unacknowledge().is_err());
+        assert!(incident
+            .set_impact(ImpactType::Minor, &PriorityMatrix::new())
+            .is_err());
+    }
+
     #[test]
     fn test_priority_deserialize() {
         let priority: PriorityType = serde_json::from_str(PRIORITY_JSON).unwrap();
@@ -15,5 +15,6 @@
 //! TMF724 Incident Management
 
 pub mod incident;
+pub mod priority;
 
 const MOD_PATH: &str = "tmf724";
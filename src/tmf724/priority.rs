@@ -0,0 +1,195 @@
+//! Incident Priority Module
+//!
+//! Derives incident priority from impact and urgency using an ITIL style priority matrix.
+//! The default matrix is:
+//!
+//! | Impact \ Urgency | Critical | High     | Medium | Low    |
+//! |------------------|----------|----------|--------|--------|
+//! | Extensive        | Critical | Critical | High   | Medium |
+//! | Significant      | Critical | High     | High   | Medium |
+//! | Moderate         | High     | High     | Medium | Low    |
+//! | Minor            | High     | Medium   | Low    | Low    |
+//!
+//! A matrix can be loaded from configuration as a list of entries. Any combination not listed
+//! keeps its default priority:
+//!
+//! ```json
+//! [{ "impact": "Minor", "urgency": "Low", "priority": "Medium" }]
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::incident::{ImpactType, PriorityType, UrgencyType};
+use crate::DateTime;
+
+/// Record of a priority forced onto an incident, bypassing the priority matrix
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityOverride {
+    /// Priority that was forced
+    pub priority: PriorityType,
+    /// Who forced the priority
+    pub user: String,
+    /// Reason for the override
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// When the priority was forced
+    pub time: DateTime,
+}
+
+const IMPACTS: [ImpactType; 4] = [
+    ImpactType::Extensive,
+    ImpactType::Significant,
+    ImpactType::Moderate,
+    ImpactType::Minor,
+];
+
+const URGENCIES: [UrgencyType; 4] = [
+    UrgencyType::Critical,
+    UrgencyType::High,
+    UrgencyType::Medium,
+    UrgencyType::Low,
+];
+
+/// Single cell of a priority matrix, as loaded from configuration
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PriorityEntry {
+    /// Impact
+    pub impact: ImpactType,
+    /// Urgency
+    pub urgency: UrgencyType,
+    /// Resulting priority
+    pub priority: PriorityType,
+}
+
+/// Configurable mapping of impact and urgency to priority
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(from = "Vec<PriorityEntry>", into = "Vec<PriorityEntry>")]
+pub struct PriorityMatrix {
+    matrix: HashMap<(ImpactType, UrgencyType), PriorityType>,
+}
+
+impl Default for PriorityMatrix {
+    fn default() -> Self {
+        use PriorityType as P;
+        let rows = [
+            [P::Critical, P::Critical, P::High, P::Medium],
+            [P::Critical, P::High, P::High, P::Medium],
+            [P::High, P::High, P::Medium, P::Low],
+            [P::High, P::Medium, P::Low, P::Low],
+        ];
+        let mut matrix = HashMap::new();
+        for (impact, priorities) in IMPACTS.into_iter().zip(rows) {
+            for (urgency, priority) in URGENCIES.into_iter().zip(priorities) {
+                matrix.insert((impact.clone(), urgency), priority);
+            }
+        }
+        PriorityMatrix { matrix }
+    }
+}
+
+impl From<Vec<PriorityEntry>> for PriorityMatrix {
+    fn from(value: Vec<PriorityEntry>) -> Self {
+        value.into_iter().fold(PriorityMatrix::default(), |m, e| {
+            m.set(e.impact, e.urgency, e.priority)
+        })
+    }
+}
+
+impl From<PriorityMatrix> for Vec<PriorityEntry> {
+    fn from(value: PriorityMatrix) -> Self {
+        // Fixed order so the same matrix always writes the same configuration
+        IMPACTS
+            .into_iter()
+            .flat_map(|impact| URGENCIES.into_iter().map(move |u| (impact.clone(), u)))
+            .map(|(impact, urgency)| PriorityEntry {
+                priority: value.priority(&impact, &urgency),
+                impact,
+                urgency,
+            })
+            .collect()
+    }
+}
+
+impl PriorityMatrix {
+    /// Create the default priority matrix
+    pub fn new() -> PriorityMatrix {
+        PriorityMatrix::default()
+    }
+
+    /// Builder pattern to set the priority for a combination of impact and urgency
+    pub fn set(
+        mut self,
+        impact: ImpactType,
+        urgency: UrgencyType,
+        priority: PriorityType,
+    ) -> PriorityMatrix {
+        self.matrix.insert((impact, urgency), priority);
+        self
+    }
+
+    /// Look up the priority for an impact and urgency
+    pub fn priority(&self, impact: &ImpactType, urgency: &UrgencyType) -> PriorityType {
+        self.matrix
+            .get(&(impact.clone(), urgency.clone()))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_matrix_default() {
+        let matrix = PriorityMatrix::new();
+
+        assert_eq!(
+            matrix.priority(&ImpactType::Extensive, &UrgencyType::Critical),
+            PriorityType::Critical
+        );
+        assert_eq!(
+            matrix.priority(&ImpactType::Moderate, &UrgencyType::Medium),
+            PriorityType::Medium
+        );
+        assert_eq!(
+            matrix.priority(&ImpactType::Minor, &UrgencyType::Low),
+            PriorityType::Low
+        );
+    }
+
+    #[test]
+    fn test_matrix_set() {
+        let matrix =
+            PriorityMatrix::new().set(ImpactType::Minor, UrgencyType::Low, PriorityType::Medium);
+
+        assert_eq!(
+            matrix.priority(&ImpactType::Minor, &UrgencyType::Low),
+            PriorityType::Medium
+        );
+    }
+
+    #[test]
+    fn test_matrix_deserialize() {
+        let matrix: PriorityMatrix = serde_json::from_str(
+            r#"[{ "impact": "Minor", "urgency": "Low", "priority": "Medium" }]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            matrix.priority(&ImpactType::Minor, &UrgencyType::Low),
+            PriorityType::Medium
+        );
+        assert_eq!(
+            matrix.priority(&ImpactType::Extensive, &UrgencyType::Critical),
+            PriorityType::Critical
+        );
+
+        let json = serde_json::to_string(&matrix).unwrap();
+        let entries: Vec<PriorityEntry> = serde_json::from_str(&json).unwrap();
+        assert_eq!(entries.len(), 16);
+    }
+}
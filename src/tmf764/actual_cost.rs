@@ -3,9 +3,12 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    common::{attachment::AttachmentRefOrValue, note::Note, related_party::RelatedParty},
+    common::{
+        attachment::AttachmentRefOrValue, money::Money, note::Note, related_entity::RelatedEntity,
+        related_party::RelatedParty,
+    },
     vec_insert, HasAttachment, HasDescription, HasId, HasLastUpdate, HasName, HasNote,
-    HasRelatedParty, TMFError, Uri,
+    HasRelatedParty, TMFError, TimePeriod, Uri,
 };
 
 use tmflib_derive::{
@@ -16,7 +19,7 @@ const CLASS_PATH: &str = "ActualCost";
 use super::MOD_PATH;
 
 /// Actual Cost Item State Type
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ActualCostItemStateType {
     /// Cost Incurred
     Incurred,
@@ -31,8 +34,28 @@ pub enum ActualCostItemStateType {
     Cancelled,
 }
 
+impl ActualCostItemStateType {
+    /// Returns true if a cost item can move from this state to the next state.
+    /// Incurred costs are verified or disputed before being allocated, cancelled costs are final.
+    pub fn can_transition(&self, next: &ActualCostItemStateType) -> bool {
+        use ActualCostItemStateType::*;
+        matches!(
+            (self, next),
+            (Incurred, Verified)
+                | (Incurred, Disputed)
+                | (Incurred, Cancelled)
+                | (Disputed, Verified)
+                | (Disputed, Cancelled)
+                | (Verified, Disputed)
+                | (Verified, Allocated)
+                | (Verified, Cancelled)
+                | (Allocated, Disputed)
+        )
+    }
+}
+
 /// Cost Item Type
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CostItemType {
     /// Once of Charges
     OneTime,
@@ -45,6 +68,7 @@ pub enum CostItemType {
 
 /// Actual Cost Item
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ActualCostItem {
     /// Unique identifier
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -56,8 +80,20 @@ pub struct ActualCostItem {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     /// Quantity
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "cost_item_type", skip_serializing_if = "Option::is_none")]
     pub cost_item_type: Option<CostItemType>,
+    /// Amount of the cost
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Money>,
+    /// Period the cost applies to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_period: Option<TimePeriod>,
+    /// State of the cost
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<ActualCostItemStateType>,
+    /// Entities the cost is for, e.g. a project or order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_entity: Option<Vec<RelatedEntity>>,
 }
 
 impl ActualCostItem {
@@ -65,9 +101,51 @@ impl ActualCostItem {
     pub fn new(name: impl Into<String>) -> Self {
         Self {
             name: Some(name.into()),
+            state: Some(ActualCostItemStateType::Incurred),
             ..Default::default()
         }
     }
+
+    /// Builder pattern to set the cost item type
+    pub fn cost_type(mut self, cost_item_type: CostItemType) -> Self {
+        self.cost_item_type = Some(cost_item_type);
+        self
+    }
+
+    /// Builder pattern to set the amount
+    pub fn amount(mut self, amount: Money) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Builder pattern to set the period the cost applies to
+    pub fn period(mut self, period: TimePeriod) -> Self {
+        self.cost_period = Some(period);
+        self
+    }
+
+    /// Builder pattern to add an entity the cost is for
+    pub fn related(mut self, entity: RelatedEntity) -> Self {
+        vec_insert(&mut self.related_entity, entity);
+        self
+    }
+
+    /// Get the current state of this item
+    pub fn get_state(&self) -> ActualCostItemStateType {
+        self.state.clone().unwrap_or_default()
+    }
+
+    /// Move this item to a new state, checking the transition is allowed
+    pub fn set_state(&mut self, state: ActualCostItemStateType) -> Result<(), TMFError> {
+        let current = self.get_state();
+        if !current.can_transition(&state) {
+            return Err(TMFError::StateError(format!(
+                "Cannot move cost item from {current:?} to {state:?}"
+            )));
+        }
+        self.state = Some(state);
+        Ok(())
+    }
 }
 
 /// Cost Relationship
@@ -162,3 +240,29 @@ impl From<ActualCost> for super::CostRef {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cost_item_set_state() {
+        let mut item = ActualCostItem::new("Labour");
+
+        assert_eq!(item.get_state(), ActualCostItemStateType::Incurred);
+        assert!(item.set_state(ActualCostItemStateType::Allocated).is_err());
+        assert!(item.set_state(ActualCostItemStateType::Verified).is_ok());
+        assert!(item.set_state(ActualCostItemStateType::Allocated).is_ok());
+        assert!(item.set_state(ActualCostItemStateType::Cancelled).is_err());
+    }
+
+    #[test]
+    fn test_cost_item_type_serde() {
+        let item: ActualCostItem =
+            serde_json::from_str(r#"{"name":"Labour","cost_item_type":"OneTime"}"#).unwrap();
+        assert_eq!(item.cost_item_type, Some(CostItemType::OneTime));
+
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(json.contains(r#""costItemType":"OneTime""#));
+    }
+}
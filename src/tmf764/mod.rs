@@ -20,6 +20,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod actual_cost;
 pub mod projected_cost;
+pub mod variance;
 
 use crate::Uri;
 
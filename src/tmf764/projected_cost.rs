@@ -3,11 +3,15 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    common::{attachment::AttachmentRefOrValue, note::Note, related_party::RelatedParty},
+    common::{
+        attachment::AttachmentRefOrValue, money::Money, note::Note, related_entity::RelatedEntity,
+        related_party::RelatedParty,
+    },
     vec_insert, HasAttachment, HasDescription, HasId, HasLastUpdate, HasName, HasNote,
-    HasRelatedParty, TMFError, Uri,
+    HasRelatedParty, TMFError, TimePeriod, Uri,
 };
 
+use super::actual_cost::CostItemType;
 use super::MOD_PATH;
 use tmflib_derive::{
     HasAttachment, HasDescription, HasId, HasLastUpdate, HasName, HasNote, HasRelatedParty,
@@ -17,6 +21,7 @@ const CLASS_PATH: &str = "ProjectedCost";
 
 /// Projected Cost Item
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ProjectedCostItem {
     /// Unique identifier
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -27,6 +32,52 @@ pub struct ProjectedCostItem {
     /// Description
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Type of cost
+    #[serde(alias = "cost_item_type", skip_serializing_if = "Option::is_none")]
+    pub cost_item_type: Option<CostItemType>,
+    /// Projected amount of the cost
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Money>,
+    /// Period the cost applies to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_period: Option<TimePeriod>,
+    /// Entities the cost is for, e.g. a project or order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_entity: Option<Vec<RelatedEntity>>,
+}
+
+impl ProjectedCostItem {
+    /// Create a new Projected Cost Item
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Builder pattern to set the cost item type
+    pub fn cost_type(mut self, cost_item_type: CostItemType) -> Self {
+        self.cost_item_type = Some(cost_item_type);
+        self
+    }
+
+    /// Builder pattern to set the projected amount
+    pub fn amount(mut self, amount: Money) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Builder pattern to set the period the cost applies to
+    pub fn period(mut self, period: TimePeriod) -> Self {
+        self.cost_period = Some(period);
+        self
+    }
+
+    /// Builder pattern to add an entity the cost is for
+    pub fn related(mut self, entity: RelatedEntity) -> Self {
+        vec_insert(&mut self.related_entity, entity);
+        self
+    }
 }
 
 /// Projected Cost
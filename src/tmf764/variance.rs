@@ -0,0 +1,418 @@
+//! Cost Variance Module
+//!
+//! Rolls up projected and actual cost items and compares them.
+//! - Items are grouped by cost item type, cost period (by its start, in UTC) or related entity.
+//!   An item for several related entities counts towards each of them.
+//! - Items without an amount are ignored, as are cancelled actual cost items.
+//! - Reports can be limited to the items for one related entity, e.g. a project or order.
+//! - All amounts must use the same currency.
+//! - Variance is actual less projected, so overspend is positive. The percentage is relative to
+//!   the projected cost, and is not given when nothing was projected.
+//! ```
+//! use tmflib::common::money::Money;
+//! use tmflib::tmf764::actual_cost::{ActualCost, ActualCostItem, CostItemType};
+//! use tmflib::tmf764::projected_cost::{ProjectedCost, ProjectedCostItem};
+//! use tmflib::tmf764::variance::{variance, GroupBy};
+//!
+//! let projected = ProjectedCost::new("Budget").item(
+//!     ProjectedCostItem::new("Licences")
+//!         .cost_type(CostItemType::Recurring)
+//!         .amount(Money::from(200)),
+//! );
+//! let actual = ActualCost::new("Spend").item(
+//!     ActualCostItem::new("Licences")
+//!         .cost_type(CostItemType::Recurring)
+//!         .amount(Money::from(250)),
+//! );
+//!
+//! let report = variance(&[projected], &[actual], GroupBy::ItemType, None).unwrap();
+//! assert_eq!(report.total.variance, Money::from(50));
+//! assert_eq!(report.total.variance_percent, Some(25.into()));
+//! ```
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::actual_cost::{ActualCost, ActualCostItem, ActualCostItemStateType, CostItemType};
+use super::projected_cost::{ProjectedCost, ProjectedCostItem};
+use crate::common::money::Money;
+use crate::common::related_entity::RelatedEntity;
+use crate::common::tmf_error::TMFError;
+use crate::TimePeriod;
+
+const VARIANCE_DP: u32 = 2;
+
+/// How cost items are grouped
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum GroupBy {
+    /// By cost item type
+    #[default]
+    ItemType,
+    /// By the start of the cost period, converted to UTC
+    Period,
+    /// By related entity id
+    RelatedEntity,
+}
+
+/// Cost item that can be rolled up
+pub trait CostLine {
+    /// Type of cost
+    fn get_cost_item_type(&self) -> Option<&CostItemType>;
+    /// Amount of the cost
+    fn get_amount(&self) -> Option<&Money>;
+    /// Period the cost applies to
+    fn get_cost_period(&self) -> Option<&TimePeriod>;
+    /// Entities the cost is for
+    fn get_related_entity(&self) -> &[RelatedEntity];
+    /// Returns true if the item counts towards totals
+    fn is_counted(&self) -> bool {
+        true
+    }
+}
+
+impl CostLine for ActualCostItem {
+    fn get_cost_item_type(&self) -> Option<&CostItemType> {
+        self.cost_item_type.as_ref()
+    }
+    fn get_amount(&self) -> Option<&Money> {
+        self.amount.as_ref()
+    }
+    fn get_cost_period(&self) -> Option<&TimePeriod> {
+        self.cost_period.as_ref()
+    }
+    fn get_related_entity(&self) -> &[RelatedEntity] {
+        self.related_entity.as_deref().unwrap_or_default()
+    }
+    fn is_counted(&self) -> bool {
+        self.get_state() != ActualCostItemStateType::Cancelled
+    }
+}
+
+impl CostLine for ProjectedCostItem {
+    fn get_cost_item_type(&self) -> Option<&CostItemType> {
+        self.cost_item_type.as_ref()
+    }
+    fn get_amount(&self) -> Option<&Money> {
+        self.amount.as_ref()
+    }
+    fn get_cost_period(&self) -> Option<&TimePeriod> {
+        self.cost_period.as_ref()
+    }
+    fn get_related_entity(&self) -> &[RelatedEntity] {
+        self.related_entity.as_deref().unwrap_or_default()
+    }
+}
+
+/// Roll up cost items by group, optionally limited to the items for one related entity.
+/// Items with no value for the group, e.g. no cost period, are rolled up under `None`.
+pub fn roll_up<'a>(
+    items: impl IntoIterator<Item = &'a dyn CostLine>,
+    by: &GroupBy,
+    entity_id: Option<&str>,
+) -> Result<BTreeMap<Option<String>, Money>, TMFError> {
+    let mut totals: BTreeMap<Option<String>, Money> = BTreeMap::new();
+    let mut currency: Option<String> = None;
+    for item in items {
+        let amount = match item.get_amount() {
+            Some(a) if item.is_counted() => a,
+            _ => continue,
+        };
+        if let Some(id) = entity_id {
+            if !item.get_related_entity().iter().any(|e| e.id == id) {
+                continue;
+            }
+        }
+        match currency.as_ref() {
+            Some(c) if *c != amount.unit => {
+                return Err(TMFError::CurrencyError(format!(
+                    "Cannot roll up {} costs with {c}",
+                    amount.unit
+                )))
+            }
+            Some(_) => {}
+            None => currency = Some(amount.unit.clone()),
+        }
+        for key in keys(item, by)? {
+            let total = totals.entry(key).or_insert_with(|| Money {
+                unit: amount.unit.clone(),
+                value: Decimal::ZERO,
+            });
+            total.value += amount.value;
+        }
+    }
+    Ok(totals)
+}
+
+fn keys(item: &dyn CostLine, by: &GroupBy) -> Result<Vec<Option<String>>, TMFError> {
+    Ok(match by {
+        GroupBy::ItemType => vec![item.get_cost_item_type().map(|t| format!("{t:?}"))],
+        GroupBy::Period => match item.get_cost_period() {
+            Some(p) => vec![Some(utc(&p.start_date_time)?)],
+            None => vec![None],
+        },
+        GroupBy::RelatedEntity => match item.get_related_entity() {
+            [] => vec![None],
+            entities => entities.iter().map(|e| Some(e.id.clone())).collect(),
+        },
+    })
+}
+
+/// Normalise a timestamp to UTC so the same instant always gives the same group
+fn utc(time: &str) -> Result<String, TMFError> {
+    chrono::DateTime::parse_from_rfc3339(time)
+        .map(|t| t.with_timezone(&chrono::Utc).to_rfc3339())
+        .map_err(|e| TMFError::InvalidTimePeriod(format!("Invalid cost period start {time}: {e}")))
+}
+
+/// Projected and actual cost for a group
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostVariance {
+    /// Group, `None` for items with no value for the group, or for the report total
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Projected cost
+    pub projected: Money,
+    /// Actual cost
+    pub actual: Money,
+    /// Actual less projected cost
+    pub variance: Money,
+    /// Variance as a percentage of the projected cost
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variance_percent: Option<Decimal>,
+}
+
+impl CostVariance {
+    fn new(key: Option<String>, projected: Money, actual: Money) -> CostVariance {
+        let variance = Money {
+            unit: actual.unit.clone(),
+            value: actual.value - projected.value,
+        };
+        let variance_percent = match projected.value.is_zero() {
+            true => None,
+            false => Some(
+                (variance.value * Decimal::ONE_HUNDRED / projected.value).round_dp(VARIANCE_DP),
+            ),
+        };
+        CostVariance {
+            key,
+            projected,
+            actual,
+            variance,
+            variance_percent,
+        }
+    }
+}
+
+/// Comparison of projected and actual costs
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VarianceReport {
+    /// Related entity the report is limited to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_id: Option<String>,
+    /// How items were grouped
+    pub group_by: GroupBy,
+    /// Variance for each group
+    pub lines: Vec<CostVariance>,
+    /// Variance across all items
+    pub total: CostVariance,
+}
+
+/// Compare projected and actual costs, see the module documentation for details.
+pub fn variance(
+    projected: &[ProjectedCost],
+    actual: &[ActualCost],
+    by: GroupBy,
+    entity_id: Option<&str>,
+) -> Result<VarianceReport, TMFError> {
+    let projected_items: Vec<&dyn CostLine> = projected
+        .iter()
+        .flat_map(|c| c.cost_item.iter().flatten())
+        .map(|i| i as &dyn CostLine)
+        .collect();
+    let actual_items: Vec<&dyn CostLine> = actual
+        .iter()
+        .flat_map(|c| c.cost_item.iter().flatten())
+        .map(|i| i as &dyn CostLine)
+        .collect();
+    let projected_totals = roll_up(projected_items.iter().copied(), &by, entity_id)?;
+    let actual_totals = roll_up(actual_items.iter().copied(), &by, entity_id)?;
+
+    let unit = match (
+        projected_totals.values().next(),
+        actual_totals.values().next(),
+    ) {
+        (Some(p), Some(a)) if p.unit != a.unit => {
+            return Err(TMFError::CurrencyError(format!(
+                "Projected costs in {} but actual costs in {}",
+                p.unit, a.unit
+            )))
+        }
+        (Some(m), _) | (None, Some(m)) => m.unit.clone(),
+        (None, None) => String::default(),
+    };
+    let zero = Money {
+        unit,
+        value: Decimal::ZERO,
+    };
+
+    let mut keys: Vec<&Option<String>> = projected_totals.keys().collect();
+    keys.extend(
+        actual_totals
+            .keys()
+            .filter(|k| !projected_totals.contains_key(*k)),
+    );
+    keys.sort();
+    let lines: Vec<CostVariance> = keys
+        .into_iter()
+        .map(|k| {
+            CostVariance::new(
+                k.clone(),
+                projected_totals.get(k).unwrap_or(&zero).clone(),
+                actual_totals.get(k).unwrap_or(&zero).clone(),
+            )
+        })
+        .collect();
+
+    // Items for several related entities appear in several lines, so total the items directly.
+    // Each item has exactly one item type, so rolling up by type counts each item once.
+    let total = |items: Vec<&dyn CostLine>| -> Result<Money, TMFError> {
+        let totals = roll_up(items, &GroupBy::ItemType, entity_id)?;
+        Ok(Money {
+            unit: zero.unit.clone(),
+            value: totals.values().map(|m| m.value).sum(),
+        })
+    };
+    let total = CostVariance::new(None, total(projected_items)?, total(actual_items)?);
+
+    Ok(VarianceReport {
+        entity_id: entity_id.map(|e| e.to_string()),
+        group_by: by,
+        lines,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entity(id: &str) -> RelatedEntity {
+        RelatedEntity {
+            id: id.to_string(),
+            referred_type: "Project".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn month(start: &str) -> TimePeriod {
+        TimePeriod {
+            start_date_time: start.to_string(),
+            end_date_time: None,
+        }
+    }
+
+    #[test]
+    fn test_variance_by_entity() {
+        let projected = [ProjectedCost::new("Budget")
+            .item(
+                ProjectedCostItem::new("Build")
+                    .amount(Money::from(100))
+                    .related(entity("P1")),
+            )
+            .item(
+                ProjectedCostItem::new("Run")
+                    .amount(Money::from(40))
+                    .related(entity("P2")),
+            )];
+        let mut cancelled = ActualCostItem::new("Cancelled")
+            .amount(Money::from(1000))
+            .related(entity("P1"));
+        cancelled
+            .set_state(ActualCostItemStateType::Cancelled)
+            .unwrap();
+        let actual = [ActualCost::new("Spend")
+            .item(
+                ActualCostItem::new("Build")
+                    .amount(Money::from(90))
+                    .related(entity("P1"))
+                    .related(entity("P2")),
+            )
+            .item(cancelled)];
+
+        let report = variance(&projected, &actual, GroupBy::RelatedEntity, None).unwrap();
+
+        assert_eq!(report.lines.len(), 2);
+        assert_eq!(report.lines[0].variance, Money::from(-10));
+        assert_eq!(report.lines[0].variance_percent, Some(Decimal::from(-10)));
+        assert_eq!(report.lines[1].variance, Money::from(50));
+        // The shared item is only counted once in the total
+        assert_eq!(report.total.actual, Money::from(90));
+
+        let p2 = variance(&projected, &actual, GroupBy::ItemType, Some("P2")).unwrap();
+        assert_eq!(p2.total.projected, Money::from(40));
+        assert_eq!(p2.total.actual, Money::from(90));
+    }
+
+    #[test]
+    fn test_variance_by_period() {
+        let projected = ProjectedCost::new("Budget").item(
+            ProjectedCostItem::new("Jan")
+                .amount(Money::from(100))
+                .period(month("2024-01-01T00:00:00+00:00")),
+        );
+        let actual = ActualCost::new("Spend")
+            .item(
+                ActualCostItem::new("Jan")
+                    .amount(Money::from(100))
+                    .period(month("2024-01-01T00:00:00+00:00")),
+            )
+            .item(
+                ActualCostItem::new("Jan")
+                    .amount(Money::from(20))
+                    .period(month("2024-01-01T10:00:00+10:00")),
+            )
+            .item(
+                ActualCostItem::new("Feb")
+                    .amount(Money::from(30))
+                    .period(month("2024-02-01T00:00:00+00:00")),
+            );
+
+        let report = variance(&[projected], &[actual], GroupBy::Period, None).unwrap();
+
+        // The same instant in another timezone is in the same group
+        assert_eq!(report.lines.len(), 2);
+        assert_eq!(report.lines[0].variance_percent, Some(Decimal::from(20)));
+        assert_eq!(report.lines[1].variance_percent, None);
+        assert_eq!(report.total.variance, Money::from(50));
+
+        let bad = ActualCost::new("Spend").item(
+            ActualCostItem::new("Bad")
+                .amount(Money::from(10))
+                .period(month("January")),
+        );
+        assert!(matches!(
+            variance(&[], &[bad], GroupBy::Period, None),
+            Err(TMFError::InvalidTimePeriod(_))
+        ));
+    }
+
+    #[test]
+    fn test_variance_currency_mismatch() {
+        let projected =
+            ProjectedCost::new("Budget").item(ProjectedCostItem::new("Build").amount(Money {
+                unit: "NZD".to_string(),
+                value: Decimal::from(100),
+            }));
+        let actual =
+            ActualCost::new("Spend").item(ActualCostItem::new("Build").amount(Money::from(90)));
+
+        assert!(matches!(
+            variance(&[projected], &[actual], GroupBy::ItemType, None),
+            Err(TMFError::CurrencyError(_))
+        ));
+    }
+}